@@ -0,0 +1,591 @@
+use crate::{
+    database::{
+        edge_ids, project_fields, Database, DatabaseError, DatabaseResult, QueryPlan, Removal,
+        Transaction, Traversal,
+    },
+    ent::{
+        Aggregate, Aggregator, Ent, Filter, GroupBy, GroupedAggregator, Query, TypedPredicate,
+        Value,
+    },
+    Id,
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+
+/// Represents an asynchronous database, which performs non-blocking CRUD
+/// operations using ents. This mirrors [`Database`], but each operation
+/// returns a future that can be awaited from within an async runtime.
+#[async_trait]
+pub trait AsyncDatabase: Send + Sync {
+    /// Retrieves a copy of a single, generic ent with the corresponding id
+    ///
+    /// This should not connect the ent back to the database upon return as
+    /// that decision should be made outside of the database itself.
+    async fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>>;
+
+    /// Removes the ent with the corresponding id, triggering edge
+    /// processing for all disconnected ents. Returns a boolean indicating
+    /// if an ent was removed.
+    async fn remove(&self, id: Id) -> DatabaseResult<bool>;
+
+    /// Removes the ent with the corresponding id the same way as
+    /// [`AsyncDatabase::remove`], describing every ent removed or updated as
+    /// a result, or returning none if there was no ent to remove
+    async fn remove_cascading(&self, id: Id) -> DatabaseResult<Option<Removal>> {
+        Ok(if self.remove(id).await? {
+            Some(Removal::new(vec![id], Vec::new()))
+        } else {
+            None
+        })
+    }
+
+    /// Inserts a new ent using its id as the primary index, overwriting
+    /// any ent with a matching id. If the ent's id is set to the ephemeral
+    /// id (of 0), a unique id will be assigned to the ent prior to being
+    /// inserted.
+    ///
+    /// The ent's id is returned after being inserted.
+    async fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id>;
+
+    /// Performs a retrieval of multiple ents of any type
+    async fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Finds all generic ents that match the query
+    async fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Counts the generic ents that match the query
+    async fn count(&self, query: Query) -> DatabaseResult<usize> {
+        self.find_all(query).await.map(|x| x.len())
+    }
+
+    /// Returns true if at least one generic ent matches the query
+    async fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        self.find_first(query).await.map(|x| x.is_some())
+    }
+
+    /// Finds the first generic ent that matches the query, returning none
+    /// if no ent matches
+    async fn find_first(&self, query: Query) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.find_all(query.first_only())
+            .await
+            .map(|x| x.into_iter().next())
+    }
+
+    /// Computes the aggregate over the generic ents that match the query
+    async fn aggregate(&self, query: Query, aggregate: Aggregate) -> DatabaseResult<Value> {
+        let mut aggregator = Aggregator::new(aggregate);
+        for ent in self.find_all(query).await? {
+            aggregator.push(ent.as_ref());
+        }
+        Ok(aggregator.finish())
+    }
+
+    /// Computes the aggregate separately for each group of generic ents that
+    /// match the query, returning the value identifying each group alongside
+    /// the value of the group's aggregate
+    async fn aggregate_by(
+        &self,
+        query: Query,
+        aggregate: Aggregate,
+        group_by: GroupBy,
+    ) -> DatabaseResult<Vec<(Value, Value)>> {
+        let mut aggregator = GroupedAggregator::new(aggregate, group_by);
+        for ent in self.find_all(query).await? {
+            aggregator.push(ent.as_ref());
+        }
+        Ok(aggregator.finish())
+    }
+
+    /// Finds the values of the named fields of each generic ent that matches
+    /// the query, returning the id of each ent alongside its values by name
+    async fn project(
+        &self,
+        query: Query,
+        fields: Vec<String>,
+    ) -> DatabaseResult<Vec<(Id, HashMap<String, Value>)>> {
+        Ok(self
+            .find_all(query)
+            .await?
+            .into_iter()
+            .map(|ent| (ent.id(), project_fields(ent.as_ref(), &fields)))
+            .collect())
+    }
+
+    /// Describes the steps taken to find the ents that match the query
+    /// without finding them
+    async fn explain(&self, query: Query) -> DatabaseResult<QueryPlan> {
+        Ok(QueryPlan::unoptimized(&query))
+    }
+
+    /// Finds the ids of the ents reachable from the ent with the given id by
+    /// following the edge of the same name, alongside the fewest hops taken
+    /// to reach each ent
+    async fn traverse(
+        &self,
+        id: Id,
+        edge_name: &str,
+        max_depth: Option<usize>,
+    ) -> DatabaseResult<Vec<(Id, usize)>> {
+        let mut traversal = Traversal::new(vec![id], max_depth);
+        let mut reached = Vec::new();
+        while let Some(id) = traversal.pending() {
+            let ent = self.get(id).await?;
+            reached.extend(traversal.visit(edge_ids(ent.as_deref(), edge_name)));
+        }
+        Ok(reached)
+    }
+
+    /// Finds the ids along a shortest path from one ent to another by
+    /// following the edge of the given name, including both ents
+    async fn shortest_path(
+        &self,
+        from: Id,
+        to: Id,
+        edge_name: &str,
+    ) -> DatabaseResult<Option<Vec<Id>>> {
+        if from == to {
+            return Ok(self.get(from).await?.map(|_| vec![from]));
+        }
+
+        let mut traversal = Traversal::new(vec![from], None);
+        while let Some(id) = traversal.pending() {
+            let ent = self.get(id).await?;
+            if let Some((id, _)) = traversal.visit(edge_ids(ent.as_deref(), edge_name)) {
+                if id == to {
+                    return Ok(Some(traversal.path_to(to)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the ids of all ents whose edge of the given name points at the
+    /// ent with the given id, in ascending order
+    async fn referrers(&self, id: Id, edge_name: &str) -> DatabaseResult<Vec<Id>> {
+        let query = Query::default().where_edge(edge_name, Filter::Id(TypedPredicate::equals(id)));
+        Ok(self
+            .find_all(query)
+            .await?
+            .into_iter()
+            .map(|ent| ent.id())
+            .collect())
+    }
+
+    /// Reserves a new, unique id without inserting an ent
+    ///
+    /// By default, this fails with [`DatabaseError::UnsupportedOperation`]
+    /// as only the database knows how to allocate its ids.
+    async fn reserve_id(&self) -> DatabaseResult<Id> {
+        Err(DatabaseError::UnsupportedOperation {
+            name: String::from("reserve_id"),
+        })
+    }
+
+    /// Applies all operations staged within the transaction in order such
+    /// that either every operation succeeds or none of them are applied
+    ///
+    /// By default, this fails with [`DatabaseError::UnsupportedOperation`]
+    /// as only the database knows how to apply operations atomically.
+    async fn commit_transaction(&self, _tx: Transaction) -> DatabaseResult<Vec<Id>> {
+        Err(DatabaseError::UnsupportedOperation {
+            name: String::from("commit_transaction"),
+        })
+    }
+}
+
+#[async_trait]
+pub trait AsyncDatabaseExt: AsyncDatabase {
+    /// Inserts an ent of a specific type
+    async fn insert_typed<E: Ent>(&self, ent: E) -> DatabaseResult<Id>;
+
+    /// Retrieves an ent by id with a specific type
+    async fn get_typed<E: Ent>(&self, id: Id) -> DatabaseResult<Option<E>>;
+
+    /// Retrieves ents by id with a specific type
+    async fn get_all_typed<E: Ent>(&self, ids: Vec<Id>) -> DatabaseResult<Vec<E>>;
+
+    /// Finds ents that match the specified query and are of the specified type
+    async fn find_all_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Vec<E>>;
+
+    /// Finds the first ent that matches the specified query and is of the
+    /// specified type
+    async fn find_first_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Option<E>>;
+
+    /// Stages operations within a new transaction using the given function,
+    /// committing the transaction if the function succeeds and discarding
+    /// it if the function fails
+    async fn transaction<F: FnOnce(&mut Transaction) -> DatabaseResult<()> + Send>(
+        &self,
+        f: F,
+    ) -> DatabaseResult<Vec<Id>>;
+}
+
+#[async_trait]
+impl<T: AsyncDatabase> AsyncDatabaseExt for T {
+    async fn insert_typed<E: Ent>(&self, ent: E) -> DatabaseResult<Id> {
+        self.insert(Box::from(ent)).await
+    }
+
+    async fn get_typed<E: Ent>(&self, id: Id) -> DatabaseResult<Option<E>> {
+        self.get(id)
+            .await
+            .map(|x| x.and_then(|ent| ent.to_ent::<E>()))
+    }
+
+    async fn get_all_typed<E: Ent>(&self, ids: Vec<Id>) -> DatabaseResult<Vec<E>> {
+        self.get_all(ids)
+            .await
+            .map(|x| x.into_iter().filter_map(|ent| ent.to_ent::<E>()).collect())
+    }
+
+    async fn find_all_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Vec<E>> {
+        self.find_all(query)
+            .await
+            .map(|x| x.into_iter().filter_map(|ent| ent.to_ent::<E>()).collect())
+    }
+
+    async fn find_first_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Option<E>> {
+        self.find_first(query)
+            .await
+            .map(|x| x.and_then(|ent| ent.to_ent::<E>()))
+    }
+
+    async fn transaction<F: FnOnce(&mut Transaction) -> DatabaseResult<()> + Send>(
+        &self,
+        f: F,
+    ) -> DatabaseResult<Vec<Id>> {
+        let mut tx = Transaction::new();
+        f(&mut tx)?;
+        self.commit_transaction(tx).await
+    }
+}
+
+/// Represents an adapter around a synchronous [`Database`] that implements
+/// [`AsyncDatabase`] by running each blocking operation on a dedicated
+/// thread pool, keeping the async executor free to make progress.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{AsyncDatabase, AsyncDatabaseAdapter, InmemoryDatabase, UntypedEnt};
+///
+/// let db = AsyncDatabaseAdapter::new(InmemoryDatabase::default());
+///
+/// futures::executor::block_on(async {
+///     let id = db.insert(Box::from(UntypedEnt::empty_with_id(999))).await.unwrap();
+///     assert_eq!(id, 999);
+///     assert!(db.get(999).await.unwrap().is_some());
+/// });
+/// ```
+pub struct AsyncDatabaseAdapter<D: Database>(Arc<D>);
+
+impl<D: Database> AsyncDatabaseAdapter<D> {
+    /// Wraps the given database so it can be used asynchronously
+    pub fn new(database: D) -> Self {
+        Self(Arc::new(database))
+    }
+
+    /// Wraps the given shared database so it can be used asynchronously
+    /// while still being accessible synchronously elsewhere
+    pub fn from_arc(database: Arc<D>) -> Self {
+        Self(database)
+    }
+
+    /// Returns a reference to the underlying synchronous database
+    pub fn as_database(&self) -> &D {
+        &self.0
+    }
+
+    /// Returns a shared reference to the underlying synchronous database
+    pub fn to_arc(&self) -> Arc<D> {
+        Arc::clone(&self.0)
+    }
+
+    /// Runs the given function against the underlying database on the
+    /// blocking thread pool, returning its result once complete
+    async fn unblock<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&D) -> T + Send + 'static,
+        D: 'static,
+    {
+        let database = Arc::clone(&self.0);
+        blocking::unblock(move || f(database.as_ref())).await
+    }
+}
+
+impl<D: Database> Clone for AsyncDatabaseAdapter<D> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<D: Database> From<D> for AsyncDatabaseAdapter<D> {
+    fn from(database: D) -> Self {
+        Self::new(database)
+    }
+}
+
+#[async_trait]
+impl<D: Database + 'static> AsyncDatabase for AsyncDatabaseAdapter<D> {
+    async fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.unblock(move |db| db.get(id)).await
+    }
+
+    async fn remove(&self, id: Id) -> DatabaseResult<bool> {
+        self.unblock(move |db| db.remove(id)).await
+    }
+
+    async fn remove_cascading(&self, id: Id) -> DatabaseResult<Option<Removal>> {
+        self.unblock(move |db| db.remove_cascading(id)).await
+    }
+
+    async fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        self.unblock(move |db| db.insert(ent)).await
+    }
+
+    async fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.unblock(move |db| db.get_all(ids)).await
+    }
+
+    async fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.unblock(move |db| db.find_all(query)).await
+    }
+
+    async fn count(&self, query: Query) -> DatabaseResult<usize> {
+        self.unblock(move |db| db.count(query)).await
+    }
+
+    async fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        self.unblock(move |db| db.exists(query)).await
+    }
+
+    async fn find_first(&self, query: Query) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.unblock(move |db| db.find_first(query)).await
+    }
+
+    async fn aggregate(&self, query: Query, aggregate: Aggregate) -> DatabaseResult<Value> {
+        self.unblock(move |db| db.aggregate(query, aggregate)).await
+    }
+
+    async fn aggregate_by(
+        &self,
+        query: Query,
+        aggregate: Aggregate,
+        group_by: GroupBy,
+    ) -> DatabaseResult<Vec<(Value, Value)>> {
+        self.unblock(move |db| db.aggregate_by(query, aggregate, group_by))
+            .await
+    }
+
+    async fn project(
+        &self,
+        query: Query,
+        fields: Vec<String>,
+    ) -> DatabaseResult<Vec<(Id, HashMap<String, Value>)>> {
+        self.unblock(move |db| db.project(query, fields)).await
+    }
+
+    async fn explain(&self, query: Query) -> DatabaseResult<QueryPlan> {
+        self.unblock(move |db| db.explain(query)).await
+    }
+
+    async fn traverse(
+        &self,
+        id: Id,
+        edge_name: &str,
+        max_depth: Option<usize>,
+    ) -> DatabaseResult<Vec<(Id, usize)>> {
+        let edge_name = edge_name.to_string();
+        self.unblock(move |db| db.traverse(id, &edge_name, max_depth))
+            .await
+    }
+
+    async fn shortest_path(
+        &self,
+        from: Id,
+        to: Id,
+        edge_name: &str,
+    ) -> DatabaseResult<Option<Vec<Id>>> {
+        let edge_name = edge_name.to_string();
+        self.unblock(move |db| db.shortest_path(from, to, &edge_name))
+            .await
+    }
+
+    async fn referrers(&self, id: Id, edge_name: &str) -> DatabaseResult<Vec<Id>> {
+        let edge_name = edge_name.to_string();
+        self.unblock(move |db| db.referrers(id, &edge_name)).await
+    }
+
+    async fn reserve_id(&self) -> DatabaseResult<Id> {
+        self.unblock(Database::reserve_id).await
+    }
+
+    async fn commit_transaction(&self, tx: Transaction) -> DatabaseResult<Vec<Id>> {
+        self.unblock(move |db| db.commit_transaction(tx)).await
+    }
+}
+
+#[cfg(all(test, feature = "inmemory_db"))]
+mod tests {
+    use super::*;
+    use crate::{
+        AsyncEntExt, DatabaseExt, DatabaseRc, Edge, InmemoryDatabase, TypedPredicate as P,
+        UntypedEnt, EPHEMERAL_ID,
+    };
+    use futures::executor::block_on;
+
+    #[test]
+    fn adapter_should_perform_operations_against_the_wrapped_database() {
+        let db = AsyncDatabaseAdapter::new(InmemoryDatabase::default());
+
+        block_on(async {
+            let id = db
+                .insert(Box::from(UntypedEnt::empty_with_id(999)))
+                .await
+                .expect("Failed to insert ent");
+            assert_eq!(id, 999);
+
+            let ent = db.get(999).await.expect("Failed to get ent");
+            assert_eq!(ent.map(|ent| ent.id()), Some(999));
+
+            let ents = db
+                .get_all(vec![999, 1000])
+                .await
+                .expect("Failed to get ents");
+            assert_eq!(ents.len(), 1);
+
+            let ents = db
+                .find_all(Query::default().where_id(P::equals(999)))
+                .await
+                .expect("Failed to find ents");
+            assert_eq!(ents.len(), 1);
+
+            assert!(db.remove(999).await.expect("Failed to remove ent"));
+            assert!(db.get(999).await.expect("Failed to get ent").is_none());
+        });
+
+        // Changes should be visible to the synchronous database as well
+        assert!(db.as_database().get(999).unwrap().is_none());
+    }
+
+    #[test]
+    fn adapter_should_support_typed_operations() {
+        let db = AsyncDatabaseAdapter::new(InmemoryDatabase::default());
+
+        block_on(async {
+            let id = db
+                .insert_typed(UntypedEnt::empty_with_id(999))
+                .await
+                .expect("Failed to insert ent");
+
+            let ent: Option<UntypedEnt> = db.get_typed(id).await.expect("Failed to get ent");
+            assert_eq!(ent.map(|ent| ent.id()), Some(999));
+        });
+
+        assert!(db
+            .as_database()
+            .get_typed::<UntypedEnt>(999)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn adapter_should_support_transactions() {
+        let db = AsyncDatabaseAdapter::new(InmemoryDatabase::default());
+
+        block_on(async {
+            let id = db.reserve_id().await.expect("Failed to reserve id");
+
+            let ids = db
+                .transaction(|tx| {
+                    tx.insert(Box::from(UntypedEnt::empty_with_id(id)));
+                    Ok(())
+                })
+                .await
+                .expect("Failed to commit transaction");
+            assert_eq!(ids, vec![id]);
+            assert!(db.get(id).await.unwrap().is_some());
+        });
+    }
+
+    /// Asynchronous database that only implements the required methods
+    struct MinimalAsyncDatabase(AsyncDatabaseAdapter<InmemoryDatabase>);
+
+    #[async_trait]
+    impl AsyncDatabase for MinimalAsyncDatabase {
+        async fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+            self.0.get(id).await
+        }
+
+        async fn remove(&self, id: Id) -> DatabaseResult<bool> {
+            self.0.remove(id).await
+        }
+
+        async fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+            self.0.insert(ent).await
+        }
+
+        async fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+            self.0.get_all(ids).await
+        }
+
+        async fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+            self.0.find_all(query).await
+        }
+    }
+
+    #[test]
+    fn reserve_id_and_commit_transaction_should_fail_by_default() {
+        let db = MinimalAsyncDatabase(AsyncDatabaseAdapter::new(InmemoryDatabase::default()));
+
+        block_on(async {
+            assert!(matches!(
+                db.reserve_id().await,
+                Err(DatabaseError::UnsupportedOperation { .. })
+            ));
+
+            let result = db
+                .transaction(|tx| {
+                    tx.insert(Box::from(UntypedEnt::empty_with_id(1)));
+                    Ok(())
+                })
+                .await;
+            assert!(matches!(
+                result,
+                Err(DatabaseError::UnsupportedOperation { .. })
+            ));
+            assert!(db.get(1).await.unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn async_ent_ext_should_perform_operations_against_connected_database() {
+        let db = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+        let _ = db
+            .insert(Box::from(UntypedEnt::empty_with_id(1000)))
+            .unwrap();
+
+        let mut ent =
+            UntypedEnt::from_collections(EPHEMERAL_ID, vec![], vec![Edge::new("other", 1000)]);
+        ent.connect(DatabaseRc::downgrade(&db));
+
+        block_on(async {
+            ent.commit_async().await.expect("Failed to commit ent");
+            assert_ne!(ent.id(), EPHEMERAL_ID);
+            assert!(db.get(ent.id()).unwrap().is_some());
+
+            ent.refresh_async().await.expect("Failed to refresh ent");
+
+            let ents: Vec<UntypedEnt> = ent
+                .load_edge_typed_async("other")
+                .await
+                .expect("Failed to load edge");
+            assert_eq!(ents.len(), 1);
+            assert_eq!(ents[0].id(), 1000);
+
+            assert!(ent.remove_async().await.expect("Failed to remove ent"));
+            assert!(db.get(ent.id()).unwrap().is_none());
+        });
+    }
+}
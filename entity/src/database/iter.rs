@@ -0,0 +1,63 @@
+use crate::{
+    database::DatabaseResult,
+    ent::{Cursor, Ent, Order, Query},
+};
+use std::fmt;
+
+/// Represents an iterator over the ents found by a query, which keeps track
+/// of the position of the last ent returned so that the query can be resumed
+/// later using [`Query::after`]
+pub struct FindIter<'a> {
+    inner: Box<dyn Iterator<Item = DatabaseResult<Box<dyn Ent>>> + 'a>,
+    orders: Vec<Order>,
+    cursor: Option<Cursor>,
+}
+
+impl<'a> FindIter<'a> {
+    /// Creates a new iterator over the results of the query, which are
+    /// expected to already be ordered, positioned after the cursor, and
+    /// windowed as the query describes
+    pub fn new<I>(query: &Query, iter: I) -> Self
+    where
+        I: Iterator<Item = DatabaseResult<Box<dyn Ent>>> + 'a,
+    {
+        Self {
+            inner: Box::new(iter),
+            orders: query.orders().to_vec(),
+            cursor: query.results_after().cloned(),
+        }
+    }
+
+    /// Returns the position of the last ent returned, or the position the
+    /// query started from if no ent has been returned yet
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.cursor.as_ref()
+    }
+
+    /// Consumes the iterator, returning the position of the last ent
+    /// returned so that iteration can be continued later
+    pub fn into_cursor(self) -> Option<Cursor> {
+        self.cursor
+    }
+}
+
+impl<'a> Iterator for FindIter<'a> {
+    type Item = DatabaseResult<Box<dyn Ent>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.inner.next();
+        if let Some(Ok(ent)) = next.as_ref() {
+            self.cursor = Some(Cursor::from_ent(&self.orders, ent.as_ref()));
+        }
+        next
+    }
+}
+
+impl<'a> fmt::Debug for FindIter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FindIter")
+            .field("orders", &self.orders)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
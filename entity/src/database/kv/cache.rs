@@ -0,0 +1,80 @@
+use super::KeyValueDatabase;
+use crate::{database::DatabaseResult, Ent, Id};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Holds the ents loaded from a database while executing a single query so
+/// that each ent is retrieved and decoded at most once, no matter how many
+/// filters check it
+///
+/// Ents that cannot be retrieved, whether they do not exist or fail to load,
+/// are remembered as missing.
+pub(crate) struct EntCache<'a, D: KeyValueDatabase> {
+    db: &'a D,
+    ents: Mutex<HashMap<Id, Option<Arc<dyn Ent>>>>,
+}
+
+impl<'a, D: KeyValueDatabase> EntCache<'a, D> {
+    /// Creates an empty cache of ents from the database
+    pub fn new(db: &'a D) -> Self {
+        Self {
+            db,
+            ents: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the ent with the given id, loading it from the database if
+    /// it has not been loaded already
+    pub fn get(&self, id: Id) -> Option<Arc<dyn Ent>> {
+        if let Some(ent) = self.ents.lock().unwrap().get(&id) {
+            return ent.clone();
+        }
+
+        // NOTE: The lock is not held while loading so other threads are
+        //       free to load other ents, at the risk of an ent occasionally
+        //       being loaded more than once
+        let ent: Option<Arc<dyn Ent>> = self.db.get(id).ok().flatten().map(Arc::from);
+        self.ents.lock().unwrap().entry(id).or_insert(ent).clone()
+    }
+
+    /// Returns a copy of the ent with the given id that is independent of
+    /// the cache, loading it from the database if it has not been loaded
+    /// already, in which case any error from loading it is returned
+    pub fn get_owned(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        if let Some(ent) = self.ents.lock().unwrap().get(&id) {
+            return Ok(ent.as_ref().map(|ent| dyn_clone::clone_box(ent.as_ref())));
+        }
+        self.db.get(id)
+    }
+}
+
+#[cfg(all(test, feature = "inmemory_db"))]
+mod tests {
+    use super::*;
+    use crate::{database::Database, InmemoryDatabase, UntypedEnt};
+
+    #[test]
+    fn get_should_load_each_ent_once() {
+        let db = InmemoryDatabase::default();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        let cache = EntCache::new(&db);
+        let ent = cache.get(1).expect("Missing ent");
+        assert_eq!(ent.id(), 1);
+        assert!(cache.get(2).is_none());
+
+        // Changes made after an ent is loaded are not seen by the cache
+        let _ = db.remove(1).unwrap();
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(2))).unwrap();
+        assert!(Arc::ptr_eq(&cache.get(1).expect("Missing ent"), &ent));
+        assert!(cache.get(2).is_none());
+
+        // Ents that have not been loaded are loaded without being cached
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(3))).unwrap();
+        assert_eq!(cache.get_owned(3).unwrap().map(|ent| ent.id()), Some(3));
+        assert_eq!(cache.get_owned(1).unwrap().map(|ent| ent.id()), Some(1));
+        assert!(cache.get_owned(2).unwrap().is_none());
+    }
+}
@@ -0,0 +1,502 @@
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+use super::EntIdSet;
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+use crate::{search_value_tokens, Ent};
+use crate::{Number, NumberSign, Predicate, PrimitiveValue, Value};
+use std::{fmt, ops::Bound, str::FromStr};
+
+/// Represents a range of index keys, where each end of the range may be
+/// inclusive, exclusive, or unbounded
+pub type IndexRange = (Bound<IndexKey>, Bound<IndexKey>);
+
+/// Represents a range of timestamps, where each end of the range may be
+/// inclusive, exclusive, or unbounded
+pub type TimestampRange = (Bound<u64>, Bound<u64>);
+
+/// Represents a range of totals of ids on an edge, where each end of the
+/// range may be inclusive, exclusive, or unbounded
+pub type EdgeCountRange = (Bound<usize>, Bound<usize>);
+
+/// Represents the byte encoding of a [`Value`] used as the key of a field
+/// index. Keys are ordered such that comparing the bytes of two keys matches
+/// comparing the values themselves, which allows equality and range lookups
+/// to be performed directly against the index.
+///
+/// Only primitive values, text, and optional values wrapping either are
+/// able to be represented as a key.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IndexKey(Vec<u8>);
+
+/// Tags placed at the front of each key to separate values that cannot be
+/// compared against one another
+const TAG_NONE: u8 = 0;
+const TAG_UNIT: u8 = 1;
+const TAG_BOOL: u8 = 2;
+const TAG_NUMBER: u8 = 3;
+const TAG_TEXT: u8 = 4;
+
+impl IndexKey {
+    /// Produces a key from the given value, returning none if the value
+    /// cannot be indexed
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let mut bytes = Vec::new();
+        match value {
+            Value::Optional(None) => bytes.push(TAG_NONE),
+            Value::Optional(Some(x)) => return Self::from_value(x),
+            Value::Primitive(PrimitiveValue::Unit) => bytes.push(TAG_UNIT),
+            Value::Primitive(PrimitiveValue::Bool(x)) => {
+                bytes.push(TAG_BOOL);
+                bytes.push(*x as u8);
+            }
+            Value::Primitive(PrimitiveValue::Number(x)) => {
+                bytes.push(TAG_NUMBER);
+                bytes.extend(number_to_bytes(x)?);
+            }
+
+            // NOTE: Chars are compared against text by converting them into
+            //       strings, so we encode them the same way
+            Value::Primitive(PrimitiveValue::Char(x)) => {
+                bytes.push(TAG_TEXT);
+                bytes.extend(x.to_string().into_bytes());
+            }
+            Value::Text(x) => {
+                bytes.push(TAG_TEXT);
+                bytes.extend(x.as_bytes());
+            }
+            Value::List(_) | Value::Map(_) => return None,
+        }
+        Some(Self(bytes))
+    }
+
+    /// Produces a key from raw bytes previously acquired from
+    /// [`IndexKey::as_bytes`]
+    pub fn from_bytes<B: Into<Vec<u8>>>(bytes: B) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Returns the underlying bytes of the key
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the smallest key that can be compared against this key
+    fn first_comparable(&self) -> Self {
+        Self(self.0.iter().take(1).copied().collect())
+    }
+
+    /// Returns the key just beyond the largest key that can be compared
+    /// against this key
+    fn beyond_last_comparable(&self) -> Self {
+        Self(self.0.iter().take(1).map(|tag| tag + 1).collect())
+    }
+
+    /// Returns true if this key can be compared against the other key
+    fn is_comparable_with(&self, other: &Self) -> bool {
+        self.0.first() == other.0.first()
+    }
+}
+
+impl fmt::Debug for IndexKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IndexKey({})", self)
+    }
+}
+
+/// Displays the key as a hex string, which can be parsed back into a key
+impl fmt::Display for IndexKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for IndexKey {
+    type Err = &'static str;
+
+    /// Parses a key from a hex string produced by displaying a key
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .filter(|x| x.len() == 2)
+                    .and_then(|x| u8::from_str_radix(x, 16).ok())
+                    .ok_or("Index key is not valid hex")
+            })
+            .collect::<Result<Vec<u8>, Self::Err>>()
+            .map(Self)
+    }
+}
+
+/// Serialized as a hex string so the key can be used within maps of formats
+/// that only support text keys
+#[cfg(feature = "serde-1")]
+impl serde::Serialize for IndexKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde-1")]
+impl<'de> serde::Deserialize<'de> for IndexKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Encodes a number by its sign followed by its magnitude such that the
+/// ordering of the bytes mirrors the ordering of [`Number`]; numbers that
+/// cannot be compared (such as NaN) cannot be encoded
+fn number_to_bytes(number: &Number) -> Option<Vec<u8>> {
+    if !number.is_normal() && !number.is_zero() {
+        return None;
+    }
+
+    let magnitude = number.to_absolute().to_u128();
+    let mut bytes = Vec::with_capacity(17);
+    match number.sign() {
+        NumberSign::Negative => {
+            bytes.push(0);
+            bytes.extend(&(!magnitude).to_be_bytes());
+        }
+        NumberSign::Zero => {
+            bytes.push(1);
+            bytes.extend(&0u128.to_be_bytes());
+        }
+        NumberSign::Positive => {
+            bytes.push(2);
+            bytes.extend(&magnitude.to_be_bytes());
+        }
+    }
+    Some(bytes)
+}
+
+/// Represents the fields of an ent as seen by a field index
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+#[derive(Debug, Default)]
+pub struct EntFieldIndexKeys {
+    /// Names of fields marked as indexed alongside the keys of their values;
+    /// indexed fields whose values cannot be represented as keys are excluded
+    pub indexed: Vec<(String, IndexKey)>,
+
+    /// Names of fields that are not marked as indexed
+    pub unindexed: Vec<String>,
+
+    /// Names of fields marked as searchable alongside the distinct words of
+    /// the text within their values
+    pub searchable: Vec<(String, Vec<String>)>,
+
+    /// Names of fields that are not marked as searchable
+    pub unsearchable: Vec<String>,
+}
+
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+impl EntFieldIndexKeys {
+    /// Collects the index keys for the fields of the given ent
+    pub fn from_ent(ent: &dyn Ent) -> Self {
+        let mut keys = Self::default();
+        for def in ent.field_definitions() {
+            if def.is_indexed() {
+                if let Some(key) = ent
+                    .field(def.name())
+                    .as_ref()
+                    .and_then(IndexKey::from_value)
+                {
+                    keys.indexed.push((def.name().to_string(), key));
+                }
+            } else {
+                keys.unindexed.push(def.name().to_string());
+            }
+
+            if def.is_searchable() {
+                let mut tokens = ent
+                    .field(def.name())
+                    .map(|value| search_value_tokens(&value))
+                    .unwrap_or_default();
+                tokens.sort_unstable();
+                tokens.dedup();
+                keys.searchable.push((def.name().to_string(), tokens));
+            } else {
+                keys.unsearchable.push(def.name().to_string());
+            }
+        }
+        keys
+    }
+}
+
+/// Collects the name of each edge of the given ent alongside the total ids
+/// on the edge, as seen by an edge count index
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+pub fn edge_counts(ent: &dyn Ent) -> Vec<(String, usize)> {
+    ent.edges()
+        .into_iter()
+        .map(|edge| (edge.name().to_string(), edge.to_ids().len()))
+        .collect()
+}
+
+/// Collects the name of each edge of the given ent alongside the ids of the
+/// ents it points at, as seen by a referrer index
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+pub fn edge_targets(ent: &dyn Ent) -> Vec<(String, EntIdSet)> {
+    ent.edges()
+        .into_iter()
+        .map(|edge| (edge.name().to_string(), edge.to_ids().into_iter().collect()))
+        .collect()
+}
+
+/// Converts a predicate into the ranges of an index that contain every
+/// value that could satisfy the predicate, returning none if the predicate
+/// cannot be answered by an index
+///
+/// The ranges are allowed to contain values that do not satisfy the
+/// predicate, so the predicate should still be checked against each ent
+/// found using the ranges.
+pub fn predicate_to_index_ranges(p: &Predicate) -> Option<Vec<IndexRange>> {
+    match p {
+        Predicate::Equals(v) => {
+            let key = IndexKey::from_value(v)?;
+            Some(vec![(Bound::Included(key.clone()), Bound::Included(key))])
+        }
+        Predicate::InSet(set) => set
+            .iter()
+            .map(|v| predicate_to_index_ranges(&Predicate::Equals(v.clone())))
+            .collect::<Option<Vec<Vec<IndexRange>>>>()
+            .map(|x| x.into_iter().flatten().collect()),
+        Predicate::Or(list) => list
+            .iter()
+            .map(predicate_to_index_ranges)
+            .collect::<Option<Vec<Vec<IndexRange>>>>()
+            .map(|x| x.into_iter().flatten().collect()),
+        Predicate::GreaterThan(v) => {
+            let key = IndexKey::from_value(v)?;
+            let end = key.beyond_last_comparable();
+            Some(vec![(Bound::Excluded(key), Bound::Excluded(end))])
+        }
+        Predicate::GreaterThanOrEquals(v) => {
+            let key = IndexKey::from_value(v)?;
+            let end = key.beyond_last_comparable();
+            Some(vec![(Bound::Included(key), Bound::Excluded(end))])
+        }
+        Predicate::LessThan(v) => {
+            let key = IndexKey::from_value(v)?;
+            let start = key.first_comparable();
+            Some(vec![(Bound::Included(start), Bound::Excluded(key))])
+        }
+        Predicate::LessThanOrEquals(v) => {
+            let key = IndexKey::from_value(v)?;
+            let start = key.first_comparable();
+            Some(vec![(Bound::Included(start), Bound::Included(key))])
+        }
+        Predicate::InRange(r) => {
+            let start = IndexKey::from_value(r.start())?;
+            let end = IndexKey::from_value(r.end())?;
+            if !start.is_comparable_with(&end) {
+                None
+            } else if start > end {
+                Some(Vec::new())
+            } else {
+                Some(vec![(Bound::Included(start), Bound::Included(end))])
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Converts a predicate on a timestamp into the ranges of timestamps that
+/// contain every timestamp that could satisfy the predicate, returning none
+/// if the predicate cannot be answered by a timestamp index
+///
+/// Like [`predicate_to_index_ranges`], the ranges are allowed to contain
+/// timestamps that do not satisfy the predicate.
+pub fn predicate_to_timestamp_ranges(p: &Predicate) -> Option<Vec<TimestampRange>> {
+    match p {
+        Predicate::Equals(v) => {
+            let t = value_to_timestamp(v)?;
+            Some(vec![(Bound::Included(t), Bound::Included(t))])
+        }
+        Predicate::InSet(set) => set
+            .iter()
+            .map(|v| predicate_to_timestamp_ranges(&Predicate::Equals(v.clone())))
+            .collect::<Option<Vec<Vec<TimestampRange>>>>()
+            .map(|x| x.into_iter().flatten().collect()),
+        Predicate::Or(list) => list
+            .iter()
+            .map(predicate_to_timestamp_ranges)
+            .collect::<Option<Vec<Vec<TimestampRange>>>>()
+            .map(|x| x.into_iter().flatten().collect()),
+        Predicate::GreaterThan(v) => Some(vec![(
+            Bound::Excluded(value_to_timestamp(v)?),
+            Bound::Unbounded,
+        )]),
+        Predicate::GreaterThanOrEquals(v) => Some(vec![(
+            Bound::Included(value_to_timestamp(v)?),
+            Bound::Unbounded,
+        )]),
+        Predicate::LessThan(v) => Some(vec![(
+            Bound::Unbounded,
+            Bound::Excluded(value_to_timestamp(v)?),
+        )]),
+        Predicate::LessThanOrEquals(v) => Some(vec![(
+            Bound::Unbounded,
+            Bound::Included(value_to_timestamp(v)?),
+        )]),
+        Predicate::InRange(r) => {
+            let start = value_to_timestamp(r.start())?;
+            let end = value_to_timestamp(r.end())?;
+            if start > end {
+                Some(Vec::new())
+            } else {
+                Some(vec![(Bound::Included(start), Bound::Included(end))])
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Converts a predicate on the total ids of an edge into the ranges of totals
+/// that contain every total that could satisfy the predicate, returning none
+/// if the predicate cannot be answered by an edge count index
+///
+/// Totals are whole numbers just like timestamps, so this follows the same
+/// rules as [`predicate_to_timestamp_ranges`].
+pub fn predicate_to_edge_count_ranges(p: &Predicate) -> Option<Vec<EdgeCountRange>> {
+    fn to_count_bound(bound: Bound<u64>) -> Option<Bound<usize>> {
+        use std::convert::TryFrom;
+        match bound {
+            Bound::Included(x) => usize::try_from(x).ok().map(Bound::Included),
+            Bound::Excluded(x) => usize::try_from(x).ok().map(Bound::Excluded),
+            Bound::Unbounded => Some(Bound::Unbounded),
+        }
+    }
+
+    predicate_to_timestamp_ranges(p)?
+        .into_iter()
+        .map(|(start, end)| Some((to_count_bound(start)?, to_count_bound(end)?)))
+        .collect()
+}
+
+/// Converts a value into a timestamp if it is a whole number that fits
+fn value_to_timestamp(value: &Value) -> Option<u64> {
+    match value {
+        Value::Optional(Some(x)) => value_to_timestamp(x),
+        Value::Primitive(PrimitiveValue::Number(x)) if !x.is_float() && !x.is_negative() => {
+            let t = x.to_u128();
+            if t <= u64::MAX as u128 {
+                Some(t as u64)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the range cannot contain anything, which includes ranges
+/// whose start is beyond their end
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+pub fn is_empty_range<T: Ord>(range: &(Bound<T>, Bound<T>)) -> bool {
+    match range {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end))
+        | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key<V: Into<Value>>(v: V) -> IndexKey {
+        IndexKey::from_value(&v.into()).expect("Value not indexable")
+    }
+
+    #[test]
+    fn from_value_should_preserve_ordering_of_numbers() {
+        assert!(key(-100) < key(-3));
+        assert!(key(-3) < key(0));
+        assert!(key(0) < key(3u8));
+        assert!(key(3u8) < key(100i64));
+        assert_eq!(key(3u8), key(3i128));
+    }
+
+    #[test]
+    fn from_value_should_preserve_ordering_of_text() {
+        assert!(key("a") < key("ab"));
+        assert!(key("ab") < key("b"));
+        assert_eq!(key('a'), key("a"));
+    }
+
+    #[test]
+    fn from_value_should_unwrap_optional_values() {
+        assert_eq!(key(Some(3)), key(3));
+        assert_ne!(key(None::<u8>), key(0));
+    }
+
+    #[test]
+    fn from_value_should_not_support_complex_values() {
+        assert_eq!(IndexKey::from_value(&Value::from(vec![1, 2])), None);
+        assert_eq!(IndexKey::from_value(&Value::from(f64::NAN)), None);
+    }
+
+    #[test]
+    fn from_str_should_parse_displayed_key() {
+        let k = key("hello");
+        assert_eq!(k.to_string().parse::<IndexKey>(), Ok(k));
+        assert!("abc".parse::<IndexKey>().is_err());
+        assert!("zz".parse::<IndexKey>().is_err());
+    }
+
+    #[test]
+    fn predicate_to_index_ranges_should_keep_ranges_within_comparable_values() {
+        let ranges = predicate_to_index_ranges(&Predicate::greater_than(3)).unwrap();
+        assert_eq!(ranges.len(), 1);
+        let (start, end) = &ranges[0];
+        assert_eq!(start, &Bound::Excluded(key(3)));
+        match end {
+            Bound::Excluded(end) => assert!(&key(u128::MAX) < end && end <= &key("")),
+            x => panic!("Unexpected bound: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn predicate_to_timestamp_ranges_should_support_ranges_of_whole_numbers() {
+        let ranges = predicate_to_timestamp_ranges(&Predicate::greater_than(3u64)).unwrap();
+        assert_eq!(ranges, vec![(Bound::Excluded(3), Bound::Unbounded)]);
+
+        let ranges = predicate_to_timestamp_ranges(&Predicate::in_range(3u64..=9)).unwrap();
+        assert_eq!(ranges, vec![(Bound::Included(3), Bound::Included(9))]);
+
+        let reversed = std::ops::RangeInclusive::new(9u64, 3);
+        let ranges = predicate_to_timestamp_ranges(&Predicate::in_range(reversed)).unwrap();
+        assert!(ranges.is_empty());
+
+        assert!(predicate_to_timestamp_ranges(&Predicate::less_than(3.5)).is_none());
+        assert!(predicate_to_timestamp_ranges(&Predicate::less_than(-3)).is_none());
+    }
+
+    #[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+    #[test]
+    fn is_empty_range_should_detect_ranges_that_cannot_contain_anything() {
+        assert!(!is_empty_range(&(Bound::Included(3), Bound::Included(3))));
+        assert!(is_empty_range(&(Bound::Included(3), Bound::Excluded(3))));
+        assert!(is_empty_range(&(Bound::Included(4), Bound::Included(3))));
+        assert!(!is_empty_range(&(Bound::Excluded(4), Bound::Unbounded)));
+    }
+
+    #[test]
+    fn predicate_to_index_ranges_should_fail_if_any_part_is_unsupported() {
+        let p = Predicate::equals(3) | Predicate::lambda(|_| true);
+        assert!(predicate_to_index_ranges(&p).is_none());
+
+        let p = Predicate::equals(3) | Predicate::equals(5);
+        assert_eq!(predicate_to_index_ranges(&p).map(|x| x.len()), Some(2));
+    }
+}
@@ -1,37 +1,138 @@
-use super::{EntIdSet, KeyValueDatabase, KeyValueDatabaseExecutor};
+use super::{
+    index::{self, EntFieldIndexKeys},
+    CascadeStore, EdgeCountRange, EntIdSet, IndexKey, IndexRange, KeyValueDatabase,
+    KeyValueDatabaseExecutor, TimestampRange,
+};
 use crate::{
     alloc::{IdAllocator, EPHEMERAL_ID},
-    database::{Database, DatabaseError, DatabaseResult},
-    ent::EdgeDeletionPolicy,
-    Ent, Id, Query,
+    database::{
+        project_fields, Database, DatabaseError, DatabaseResult, FindIter, QueryPlan, Removal,
+        Transaction, TransactionOp,
+    },
+    Aggregate, Ent, GroupBy, Id, OrderDirection, Query, Value,
 };
 use std::{
-    collections::{HashMap, HashSet},
-    sync::Mutex,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Bound,
+    sync::RwLock,
 };
 
 /// Represents an in-memory database that performs synchronous insertion,
 /// retrieval, and removal. If the feature `serde` is enabled, this database
-/// can be serialized and deserialized.
-#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+/// can be serialized and deserialized, where its indexes are left out and
+/// rebuilt from its ents when deserialized.
+#[cfg_attr(feature = "serde-1", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde-1", serde(from = "StoredInmemoryDatabase"))]
 pub struct InmemoryDatabase {
+    /// Ents along with everything derived from them, kept behind a single
+    /// lock so that an ent and its index entries always change together
+    state: RwLock<InmemoryState>,
+}
+
+impl Default for InmemoryDatabase {
+    /// Creates a new, empty database entry
+    fn default() -> Self {
+        Self {
+            state: RwLock::new(InmemoryState::default()),
+        }
+    }
+}
+
+/// Represents the ents of an in-memory database along with their ids and
+/// secondary indexes
+#[derive(Default)]
+struct InmemoryState {
     /// Primary ent storage
-    ents: Mutex<HashMap<Id, Box<dyn Ent>>>,
+    ents: HashMap<Id, Box<dyn Ent>>,
 
     /// Type matching from specific ents to all ids of those ents
-    ents_of_type: Mutex<HashMap<String, EntIdSet>>,
+    ents_of_type: HashMap<String, EntIdSet>,
 
     /// Id allocator for ents
-    alloc: Mutex<IdAllocator>,
+    alloc: IdAllocator,
+
+    /// Secondary indexes for fields marked as indexed, mapping the name of
+    /// each field to the ids of ents associated with each value of the field
+    field_index: HashMap<String, BTreeMap<IndexKey, EntIdSet>>,
+
+    /// Names of fields stored by at least one ent without being indexed
+    unindexed_fields: HashSet<String>,
+
+    /// Ordered index of the creation timestamp of ents to their ids
+    created_index: BTreeMap<u64, EntIdSet>,
+
+    /// Ordered index of the last updated timestamp of ents to their ids
+    last_updated_index: BTreeMap<u64, EntIdSet>,
+
+    /// Ordered indexes of the total ids on each edge, mapping the name of
+    /// each edge to the ids of ents associated with each total
+    edge_count_index: HashMap<String, BTreeMap<usize, EntIdSet>>,
+
+    /// Inverted indexes for fields marked as searchable, mapping the name of
+    /// each field to the ids of ents associated with each word of the field
+    search_index: HashMap<String, BTreeMap<String, EntIdSet>>,
+
+    /// Names of fields stored by at least one ent without being searchable
+    unsearchable_fields: HashSet<String>,
+
+    /// Reverse index of edges, mapping the id of each ent pointed at by an
+    /// edge to the name of each edge pointing at it and the ids of the ents
+    /// with that edge
+    referrer_index: HashMap<Id, HashMap<String, EntIdSet>>,
 }
 
-impl Default for InmemoryDatabase {
-    /// Creates a new, empty database entry
-    fn default() -> Self {
+/// Represents the parts of an in-memory database that are serialized, which
+/// match the fields of databases serialized before indexes existed
+#[cfg(feature = "serde-1")]
+#[derive(serde::Deserialize)]
+struct StoredInmemoryDatabase {
+    ents: HashMap<Id, Box<dyn Ent>>,
+    ents_of_type: HashMap<String, EntIdSet>,
+    alloc: IdAllocator,
+}
+
+/// Represents the parts of an in-memory database that are serialized,
+/// borrowed from the database
+#[cfg(feature = "serde-1")]
+#[derive(serde::Serialize)]
+#[serde(rename = "InmemoryDatabase")]
+struct StoredInmemoryDatabaseRef<'a> {
+    ents: &'a HashMap<Id, Box<dyn Ent>>,
+    ents_of_type: &'a HashMap<String, EntIdSet>,
+    alloc: &'a IdAllocator,
+}
+
+#[cfg(feature = "serde-1")]
+impl serde::Serialize for InmemoryDatabase {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let state = self.state.read().unwrap();
+        StoredInmemoryDatabaseRef {
+            ents: &state.ents,
+            ents_of_type: &state.ents_of_type,
+            alloc: &state.alloc,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde-1")]
+impl From<StoredInmemoryDatabase> for InmemoryDatabase {
+    /// Restores a deserialized database, rebuilding its indexes from its ents
+    fn from(stored: StoredInmemoryDatabase) -> Self {
+        let mut state = InmemoryState {
+            ents_of_type: stored.ents_of_type,
+            alloc: stored.alloc,
+            ..InmemoryState::default()
+        };
+
+        for ent in stored.ents.values() {
+            state.index(ent.as_ref());
+        }
+        state.ents = stored.ents;
+
         Self {
-            ents: Mutex::new(HashMap::new()),
-            ents_of_type: Mutex::new(HashMap::new()),
-            alloc: Mutex::new(IdAllocator::new()),
+            state: RwLock::new(state),
         }
     }
 }
@@ -45,127 +146,577 @@ impl Database for InmemoryDatabase {
         KeyValueDatabaseExecutor::from(self).find_all(query)
     }
 
+    fn find_iter(&self, query: Query) -> DatabaseResult<FindIter<'_>> {
+        KeyValueDatabaseExecutor::from(self).find_iter(query)
+    }
+
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        KeyValueDatabaseExecutor::from(self).count(query)
+    }
+
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        KeyValueDatabaseExecutor::from(self).exists(query)
+    }
+
+    fn find_first(&self, query: Query) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        KeyValueDatabaseExecutor::from(self).find_first(query)
+    }
+
+    fn aggregate(&self, query: Query, aggregate: Aggregate) -> DatabaseResult<Value> {
+        KeyValueDatabaseExecutor::from(self).aggregate(query, aggregate)
+    }
+
+    fn aggregate_by(
+        &self,
+        query: Query,
+        aggregate: Aggregate,
+        group_by: GroupBy,
+    ) -> DatabaseResult<Vec<(Value, Value)>> {
+        KeyValueDatabaseExecutor::from(self).aggregate_by(query, aggregate, group_by)
+    }
+
+    fn explain(&self, query: Query) -> DatabaseResult<QueryPlan> {
+        KeyValueDatabaseExecutor::from(self).explain(query)
+    }
+
+    fn project(
+        &self,
+        query: Query,
+        fields: Vec<String>,
+    ) -> DatabaseResult<Vec<(Id, HashMap<String, Value>)>> {
+        KeyValueDatabaseExecutor::from(self).project(query, fields)
+    }
+
+    fn referrers(&self, id: Id, edge_name: &str) -> DatabaseResult<Vec<Id>> {
+        KeyValueDatabaseExecutor::from(self).referrers(id, edge_name)
+    }
+
     fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
         Ok(self
-            .ents
-            .lock()
+            .state
+            .read()
             .unwrap()
+            .ents
             .get(&id)
             .map(|ent| dyn_clone::clone_box(ent.as_ref())))
     }
 
     fn remove(&self, id: Id) -> DatabaseResult<bool> {
-        // Remove the ent and, if it has an associated schema, we process
-        // each of the edges identified in the schema based on deletion attributes
-        if let Some(ent) = self.ents.lock().unwrap().remove(&id) {
-            for edge in ent.edges() {
-                match edge.deletion_policy() {
-                    // If shallow deletion, we only want to remove the connections
-                    // back to this ent from the corresponding ents
-                    EdgeDeletionPolicy::ShallowDelete => {
-                        for edge_id in edge.to_ids() {
-                            if let Some(ent) = self.ents.lock().unwrap().get_mut(&edge_id) {
-                                for mut edge in ent.edges() {
-                                    let _ = edge.value_mut().remove_ids(Some(edge_id));
-                                    let name = edge.name().to_string();
-                                    let _ = ent.update_edge(&name, edge.into_value());
-                                }
+        Ok(self.remove_cascading(id)?.is_some())
+    }
+
+    fn remove_cascading(&self, id: Id) -> DatabaseResult<Option<Removal>> {
+        let mut state = self.state.write().unwrap();
+        let staged = StagedChanges::new(&state);
+        let removal = super::remove_cascading(&staged, id)?;
+        let changes = staged.into_changes();
+        state.apply(changes);
+        Ok(removal)
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        let mut tx = Transaction::new();
+        tx.insert(ent);
+        Ok(self.commit_transaction(tx)?.remove(0))
+    }
+
+    fn reserve_id(&self) -> DatabaseResult<Id> {
+        self.state
+            .write()
+            .unwrap()
+            .alloc
+            .next()
+            .ok_or(DatabaseError::EntCapacityReached)
+    }
+
+    fn commit_transaction(&self, tx: Transaction) -> DatabaseResult<Vec<Id>> {
+        // NOTE: The database stays locked from staging the first operation
+        //       through applying the last, so nothing can read the changes
+        //       half-applied or write in between
+        let mut state = self.state.write().unwrap();
+
+        // Assign ids to all inserted ents up front as the allocator cannot
+        // change while operations are being staged
+        let mut ids = Vec::new();
+        let mut allocated_ids = Vec::new();
+        let mut ops = Vec::new();
+        for op in tx {
+            match op {
+                TransactionOp::Insert(ent) => {
+                    let is_ephemeral = ent.id() == EPHEMERAL_ID;
+                    match state.prepare_insert(ent) {
+                        Ok(ent) => {
+                            if is_ephemeral {
+                                allocated_ids.push(ent.id());
                             }
+                            ids.push(ent.id());
+                            ops.push(TransactionOp::Insert(ent));
                         }
-                    }
-                    // If deep deletion, we want to remove the ents connected
-                    // by the edge
-                    EdgeDeletionPolicy::DeepDelete => {
-                        for id in edge.to_ids() {
-                            let _ = self.remove(id);
+                        Err(x) => {
+                            // Return any ids we allocated back to the allocator
+                            state.alloc.extend(allocated_ids);
+                            return Err(x);
                         }
                     }
-                    // If deletion policy is nothing, then do nothing
-                    EdgeDeletionPolicy::Nothing => {}
                 }
+                TransactionOp::Remove(id) => ops.push(TransactionOp::Remove(id)),
             }
+        }
 
-            // Remove the id from our type mapping if it is there
-            self.ents_of_type
-                .lock()
-                .unwrap()
-                .entry(ent.r#type().to_string())
-                .and_modify(|e| {
-                    e.remove(&id);
-                });
-
-            // Add the id to the freed ids available in the allocator
-            self.alloc.lock().unwrap().extend(vec![id]);
-
-            Ok(true)
-        } else {
-            Ok(false)
+        // Stage every operation before applying anything so that any failure
+        // (such as a restricted removal) leaves the database untouched, where
+        // each operation sees the changes staged by the operations before it
+        let staged = StagedChanges::new(&state);
+        let result = ops.into_iter().try_for_each(|op| match op {
+            TransactionOp::Insert(ent) => {
+                let old = staged.load(ent.id())?;
+                staged.store(dyn_clone::clone_box(ent.as_ref()))?;
+                super::sync_inverse_edges(&staged, old, ent)
+            }
+            TransactionOp::Remove(id) => super::remove_cascading(&staged, id).map(|_| ()),
+        });
+
+        match result {
+            // With everything staged, none of the changes can fail
+            Ok(()) => {
+                let changes = staged.into_changes();
+                state.apply(changes);
+                Ok(ids)
+            }
+            Err(x) => {
+                // Return any ids we allocated back to the allocator
+                drop(staged);
+                state.alloc.extend(allocated_ids);
+                Err(x)
+            }
         }
     }
+}
+
+impl InmemoryState {
+    /// Prepares an ent to be stored by swapping out an ephemeral id for a
+    /// newly-allocated one and marking the ent as updated
+    fn prepare_insert(&mut self, mut ent: Box<dyn Ent>) -> DatabaseResult<Box<dyn Ent>> {
+        // Update the ent's last_updated to be the current time
+        ent.mark_updated().map_err(|e| DatabaseError::Other {
+            source: Box::from(e),
+        })?;
 
-    fn insert(&self, mut ent: Box<dyn Ent>) -> DatabaseResult<Id> {
         // Get the id of the ent, swapping out the ephemeral id
         let id = ent.id();
         let id = if id == EPHEMERAL_ID {
-            if let Some(id) = self.alloc.lock().unwrap().next() {
+            if let Some(id) = self.alloc.next() {
                 id
             } else {
                 return Err(DatabaseError::EntCapacityReached);
             }
         } else {
-            self.alloc.lock().unwrap().mark_external_id(id);
+            self.alloc.mark_external_id(id);
             id
         };
 
         // Update the ent's id to match what is actually to be used
         ent.set_id(id);
 
-        // Update the ent's last_updated to be the current time
-        ent.mark_updated().map_err(|e| DatabaseError::Other {
-            source: Box::from(e),
-        })?;
+        Ok(ent)
+    }
+
+    /// Applies changes staged for the ents with the given ids, storing each
+    /// given ent and removing the ents without one
+    fn apply(&mut self, changes: HashMap<Id, Option<Box<dyn Ent>>>) {
+        for (id, ent) in changes {
+            match ent {
+                Some(ent) => self.store(ent),
+                None => self.discard(id),
+            }
+        }
+    }
+
+    /// Stores a prepared ent, overwriting any existing ent with the same id
+    fn store(&mut self, ent: Box<dyn Ent>) {
+        let id = ent.id();
+
+        // Replace the indexes of any ent we are overwriting with our own
+        if let Some(old_ent) = self.ents.remove(&id) {
+            self.unindex(old_ent.as_ref());
+        }
+        self.index(ent.as_ref());
 
         // Add our ent's id to the set of ids associated with the ent's type
         self.ents_of_type
-            .lock()
-            .unwrap()
             .entry(ent.r#type().to_string())
-            .or_insert_with(HashSet::new)
+            .or_default()
             .insert(id);
 
         // Add our ent to the primary database
-        self.ents.lock().unwrap().insert(id, ent);
+        self.ents.insert(id, ent);
+    }
+
+    /// Removes the stored ent with the given id along with its index
+    /// entries, freeing the id
+    fn discard(&mut self, id: Id) {
+        if let Some(ent) = self.ents.remove(&id) {
+            // Remove the id from the indexes of the ent's fields, timestamps, and edges
+            self.unindex(ent.as_ref());
+
+            // Remove the id from our type mapping if it is there
+            if let Some(ids) = self.ents_of_type.get_mut(ent.r#type()) {
+                ids.remove(&id);
+            }
+        }
 
-        Ok(id)
+        // Add the id to the freed ids available in the allocator
+        self.alloc.extend(vec![id]);
+    }
+
+    /// Adds the id of the ent to the indexes of its indexed fields, its
+    /// timestamps, the totals of its edges, the words of its searchable
+    /// fields, and the ents its edges point at
+    fn index(&mut self, ent: &dyn Ent) {
+        let id = ent.id();
+        let keys = EntFieldIndexKeys::from_ent(ent);
+
+        self.created_index
+            .entry(ent.created())
+            .or_default()
+            .insert(id);
+        self.last_updated_index
+            .entry(ent.last_updated())
+            .or_default()
+            .insert(id);
+
+        for (name, key) in keys.indexed {
+            self.field_index
+                .entry(name)
+                .or_default()
+                .entry(key)
+                .or_default()
+                .insert(id);
+        }
+
+        self.unindexed_fields.extend(keys.unindexed);
+
+        for (name, count) in index::edge_counts(ent) {
+            self.edge_count_index
+                .entry(name)
+                .or_default()
+                .entry(count)
+                .or_default()
+                .insert(id);
+        }
+
+        for (name, tokens) in keys.searchable {
+            let index = self.search_index.entry(name).or_default();
+            for token in tokens {
+                index.entry(token).or_default().insert(id);
+            }
+        }
+
+        self.unsearchable_fields.extend(keys.unsearchable);
+
+        for (name, targets) in index::edge_targets(ent) {
+            for target in targets {
+                self.referrer_index
+                    .entry(target)
+                    .or_default()
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(id);
+            }
+        }
+    }
+
+    /// Removes the id of the ent from the indexes of its indexed fields, its
+    /// timestamps, the totals of its edges, the words of its searchable
+    /// fields, and the ents its edges point at
+    fn unindex(&mut self, ent: &dyn Ent) {
+        let id = ent.id();
+        let keys = EntFieldIndexKeys::from_ent(ent);
+
+        remove_from_index(&mut self.created_index, &ent.created(), id);
+        remove_from_index(&mut self.last_updated_index, &ent.last_updated(), id);
+
+        for (name, key) in keys.indexed {
+            if let Some(index) = self.field_index.get_mut(&name) {
+                remove_from_index(index, &key, id);
+            }
+        }
+
+        for (name, count) in index::edge_counts(ent) {
+            if let Some(index) = self.edge_count_index.get_mut(&name) {
+                remove_from_index(index, &count, id);
+            }
+        }
+
+        for (name, tokens) in keys.searchable {
+            if let Some(index) = self.search_index.get_mut(&name) {
+                for token in tokens {
+                    remove_from_index(index, &token, id);
+                }
+            }
+        }
+
+        for (name, targets) in index::edge_targets(ent) {
+            for target in targets {
+                if let Some(index) = self.referrer_index.get_mut(&target) {
+                    if let Some(ids) = index.get_mut(&name) {
+                        ids.remove(&id);
+                        if ids.is_empty() {
+                            index.remove(&name);
+                        }
+                    }
+                    if index.is_empty() {
+                        self.referrer_index.remove(&target);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns ids of all ents with an edge pointing at the ent with the
+    /// given id
+    fn referrer_ids(&self, id: Id) -> EntIdSet {
+        self.referrer_index
+            .get(&id)
+            .map(|index| index.values().flatten().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Removes the id from the set associated with the key in the index, removing
+/// the set entirely once it is empty
+fn remove_from_index<K: Ord>(index: &mut BTreeMap<K, EntIdSet>, key: &K, id: Id) {
+    if let Some(ids) = index.get_mut(key) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+/// Collects the ids of all keys within the range of the index
+fn ids_in_range<K: Ord>(index: &BTreeMap<K, EntIdSet>, range: (Bound<K>, Bound<K>)) -> EntIdSet {
+    if index::is_empty_range(&range) {
+        return EntIdSet::new();
+    }
+
+    index
+        .range(range)
+        .flat_map(|(_, ids)| ids)
+        .copied()
+        .collect()
+}
+
+/// Represents the changes made by operations against the database before
+/// they are applied, mapping the id of each changed ent to the ent to store
+/// or to none if the ent is to be removed
+struct StagedChanges<'a> {
+    state: &'a InmemoryState,
+    ents: RefCell<HashMap<Id, Option<Box<dyn Ent>>>>,
+}
+
+impl<'a> StagedChanges<'a> {
+    fn new(state: &'a InmemoryState) -> Self {
+        Self {
+            state,
+            ents: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the changes to apply to the database
+    fn into_changes(self) -> HashMap<Id, Option<Box<dyn Ent>>> {
+        self.ents.into_inner()
+    }
+}
+
+impl<'a> CascadeStore for StagedChanges<'a> {
+    type Error = DatabaseError;
+
+    fn load(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        let ent = match self.ents.borrow().get(&id) {
+            Some(ent) => ent.as_ref().map(|ent| dyn_clone::clone_box(ent.as_ref())),
+            None => self
+                .state
+                .ents
+                .get(&id)
+                .map(|ent| dyn_clone::clone_box(ent.as_ref())),
+        };
+        Ok(ent)
+    }
+
+    fn store(&self, ent: Box<dyn Ent>) -> DatabaseResult<()> {
+        self.ents.borrow_mut().insert(ent.id(), Some(ent));
+        Ok(())
+    }
+
+    fn delete(&self, ent: &dyn Ent) -> DatabaseResult<()> {
+        self.ents.borrow_mut().insert(ent.id(), None);
+        Ok(())
+    }
+
+    fn referrer_ids(&self, id: Id) -> DatabaseResult<EntIdSet> {
+        // Staged ents replace whatever the database knows about them
+        let ents = self.ents.borrow();
+        let mut ids: EntIdSet = self
+            .state
+            .referrer_ids(id)
+            .into_iter()
+            .filter(|referrer_id| !ents.contains_key(referrer_id))
+            .collect();
+        for (referrer_id, ent) in ents.iter() {
+            if let Some(ent) = ent {
+                if index::edge_targets(ent.as_ref())
+                    .iter()
+                    .any(|(_, targets)| targets.contains(&id))
+                {
+                    ids.insert(*referrer_id);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    fn abort(error: DatabaseError) -> DatabaseError {
+        error
     }
 }
 
 impl KeyValueDatabase for InmemoryDatabase {
     /// Returns ids of all ents stored in the database
     fn ids(&self) -> EntIdSet {
-        self.ents.lock().unwrap().keys().copied().collect()
+        self.state.read().unwrap().ents.keys().copied().collect()
     }
 
     /// Returns true if database contains the provided id
     fn has_id(&self, id: Id) -> bool {
-        self.ents.lock().unwrap().contains_key(&id)
+        self.state.read().unwrap().ents.contains_key(&id)
+    }
+
+    /// Returns the values of the named fields of the ent with the provided
+    /// id, cloning only the values rather than the entire ent
+    fn get_fields(
+        &self,
+        id: Id,
+        names: &[String],
+    ) -> DatabaseResult<Option<HashMap<String, Value>>> {
+        Ok(self
+            .state
+            .read()
+            .unwrap()
+            .ents
+            .get(&id)
+            .map(|ent| project_fields(ent.as_ref(), names)))
     }
 
     /// Returns ids of all ents for the given type
     fn ids_for_type(&self, r#type: &str) -> EntIdSet {
-        self.ents_of_type
-            .lock()
+        self.state
+            .read()
             .unwrap()
+            .ents_of_type
             .get(r#type)
             .cloned()
             .unwrap_or_default()
     }
+
+    /// Returns ids of all ents whose indexed field has a value in the range
+    fn ids_for_field_range(&self, name: &str, range: IndexRange) -> Option<EntIdSet> {
+        let state = self.state.read().unwrap();
+        if state.unindexed_fields.contains(name) {
+            return None;
+        }
+
+        Some(
+            state
+                .field_index
+                .get(name)
+                .map(|index| ids_in_range(index, range))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns ids of all ents ordered by id
+    fn ids_in_order(
+        &self,
+        after: Option<Id>,
+        direction: OrderDirection,
+    ) -> Box<dyn Iterator<Item = Id> + '_> {
+        let mut ids: Vec<Id> = self
+            .state
+            .read()
+            .unwrap()
+            .ents
+            .keys()
+            .copied()
+            .filter(|id| match (after, direction) {
+                (Some(after), OrderDirection::Ascending) => *id > after,
+                (Some(after), OrderDirection::Descending) => *id < after,
+                (None, _) => true,
+            })
+            .collect();
+        ids.sort_unstable();
+        if direction == OrderDirection::Descending {
+            ids.reverse();
+        }
+        Box::new(ids.into_iter())
+    }
+
+    /// Returns ids of all ents whose creation timestamp is within the range
+    fn ids_for_created_range(&self, range: TimestampRange) -> EntIdSet {
+        ids_in_range(&self.state.read().unwrap().created_index, range)
+    }
+
+    /// Returns ids of all ents whose last updated timestamp is within the range
+    fn ids_for_last_updated_range(&self, range: TimestampRange) -> EntIdSet {
+        ids_in_range(&self.state.read().unwrap().last_updated_index, range)
+    }
+
+    /// Returns ids of all ents with the edge whose total ids is in the range
+    fn ids_for_edge_count_range(&self, name: &str, range: EdgeCountRange) -> EntIdSet {
+        self.state
+            .read()
+            .unwrap()
+            .edge_count_index
+            .get(name)
+            .map(|index| ids_in_range(index, range))
+            .unwrap_or_default()
+    }
+
+    /// Returns ids of all ents whose searchable field contains the word
+    fn ids_for_search_token(&self, name: &str, token: &str) -> Option<EntIdSet> {
+        let state = self.state.read().unwrap();
+        if state.unsearchable_fields.contains(name) {
+            return None;
+        }
+
+        Some(
+            state
+                .search_index
+                .get(name)
+                .and_then(|index| index.get(token))
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns ids of all ents whose edge of the given name points at the
+    /// ent with the given id
+    fn ids_for_referrers(&self, id: Id, name: &str) -> EntIdSet {
+        self.state
+            .read()
+            .unwrap()
+            .referrer_index
+            .get(&id)
+            .and_then(|index| index.get(name))
+            .cloned()
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Field, UntypedEnt, Value};
+    use crate::{DatabaseExt, Field, UntypedEnt, Value};
 
     #[test]
     fn insert_should_replace_ephemeral_id_with_allocator_id() {
@@ -205,7 +756,7 @@ mod tests {
             .expect("Failed to get ent")
             .expect("Ent missing");
         assert_eq!(ent.id(), 999);
-        assert_eq!(db.alloc.lock().unwrap().next(), Some(1000));
+        assert_eq!(db.state.write().unwrap().alloc.next(), Some(1000));
     }
 
     #[test]
@@ -256,6 +807,107 @@ mod tests {
         assert!(db.get(999).unwrap().is_none(), "Did not remove ent");
 
         // Id allocator should indicate that id has been freed
-        assert_eq!(db.alloc.lock().unwrap().freed(), &[999]);
+        assert_eq!(db.state.read().unwrap().alloc.freed(), &[999]);
+    }
+
+    #[test]
+    fn overwriting_an_ent_should_never_leave_it_missing() {
+        use std::{sync::Arc, thread};
+
+        let db = Arc::new(InmemoryDatabase::default());
+        let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+        let writer = {
+            let db = Arc::clone(&db);
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    // Overwrite the ent over and over
+                    let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+                }
+            })
+        };
+
+        while !writer.is_finished() {
+            assert_eq!(db.ids().len(), 1, "Saw a missing ent");
+        }
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn transactions_should_not_overwrite_concurrent_changes() {
+        use crate::{Edge, EdgeValue};
+        use std::{sync::Arc, thread};
+
+        let db = Arc::new(InmemoryDatabase::default());
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                1,
+                vec![],
+                vec![Edge::new("members", EdgeValue::Many(vec![])).with_inverse("groups")],
+            )))
+            .unwrap();
+
+        // Each member added updates the members of the same group
+        let writers: Vec<_> = (0..2)
+            .map(|n| {
+                let db = Arc::clone(&db);
+                thread::spawn(move || {
+                    for i in 0..250 {
+                        let _ = db
+                            .transaction(|tx| {
+                                tx.insert(Box::from(UntypedEnt::from_collections(
+                                    1000 + i * 2 + n,
+                                    vec![],
+                                    vec![Edge::new("groups", EdgeValue::Many(vec![1]))
+                                        .with_inverse("members")],
+                                )));
+                                Ok(())
+                            })
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let members = db.get(1).unwrap().unwrap().edge("members").unwrap();
+        assert_eq!(members.to_ids().len(), 500);
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn deserialize_should_rebuild_indexes_from_ents() {
+        use crate::{FieldAttribute, Predicate};
+
+        let db = InmemoryDatabase::default();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                999,
+                vec![Field::new_with_attributes(
+                    "x",
+                    3,
+                    vec![FieldAttribute::Indexed],
+                )],
+                vec![],
+            )))
+            .unwrap();
+
+        // Indexes are left out, keeping the format the same as databases
+        // serialized before indexes existed
+        let json = serde_json::to_value(&db).unwrap();
+        let mut names = json.as_object().unwrap().keys().collect::<Vec<&String>>();
+        names.sort_unstable();
+        assert_eq!(names, vec!["alloc", "ents", "ents_of_type"]);
+
+        let db: InmemoryDatabase = serde_json::from_value(json).unwrap();
+        let results = db
+            .find_all(Query::default().where_field("x", Predicate::equals(3)))
+            .unwrap();
+        assert_eq!(
+            results.iter().map(|ent| ent.id()).collect::<Vec<Id>>(),
+            vec![999]
+        );
     }
 }
@@ -1,3 +1,9 @@
+mod cache;
+use cache::EntCache;
+
+mod index;
+pub use index::{EdgeCountRange, IndexKey, IndexRange, TimestampRange};
+
 #[cfg(feature = "inmemory_db")]
 mod inmemory;
 
@@ -9,11 +15,20 @@ mod sled_db;
 #[cfg(feature = "sled_db")]
 pub use sled_db::SledDatabase;
 
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+use crate::{database::Removal, EdgeDeletionPolicy, EdgeValue};
 use crate::{
-    database::{Database, DatabaseResult},
-    Ent, Filter, Id, Predicate, PrimitiveValue, Query, Value,
+    database::{
+        edge_ids, project_fields, CheckCost, Database, DatabaseError, DatabaseResult, FindIter,
+        PlanStep, QueryPlan, Traversal,
+    },
+    search_tokens, Aggregate, Aggregator, Cursor, Ent, FieldPath, Filter, GroupBy,
+    GroupedAggregator, Id, Order, OrderDirection, Predicate, PrimitiveValue, Query, Relevance,
+    Value,
 };
-use std::collections::HashSet;
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 
 type EntIdSet = HashSet<Id>;
 
@@ -27,8 +42,52 @@ pub trait KeyValueDatabase: Database {
     /// Returns true if database contains the provided id
     fn has_id(&self, id: Id) -> bool;
 
+    /// Returns the values of the named fields of the ent with the provided
+    /// id, where names can also be paths into fields, or none if there is
+    /// no ent with the id
+    ///
+    /// Any name without a value is left out, the same as
+    /// [`Database::project`](super::Database::project).
+    fn get_fields(
+        &self,
+        id: Id,
+        names: &[String],
+    ) -> DatabaseResult<Option<HashMap<String, Value>>>;
+
     /// Returns ids of all ents for the given type
     fn ids_for_type(&self, r#type: &str) -> EntIdSet;
+
+    /// Returns ids of all ents whose indexed field with the given name has a
+    /// value within the range, or none if the field is not indexed for every
+    /// ent that contains it
+    fn ids_for_field_range(&self, name: &str, range: IndexRange) -> Option<EntIdSet>;
+
+    /// Returns ids of all ents ordered by id in the given direction, starting
+    /// after the given id if provided, pulling ids lazily where possible
+    fn ids_in_order(
+        &self,
+        after: Option<Id>,
+        direction: OrderDirection,
+    ) -> Box<dyn Iterator<Item = Id> + '_>;
+
+    /// Returns ids of all ents whose creation timestamp is within the range
+    fn ids_for_created_range(&self, range: TimestampRange) -> EntIdSet;
+
+    /// Returns ids of all ents whose last updated timestamp is within the range
+    fn ids_for_last_updated_range(&self, range: TimestampRange) -> EntIdSet;
+
+    /// Returns ids of all ents with an edge of the given name whose total ids
+    /// is within the range
+    fn ids_for_edge_count_range(&self, name: &str, range: EdgeCountRange) -> EntIdSet;
+
+    /// Returns ids of all ents whose searchable field of the given name
+    /// contains the word, or none if any ent has the field without it being
+    /// searchable
+    fn ids_for_search_token(&self, name: &str, token: &str) -> Option<EntIdSet>;
+
+    /// Returns ids of all ents whose edge of the given name points at the
+    /// ent with the given id
+    fn ids_for_referrers(&self, id: Id, name: &str) -> EntIdSet;
 }
 
 pub struct KeyValueDatabaseExecutor<'a, T: KeyValueDatabase>(&'a T);
@@ -47,60 +106,559 @@ impl<'a, T: KeyValueDatabase> KeyValueDatabaseExecutor<'a, T> {
     }
 
     pub fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
-        let mut pipeline: Option<EntIdSet> = None;
+        if !has_compatible_cursor(&query) {
+            return Err(DatabaseError::InvalidCursor);
+        }
 
-        for filter in query {
-            let mut_pipeline = pipeline.get_or_insert_with(|| prefill_ids(self.0, &filter));
+        // If we are only ordering by id, we can order and window the ids
+        // themselves and avoid loading ents that would not be returned
+        let cache = EntCache::new(self.0);
+        if !query.has_non_id_order() {
+            let ids = self.find_windowed_ids(&cache, &query);
+            return load_ents(&cache, ids);
+        }
 
-            // If our filter is the special IntoEdge case, we don't want to
-            // actually filter out ids but rather transform them into the ids
-            // of their edge
-            match filter {
-                Filter::IntoEdge(name) => {
-                    pipeline = Some(
-                        mut_pipeline
-                            .iter()
-                            .flat_map(|id| {
-                                self.0
-                                    .get(*id)
-                                    .map(|maybe_ent| {
-                                        maybe_ent
-                                            .and_then(|ent| {
-                                                ent.edge(&name).map(|edge| edge.to_ids())
-                                            })
-                                            .unwrap_or_default()
-                                    })
-                                    .unwrap_or_default()
-                            })
-                            .collect(),
-                    )
+        let ids = self.find_ids(&cache, &query).into_iter().collect();
+        let ents = load_ents(&cache, ids)?;
+        Ok(query.window(query.sort_ents(ents)))
+    }
+
+    /// Finds the values of the named fields of each ent that matches the
+    /// query using [`KeyValueDatabase::get_fields`], which avoids loading
+    /// entire ents unless the query is ordered by something other than ids
+    pub fn project(
+        &self,
+        query: Query,
+        fields: Vec<String>,
+    ) -> DatabaseResult<Vec<(Id, HashMap<String, Value>)>> {
+        if !has_compatible_cursor(&query) {
+            return Err(DatabaseError::InvalidCursor);
+        }
+
+        // Ordering by anything other than ids requires the ents themselves
+        if query.has_non_id_order() {
+            return Ok(self
+                .find_all(query)?
+                .into_iter()
+                .map(|ent| (ent.id(), project_fields(ent.as_ref(), &fields)))
+                .collect());
+        }
+
+        self.find_windowed_ids(&EntCache::new(self.0), &query)
+            .into_iter()
+            .filter_map(|id| {
+                self.0
+                    .get_fields(id, &fields)
+                    .map(|values| values.map(|values| (id, values)))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    /// Finds the ids of all ents whose edge of the given name points at the
+    /// ent with the given id using [`KeyValueDatabase::ids_for_referrers`],
+    /// in ascending order
+    pub fn referrers(&self, id: Id, edge_name: &str) -> DatabaseResult<Vec<Id>> {
+        let mut ids: Vec<Id> = self
+            .0
+            .ids_for_referrers(id, edge_name)
+            .into_iter()
+            .collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// Finds all ents that match the query one at a time
+    ///
+    /// When the query is only ordered by id and does not transform ents into
+    /// the ents of their edges, ids are pulled from the database lazily in
+    /// order and each ent is loaded and checked against the filters as the
+    /// iterator advances; otherwise, all ents are found up front.
+    pub fn find_iter(&self, query: Query) -> DatabaseResult<FindIter<'a>> {
+        if !has_compatible_cursor(&query) {
+            return Err(DatabaseError::InvalidCursor);
+        }
+
+        let is_streamable = !query.has_non_id_order()
+            && !query
+                .filters()
+                .iter()
+                .any(|f| matches!(f, Filter::IntoEdge(_) | Filter::IntoEdgeRecursive(_, _)));
+        if !is_streamable {
+            let ents = self.find_all(query.clone())?;
+            return Ok(FindIter::new(&query, ents.into_iter().map(Ok)));
+        }
+
+        // Start with the ids from an index if the first planned filter has
+        // one, otherwise pull in ids lazily from the database itself
+        let db = self.0;
+        let direction = id_direction(&query);
+        let after = query.results_after().map(Cursor::id);
+        let mut planned: Vec<PlannedFilter> = self
+            .plan(&query)
+            .into_iter()
+            .flat_map(|stage| stage.filters)
+            .collect();
+        let ids: Box<dyn Iterator<Item = Id> + 'a> = match planned.first_mut() {
+            None => Box::new(std::iter::empty()),
+            Some(first) => match first.lookup.take() {
+                Some(lead) => {
+                    if lead.is_exact {
+                        planned.remove(0);
+                    }
+                    let mut ids: Vec<Id> = lead.ids.into_iter().collect();
+                    ids.sort_unstable();
+                    if direction == OrderDirection::Descending {
+                        ids.reverse();
+                    }
+                    ids.retain(|id| is_id_after_cursor(&query, *id));
+                    Box::new(ids.into_iter())
                 }
-                // Otherwise, the filter is a traditional case where we will
-                // strip out ids by the filter
-                f => {
-                    mut_pipeline.retain(|id| filter_id(self.0, id, &f));
+                None => db.ids_in_order(after, direction),
+            },
+        };
+
+        // Use the ids found for any other filters to skip loading ents that
+        // cannot match, only checking filters not answered exactly by them
+        let mut lookups = Vec::new();
+        let mut filters = Vec::new();
+        for p in planned {
+            match p.lookup {
+                Some(lookup) => {
+                    if !lookup.is_exact {
+                        filters.push(p.filter.clone());
+                    }
+                    lookups.push(lookup.ids);
                 }
+                None => filters.push(p.filter.clone()),
             }
         }
 
-        pipeline
-            .unwrap_or_default()
+        // NOTE: Each ent gets its own cache rather than sharing one for the
+        //       life of the iterator, which would otherwise hold on to every
+        //       ent reached through edges and serve them increasingly stale
+        let ents = ids
+            .filter(move |id| lookups.iter().all(|ids| ids.contains(id)))
+            .filter_map(move |id| {
+                let cache = EntCache::new(db);
+                match cache.get_owned(id) {
+                    Ok(Some(ent)) => {
+                        if filters.iter().all(|f| filter_ent(&cache, ent.as_ref(), f)) {
+                            Some(Ok(ent))
+                        } else {
+                            None
+                        }
+                    }
+                    Ok(None) => None,
+                    Err(x) => Some(Err(x)),
+                }
+            })
+            .skip(query.results_offset());
+        Ok(match query.results_limit() {
+            Some(limit) => FindIter::new(&query, ents.take(limit)),
+            None => FindIter::new(&query, ents),
+        })
+    }
+
+    /// Counts the ents that match the query using only their ids
+    ///
+    /// Ents are only loaded to check filters that cannot be answered by an
+    /// index, and never to produce the count itself unless the query is
+    /// resuming from a cursor positioned by something other than ids.
+    pub fn count(&self, query: Query) -> DatabaseResult<usize> {
+        if !has_compatible_cursor(&query) {
+            return Err(DatabaseError::InvalidCursor);
+        }
+
+        // Ordering does not change the total, so we only need to load ents
+        // when they are needed to find those positioned after the cursor
+        if query.results_after().is_some() && query.has_non_id_order() {
+            return Ok(self.find_all(query)?.len());
+        }
+
+        let total = self
+            .find_ids(&EntCache::new(self.0), &query)
             .into_iter()
-            .filter_map(|id| self.0.get(id).transpose())
-            .collect()
+            .filter(|id| is_id_after_cursor(&query, *id))
+            .count();
+        Ok(window_len(&query, total))
+    }
+
+    /// Returns true if at least one ent matches the query
+    ///
+    /// When the query has a single filter that an index answers exactly,
+    /// the ids from the index are used without loading any ents; otherwise,
+    /// this stops at the first ent found.
+    pub fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        if !has_compatible_cursor(&query) {
+            return Err(DatabaseError::InvalidCursor);
+        }
+
+        if let [filter] = query.filters() {
+            if !query.has_non_id_order() {
+                if let Some(lookup) = ids_from_lookup(self.0, filter).filter(|x| x.is_exact) {
+                    let total = lookup
+                        .ids
+                        .into_iter()
+                        .filter(|id| is_id_after_cursor(&query, *id))
+                        .count();
+                    return Ok(window_len(&query, total) > 0);
+                }
+            }
+        }
+
+        Ok(self.find_first(query)?.is_some())
+    }
+
+    /// Finds the first ent that matches the query, stopping as soon as it
+    /// is found when the query can be streamed as described by
+    /// [`KeyValueDatabaseExecutor::find_iter`]
+    pub fn find_first(&self, query: Query) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.find_iter(query.first_only())?.next().transpose()
+    }
+
+    /// Computes the aggregate over the ents that match the query, loading
+    /// one ent at a time rather than collecting them
+    ///
+    /// Counts are computed using [`KeyValueDatabaseExecutor::count`].
+    pub fn aggregate(&self, query: Query, aggregate: Aggregate) -> DatabaseResult<Value> {
+        if aggregate == Aggregate::Count {
+            return self.count(query).map(Value::from);
+        }
+
+        let mut aggregator = Aggregator::new(aggregate);
+        self.for_each_ent(query, |ent| aggregator.push(ent))?;
+        Ok(aggregator.finish())
+    }
+
+    /// Computes the aggregate separately for each group of ents that match
+    /// the query, loading one ent at a time rather than collecting them
+    pub fn aggregate_by(
+        &self,
+        query: Query,
+        aggregate: Aggregate,
+        group_by: GroupBy,
+    ) -> DatabaseResult<Vec<(Value, Value)>> {
+        let mut aggregator = GroupedAggregator::new(aggregate, group_by);
+        self.for_each_ent(query, |ent| aggregator.push(ent))?;
+        Ok(aggregator.finish())
+    }
+
+    /// Visits each ent that matches the query one at a time
+    ///
+    /// Unless the query is windowed or resumes from a cursor, the order in
+    /// which ents are visited does not matter, so ents are visited without
+    /// being sorted.
+    fn for_each_ent<F: FnMut(&dyn Ent)>(&self, query: Query, mut f: F) -> DatabaseResult<()> {
+        let is_windowed = query.results_after().is_some()
+            || query.results_offset() > 0
+            || query.results_limit().is_some();
+        if is_windowed {
+            for ent in self.find_iter(query)? {
+                f(ent?.as_ref());
+            }
+        } else {
+            let cache = EntCache::new(self.0);
+            for id in self.find_ids(&cache, &query) {
+                if let Some(ent) = cache.get_owned(id)? {
+                    f(ent.as_ref());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Describes the plan used to find the ents that match the query as
+    /// produced by [`KeyValueDatabaseExecutor::plan`], looking up the ids
+    /// of any indexes to estimate the candidates remaining after each step
+    pub fn explain(&self, query: Query) -> DatabaseResult<QueryPlan> {
+        let mut steps = Vec::new();
+        let mut estimate: Option<usize> = None;
+
+        for stage in self.plan(&query) {
+            for planned in stage.filters {
+                let filter = planned.filter.clone();
+                match planned.lookup {
+                    Some(lookup) => {
+                        let total = lookup.ids.len();
+                        estimate = Some(estimate.map_or(total, |x| std::cmp::min(x, total)));
+                        steps.push(PlanStep::Lookup {
+                            indexes: index_names(self.0, planned.filter),
+                            filter,
+                            is_exact: lookup.is_exact,
+                            estimate,
+                        });
+                    }
+                    None => {
+                        if steps.is_empty() {
+                            estimate = Some(self.0.ids().len());
+                            steps.push(PlanStep::Scan { estimate });
+                        }
+                        steps.push(PlanStep::Check {
+                            filter,
+                            cost: planned.cost,
+                            estimate,
+                        });
+                    }
+                }
+            }
+
+            if let Some((name, max_depth)) = stage.into_edge {
+                if steps.is_empty() {
+                    steps.push(PlanStep::Scan {
+                        estimate: Some(self.0.ids().len()),
+                    });
+                }
+                estimate = None;
+                steps.push(match max_depth {
+                    Some(1) => PlanStep::IntoEdge {
+                        name: name.to_string(),
+                    },
+                    max_depth => PlanStep::IntoEdgeRecursive {
+                        name: name.to_string(),
+                        max_depth,
+                    },
+                });
+            }
+        }
+
+        Ok(QueryPlan::new(steps))
+    }
+
+    /// Finds the ids of ents that match a query that is only ordered by id,
+    /// ordered and windowed the same as the ents the query would return
+    fn find_windowed_ids(&self, cache: &EntCache<'a, T>, query: &Query) -> Vec<Id> {
+        let mut ids: Vec<Id> = self.find_ids(cache, query).into_iter().collect();
+        ids.sort_unstable();
+        if id_direction(query) == OrderDirection::Descending {
+            ids.reverse();
+        }
+        ids.retain(|id| is_id_after_cursor(query, *id));
+        query.window(ids)
+    }
+
+    /// Plans the order in which the filters of the query are applied
+    ///
+    /// Filters are grouped into stages that end with each transformation
+    /// into the ents of an edge, as only the filters between transformations
+    /// are independent of one another. Within each stage, filters that can
+    /// be looked up using an index come first, starting with the lookup that
+    /// finds the fewest ids, followed by the remaining filters from cheapest
+    /// to most expensive to check, leaving filters on edges for last.
+    fn plan<'q>(&self, query: &'q Query) -> Vec<PlannedStage<'q>> {
+        let mut stages = Vec::new();
+        let mut filters = Vec::new();
+
+        for filter in query.filters() {
+            match filter {
+                Filter::IntoEdge(name) => stages.push(PlannedStage {
+                    filters: std::mem::take(&mut filters),
+                    into_edge: Some((name, Some(1))),
+                }),
+                Filter::IntoEdgeRecursive(name, max_depth) => stages.push(PlannedStage {
+                    filters: std::mem::take(&mut filters),
+                    into_edge: Some((name, *max_depth)),
+                }),
+                filter => filters.push(PlannedFilter {
+                    filter,
+                    lookup: ids_from_lookup(self.0, filter),
+                    cost: CheckCost::of(filter),
+                }),
+            }
+        }
+
+        if !filters.is_empty() {
+            stages.push(PlannedStage {
+                filters,
+                into_edge: None,
+            });
+        }
+
+        for stage in stages.iter_mut() {
+            stage.filters.sort_by_key(PlannedFilter::rank);
+        }
+
+        stages
+    }
+
+    /// Applies the filters of the query in the order planned by
+    /// [`KeyValueDatabaseExecutor::plan`] to produce the ids of matching ents,
+    /// loading ents through the cache so each is loaded at most once
+    fn find_ids(&self, cache: &EntCache<'a, T>, query: &Query) -> EntIdSet {
+        let mut candidates: Option<EntIdSet> = None;
+
+        for stage in self.plan(query) {
+            for planned in stage.filters {
+                let is_exact = matches!(&planned.lookup, Some(lookup) if lookup.is_exact);
+                let mut ids = match (candidates.take(), planned.lookup) {
+                    // The first filter starts with the ids from its index if
+                    // it has one, otherwise with every id
+                    (None, Some(lookup)) => lookup.ids,
+                    (None, None) => self.0.ids(),
+
+                    // Later filters narrow down the ids using their index
+                    (Some(mut ids), Some(lookup)) => {
+                        ids.retain(|id| lookup.ids.contains(id));
+                        ids
+                    }
+                    (Some(ids), None) => ids,
+                };
+
+                // If the ids came from an index that exactly answers the
+                // filter, there is nothing left to strip out
+                let filter = planned.filter;
+                if !is_exact {
+                    ids = retain_ids(ids, |id| filter_id(cache, id, filter));
+                }
+
+                candidates = Some(ids);
+            }
+
+            // Transform the ids into the ids of the ents reached through their
+            // edge rather than filtering them
+            if let Some((name, max_depth)) = stage.into_edge {
+                let ids = candidates.take().unwrap_or_else(|| self.0.ids());
+                let mut traversal = Traversal::new(ids, max_depth);
+                let mut reached = EntIdSet::new();
+                while let Some(id) = traversal.pending() {
+                    let ent = cache.get(id);
+                    if let Some((id, _)) = traversal.visit(edge_ids(ent.as_deref(), name)) {
+                        reached.insert(id);
+                    }
+                }
+                candidates = Some(reached);
+            }
+        }
+
+        candidates.unwrap_or_default()
+    }
+}
+
+/// Represents a filter of a query alongside the ids found for it using an
+/// index, if it has one, and the cost of checking it against an ent
+struct PlannedFilter<'q> {
+    filter: &'q Filter,
+    lookup: Option<LookupIds>,
+    cost: CheckCost,
+}
+
+impl<'q> PlannedFilter<'q> {
+    /// Produces the key used to order filters within a stage, placing
+    /// filters with the fewest ids from an index first and unindexed
+    /// filters afterward from cheapest to most expensive to check
+    fn rank(&self) -> (bool, usize, bool, CheckCost) {
+        match &self.lookup {
+            Some(lookup) => (false, lookup.ids.len(), !lookup.is_exact, self.cost),
+            None => (true, 0, true, self.cost),
+        }
+    }
+}
+
+/// Represents filters that can be applied in any order, followed by the
+/// transformation into the ents reached through an edge that ends the stage,
+/// if any, where a single hop has a maximum depth of one
+struct PlannedStage<'q> {
+    filters: Vec<PlannedFilter<'q>>,
+    into_edge: Option<(&'q str, Option<usize>)>,
+}
+
+/// Describes the indexes used to look up the ids for the filter
+fn index_names<D: KeyValueDatabase>(db: &D, filter: &Filter) -> Vec<String> {
+    let mut names = match filter {
+        Filter::Id(_) => vec![String::from("id")],
+        Filter::Type(_) => vec![String::from("type")],
+        Filter::Created(_) => vec![String::from("created")],
+        Filter::LastUpdated(_) => vec![String::from("last_updated")],
+        Filter::Field(name, _) => vec![format!("field({})", name)],
+        Filter::EdgeCount(name, _) => vec![format!("edge_count({})", name)],
+        Filter::Search(name, _) => vec![format!("search({})", name)],
+        Filter::Edge(name, _) => vec![format!("referrers({})", name)],
+        Filter::And(list) | Filter::Or(list) => list
+            .iter()
+            .filter(|f| ids_from_lookup(db, f).is_some())
+            .flat_map(|f| index_names(db, f))
+            .collect(),
+        Filter::EdgeRecursive(_, _, _)
+        | Filter::IntoEdge(_)
+        | Filter::IntoEdgeRecursive(_, _)
+        | Filter::Not(_) => Vec::new(),
+    };
+
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.to_string()));
+    names
+}
+
+/// Returns true if the query has no cursor or a cursor produced using the
+/// same ordering clauses as the query
+fn has_compatible_cursor(query: &Query) -> bool {
+    match query.results_after() {
+        Some(cursor) => cursor.is_compatible_with(query.orders()),
+        None => true,
+    }
+}
+
+/// Returns the direction of ids for a query that is only ordered by id
+fn id_direction(query: &Query) -> OrderDirection {
+    query
+        .orders()
+        .first()
+        .map_or(OrderDirection::Ascending, Order::direction)
+}
+
+/// Returns the total results remaining after applying the offset and limit
+/// of the query to the given total
+fn window_len(query: &Query, total: usize) -> usize {
+    let total = total.saturating_sub(query.results_offset());
+    match query.results_limit() {
+        Some(limit) => std::cmp::min(total, limit),
+        None => total,
+    }
+}
+
+/// Returns true if the id is positioned after the cursor of a query that is
+/// only ordered by id
+fn is_id_after_cursor(query: &Query, id: Id) -> bool {
+    match (query.results_after(), id_direction(query)) {
+        (Some(cursor), OrderDirection::Ascending) => id > cursor.id(),
+        (Some(cursor), OrderDirection::Descending) => id < cursor.id(),
+        (None, _) => true,
     }
 }
 
-/// Called once when first beginning to filter to determine which ent ids
-/// to start with based on the leading filter
+/// Represents the ent ids found for a filter without checking any ents
 ///
-/// 1. If lead filter by id equality, will only include those ids that match
+/// 1. If filter by id equality, will only include those ids that match
 ///    the predicate
-/// 2. If lead filter by type equality, will only include those ids that equal
+/// 2. If filter by type equality, will only include those ids that equal
 ///    the type (or many types if wrapped in Or)
-/// 3. Any other variation of id/type filter or other kind of filter will
-///    result in the more expensive pulling of all ids
-fn prefill_ids<D: KeyValueDatabase>(db: &D, filter: &Filter) -> EntIdSet {
+/// 3. If filter by a field that is indexed, will only include those
+///    ids found within the ranges of the index that match the predicate
+/// 4. If filter by created or last updated timestamp, will only include
+///    those ids found within the ranges of the timestamp index that match
+///    the predicate
+/// 5. If filter by a search of a searchable field, will only include those
+///    ids found within the search index for any word of the search
+/// 6. If filter by an edge pointing at specific ids, will only include those
+///    ids found within the referrer index for the edge and each id
+/// 7. If filter by a combination of filters, will only include those
+///    ids found by intersecting (And) or joining (Or) the ids of the
+///    combined filters that can be looked up
+/// 8. Any other variation of id/type/field/timestamp/edge filter or other
+///    kind of filter cannot be looked up and must be checked against every
+///    ent
+///
+/// Lookups by id, type, timestamp, search, and edge produce exactly the ids
+/// that satisfy the filter, whereas lookups by field may include ids that do
+/// not.
+struct LookupIds {
+    ids: EntIdSet,
+    is_exact: bool,
+}
+
+/// Looks up the ids for the filter as described by [`LookupIds`], returning
+/// none if the filter cannot be looked up
+fn ids_from_lookup<D: KeyValueDatabase>(db: &D, filter: &Filter) -> Option<LookupIds> {
     fn from_id_predicate<D: KeyValueDatabase>(
         db: &D,
         p: &Predicate,
@@ -138,50 +696,559 @@ fn prefill_ids<D: KeyValueDatabase>(db: &D, filter: &Filter) -> EntIdSet {
     }
 
     match filter {
-        // If leading with id, support Equals and Or(Equals(...), ...) for
+        // If filtering by id, support Equals and Or(Equals(...), ...) for
         // specific ids; otherwise, too hard to figure out so we pull in all ids
-        Filter::Id(p) => {
-            from_id_predicate(db, p.as_untyped(), EntIdSet::new()).unwrap_or_else(|| db.ids())
-        }
+        Filter::Id(p) => exact(
+            from_id_predicate(db, p.as_untyped(), EntIdSet::new())
+                .map(|ids| ids.into_iter().filter(|id| db.has_id(*id)).collect()),
+        ),
 
-        // If leading with type, support Equals and Or(Equals(...), ...) for
+        // If filtering by type, support Equals and Or(Equals(...), ...) for
         // specific ids; otherwise, too hard to figure out so we pull in all ids
-        Filter::Type(p) => {
-            from_type_predicate(db, p.as_untyped(), EntIdSet::new()).unwrap_or_else(|| db.ids())
+        Filter::Type(p) => exact(from_type_predicate(db, p.as_untyped(), EntIdSet::new())),
+
+        // If filtering by an indexed field, support Equals, InSet, ranges,
+        // and Or(...) of those; otherwise, we pull in all ids
+        f @ Filter::Field(_, _) => ids_from_index(db, f).map(|ids| LookupIds {
+            ids,
+            is_exact: false,
+        }),
+
+        // If filtering by a timestamp or the total ids of an edge, support
+        // the same predicates as an indexed field, which the timestamp and
+        // edge count indexes answer exactly
+        f @ Filter::Created(_) | f @ Filter::LastUpdated(_) | f @ Filter::EdgeCount(_, _) => {
+            exact(ids_from_index(db, f))
+        }
+
+        // If filtering by a search, the search index holds the same words
+        // that the search is checked against, so it answers exactly
+        f @ Filter::Search(_, _) => exact(ids_from_index(db, f)),
+
+        // If filtering by an edge pointing at specific ids, support the same
+        // predicates as filtering by id, which the referrer index answers
+        // exactly whether or not the ents pointed at still exist
+        Filter::Edge(name, f) => match f.as_ref() {
+            Filter::Id(p) => exact(from_id_predicate(db, p.as_untyped(), EntIdSet::new()).map(
+                |ids| {
+                    ids.into_iter()
+                        .flat_map(|id| db.ids_for_referrers(id, name))
+                        .collect()
+                },
+            )),
+            _ => None,
+        },
+
+        // If filtering by a combination, intersect or join the ids of the
+        // filters within; Not(...) would require all ids anyway
+        Filter::And(list) => {
+            let lookups: Vec<Option<LookupIds>> =
+                list.iter().map(|f| ids_from_lookup(db, f)).collect();
+            let is_exact = lookups
+                .iter()
+                .all(|x| matches!(x, Some(lookup) if lookup.is_exact));
+            ids_from_all(lookups.into_iter().map(|x| x.map(|x| x.ids)))
+                .map(|ids| LookupIds { ids, is_exact })
+        }
+        Filter::Or(list) => {
+            let lookups = list
+                .iter()
+                .map(|f| ids_from_lookup(db, f))
+                .collect::<Option<Vec<LookupIds>>>()?;
+            let is_exact = lookups.iter().all(|x| x.is_exact);
+            ids_from_any(lookups.into_iter().map(|x| Some(x.ids)))
+                .map(|ids| LookupIds { ids, is_exact })
         }
 
         // Otherwise, currently no cached/indexed way to look up (yet)
-        // TODO: Support database field indexing so equality of a field can
-        //       be used for faster id lookup; do the same for timestamp fields
-        _ => db.ids(),
+        _ => None,
+    }
+}
+
+/// Marks the ids looked up for a filter as exactly answering it
+fn exact(ids: Option<EntIdSet>) -> Option<LookupIds> {
+    ids.map(|ids| LookupIds {
+        ids,
+        is_exact: true,
+    })
+}
+
+/// Intersects the ids looked up for each filter that supports a lookup,
+/// returning none if no filter supports a lookup
+fn ids_from_all<I: IntoIterator<Item = Option<EntIdSet>>>(lookups: I) -> Option<EntIdSet> {
+    lookups
+        .into_iter()
+        .flatten()
+        .fold(None, |all_ids, ids| match all_ids {
+            Some(all_ids) => Some(ids.intersection(&all_ids).copied().collect()),
+            None => Some(ids),
+        })
+}
+
+/// Joins the ids looked up for each filter, returning none if any filter
+/// does not support a lookup
+fn ids_from_any<I: IntoIterator<Item = Option<EntIdSet>>>(lookups: I) -> Option<EntIdSet> {
+    lookups
+        .into_iter()
+        .try_fold(EntIdSet::new(), |mut all_ids, ids| {
+            all_ids.extend(ids?);
+            Some(all_ids)
+        })
+}
+
+/// Looks up the ids of ents that could satisfy the filter using the index
+/// associated with the filter, returning none if there is no index or the
+/// predicate of the filter cannot be answered by an index
+///
+/// The ids returned may include ents that do not satisfy the filter, so the
+/// filter should still be checked against each ent.
+fn ids_from_index<D: KeyValueDatabase>(db: &D, filter: &Filter) -> Option<EntIdSet> {
+    match filter {
+        // Paths into a field are never indexed, only the field itself
+        Filter::Field(name, _) if matches!(FieldPath::parse(name), Some(x) if x.is_nested()) => {
+            None
+        }
+        Filter::Field(name, p) => index::predicate_to_index_ranges(p)?.into_iter().try_fold(
+            EntIdSet::new(),
+            |mut ids, range| {
+                ids.extend(db.ids_for_field_range(name, range)?);
+                Some(ids)
+            },
+        ),
+        Filter::Created(p) => Some(
+            index::predicate_to_timestamp_ranges(p.as_untyped())?
+                .into_iter()
+                .flat_map(|range| db.ids_for_created_range(range))
+                .collect(),
+        ),
+        Filter::LastUpdated(p) => Some(
+            index::predicate_to_timestamp_ranges(p.as_untyped())?
+                .into_iter()
+                .flat_map(|range| db.ids_for_last_updated_range(range))
+                .collect(),
+        ),
+        Filter::EdgeCount(name, p) => Some(
+            index::predicate_to_edge_count_ranges(p.as_untyped())?
+                .into_iter()
+                .flat_map(|range| db.ids_for_edge_count_range(name, range))
+                .collect(),
+        ),
+        Filter::Search(name, _) if matches!(FieldPath::parse(name), Some(x) if x.is_nested()) => {
+            None
+        }
+        Filter::Search(name, text) => {
+            search_tokens(text)
+                .into_iter()
+                .try_fold(EntIdSet::new(), |mut ids, token| {
+                    ids.extend(db.ids_for_search_token(name, &token)?);
+                    Some(ids)
+                })
+        }
+        Filter::And(list) => ids_from_all(list.iter().map(|f| ids_from_index(db, f))),
+        Filter::Or(list) => ids_from_any(list.iter().map(|f| ids_from_index(db, f))),
+        _ => None,
     }
 }
 
-fn filter_id<D: KeyValueDatabase>(db: &D, id: &Id, filter: &Filter) -> bool {
+/// Keeps the ids that pass the check, checking ids in parallel if the
+/// `rayon` feature is enabled
+#[cfg(feature = "rayon")]
+fn retain_ids<F: Fn(&Id) -> bool + Sync>(ids: EntIdSet, f: F) -> EntIdSet {
+    use rayon::prelude::*;
+    ids.into_par_iter().filter(|id| f(id)).collect()
+}
+
+/// Keeps the ids that pass the check, checking ids in parallel if the
+/// `rayon` feature is enabled
+#[cfg(not(feature = "rayon"))]
+fn retain_ids<F: Fn(&Id) -> bool>(mut ids: EntIdSet, f: F) -> EntIdSet {
+    ids.retain(|id| f(id));
+    ids
+}
+
+/// Loads the ents with the given ids in order, leaving out any that do not
+/// exist and loading them in parallel if the `rayon` feature is enabled
+#[cfg(feature = "rayon")]
+fn load_ents<D: KeyValueDatabase>(
+    cache: &EntCache<D>,
+    ids: Vec<Id>,
+) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+    use rayon::prelude::*;
+    ids.into_par_iter()
+        .filter_map(|id| cache.get_owned(id).transpose())
+        .collect()
+}
+
+/// Loads the ents with the given ids in order, leaving out any that do not
+/// exist and loading them in parallel if the `rayon` feature is enabled
+#[cfg(not(feature = "rayon"))]
+fn load_ents<D: KeyValueDatabase>(
+    cache: &EntCache<D>,
+    ids: Vec<Id>,
+) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+    ids.into_iter()
+        .filter_map(|id| cache.get_owned(id).transpose())
+        .collect()
+}
+
+fn filter_id<D: KeyValueDatabase>(cache: &EntCache<D>, id: &Id, filter: &Filter) -> bool {
     match filter {
         Filter::Id(p) => p.check(*id),
-        Filter::Type(p) => with_ent(db, id, |ent| p.check(ent.r#type().to_string())),
-        Filter::Created(p) => with_ent(db, id, |ent| p.check(ent.created())),
-        Filter::LastUpdated(p) => with_ent(db, id, |ent| p.check(ent.last_updated())),
-        Filter::Field(name, p) => with_ent(db, id, |ent| match ent.field(name) {
+        f => match cache.get(*id) {
+            Some(ent) => filter_ent(cache, ent.as_ref(), f),
+            None => false,
+        },
+    }
+}
+
+fn filter_ent<D: KeyValueDatabase>(cache: &EntCache<D>, ent: &dyn Ent, filter: &Filter) -> bool {
+    match filter {
+        Filter::Id(p) => p.check(ent.id()),
+        Filter::Type(p) => p.check(ent.r#type().to_string()),
+        Filter::Created(p) => p.check(ent.created()),
+        Filter::LastUpdated(p) => p.check(ent.last_updated()),
+        Filter::Field(name, p) => match FieldPath::lookup(ent, name) {
             Some(value) => p.check(&value),
             None => false,
-        }),
-        Filter::Edge(name, f) => with_ent(db, id, |ent| match ent.edge(name) {
-            Some(edge) => edge.to_ids().iter().any(|id| filter_id(db, id, f)),
+        },
+        Filter::Edge(name, f) => match ent.edge(name) {
+            Some(edge) => edge.to_ids().iter().any(|id| filter_id(cache, id, f)),
             None => false,
-        }),
+        },
+        Filter::EdgeCount(name, p) => match ent.edge(name) {
+            Some(edge) => p.check(edge.to_ids().len()),
+            None => false,
+        },
+        Filter::Search(name, text) => Relevance::of(ent, name, text).is_match(),
+        Filter::EdgeRecursive(name, max_depth, f) => {
+            let mut traversal = Traversal::new(vec![ent.id()], *max_depth);
+            traversal.visit(edge_ids(Some(ent), name));
+            while let Some(id) = traversal.pending() {
+                let ent = cache.get(id);
+                let ids = edge_ids(ent.as_deref(), name);
+                if let (Some(_), Some(ent)) = (traversal.visit(ids), ent) {
+                    if filter_ent(cache, ent.as_ref(), f) {
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+        Filter::And(list) => list.iter().all(|f| filter_ent(cache, ent, f)),
+        Filter::Or(list) => list.iter().any(|f| filter_ent(cache, ent, f)),
+        Filter::Not(f) => !filter_ent(cache, ent, f),
+
+        // NOTE: Transformations are handled by the executor itself, so the
+        //       only way to reach this is a transformation nested within
+        //       another filter, which has no meaning and never matches
+        Filter::IntoEdge(_) | Filter::IntoEdgeRecursive(_, _) => false,
+    }
+}
+
+/// Represents the storage operations used by [`remove_cascading`], letting
+/// each key-value database remove ents and apply the deletion policies of
+/// their edges the same way
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+pub(crate) trait CascadeStore {
+    type Error;
+
+    /// Retrieves a copy of the ent with the given id
+    fn load(&self, id: Id) -> Result<Option<Box<dyn Ent>>, Self::Error>;
+
+    /// Stores the ent, replacing the stored ent with the same id along with
+    /// its index entries
+    fn store(&self, ent: Box<dyn Ent>) -> Result<(), Self::Error>;
+
+    /// Removes the ent along with its index entries, freeing its id
+    fn delete(&self, ent: &dyn Ent) -> Result<(), Self::Error>;
+
+    /// Returns ids of all ents with an edge pointing at the ent with the
+    /// given id
+    fn referrer_ids(&self, id: Id) -> Result<EntIdSet, Self::Error>;
+
+    /// Converts an error that stops the removal into an error of the store,
+    /// undoing any changes made so far where the store supports it
+    fn abort(error: DatabaseError) -> Self::Error;
+}
+
+/// Removes the ent with the given id, applying the deletion policies of its
+/// edges and of the edges pointing at it, and returns a description of the
+/// changed ents, or none if there is no ent with the id
+///
+/// 1. Every ent reached through edges with
+///    [`EdgeDeletionPolicy::DeepDelete`] is removed too, following the edges
+///    of those ents in turn and visiting each ent once so cycles of edges
+///    end the cascade
+/// 2. If any ent that remains points at a removed ent through an edge with
+///    [`EdgeDeletionPolicy::Restrict`], nothing is removed and
+///    [`DatabaseError::RestrictedRemoval`] is returned instead
+/// 3. Every ent that remains and is reached through edges with
+///    [`EdgeDeletionPolicy::ShallowDelete`] has the ids of all removed ents
+///    taken out of its edges, and every ent that remains and points at a
+///    removed ent through edges with [`EdgeDeletionPolicy::Nullify`] has the
+///    ids of all removed ents taken out of those edges, except for edges
+///    that must always have exactly one id
+/// 4. Every ent that remains and is reached through an edge of a removed ent
+///    that declares an inverse has the ids of all removed ents taken out of
+///    its inverse edge, except where that edge must always have exactly one
+///    id
+///
+/// Every ent involved is loaded before anything is changed, so a failure to
+/// load an ent leaves the store as it was.
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+pub(crate) fn remove_cascading<S: CascadeStore>(
+    store: &S,
+    id: Id,
+) -> Result<Option<Removal>, S::Error> {
+    let ent = match store.load(id)? {
+        Some(ent) => ent,
+        None => return Ok(None),
+    };
+
+    let mut visited: EntIdSet = vec![id].into_iter().collect();
+    let mut removed = vec![ent];
+    let mut shallow_ids = Vec::new();
+    let mut inverse_edges = Vec::new();
+    let mut i = 0;
+    while i < removed.len() {
+        for edge in removed[i].edges() {
+            if let Some(inverse) = edge.inverse() {
+                for edge_id in edge.to_ids() {
+                    inverse_edges.push((edge_id, inverse.to_string()));
+                }
+            }
+
+            match edge.deletion_policy() {
+                EdgeDeletionPolicy::DeepDelete => {
+                    for edge_id in edge.to_ids() {
+                        if visited.insert(edge_id) {
+                            if let Some(ent) = store.load(edge_id)? {
+                                removed.push(ent);
+                            }
+                        }
+                    }
+                }
+                EdgeDeletionPolicy::ShallowDelete => shallow_ids.extend(edge.to_ids()),
+                EdgeDeletionPolicy::Nothing
+                | EdgeDeletionPolicy::Restrict
+                | EdgeDeletionPolicy::Nullify => {}
+            }
+        }
+        i += 1;
+    }
+
+    let removed_ids: Vec<Id> = removed.iter().map(|ent| ent.id()).collect();
+    let removed_set: EntIdSet = removed_ids.iter().copied().collect();
+
+    // Load the remaining ents that need their edges updated, where ents
+    // reached through shallow edges have every edge updated while ents
+    // pointing at removed ents through nullify edges or inverse edges only
+    // have those edges updated
+    let mut others: Vec<(Box<dyn Ent>, bool)> = Vec::new();
+    let mut positions: HashMap<Id, usize> = HashMap::new();
+    for shallow_id in shallow_ids {
+        if removed_set.contains(&shallow_id) || positions.contains_key(&shallow_id) {
+            continue;
+        }
+
+        if let Some(ent) = store.load(shallow_id)? {
+            positions.insert(shallow_id, others.len());
+            others.push((ent, true));
+        }
+    }
+
+    let mut inverse_names: HashMap<Id, HashSet<String>> = HashMap::new();
+    for (edge_id, inverse) in inverse_edges {
+        if removed_set.contains(&edge_id) {
+            continue;
+        }
+
+        if let Entry::Vacant(entry) = positions.entry(edge_id) {
+            match store.load(edge_id)? {
+                Some(ent) => {
+                    entry.insert(others.len());
+                    others.push((ent, false));
+                }
+                None => continue,
+            }
+        }
+        inverse_names.entry(edge_id).or_default().insert(inverse);
+    }
+
+    for removed_id in removed_ids.iter().copied() {
+        let mut referrer_ids: Vec<Id> = store
+            .referrer_ids(removed_id)?
+            .into_iter()
+            .filter(|id| !removed_set.contains(id))
+            .collect();
+        referrer_ids.sort_unstable();
+
+        let mut restricted = Vec::new();
+        for referrer_id in referrer_ids {
+            let position = match positions.get(&referrer_id) {
+                Some(position) => *position,
+                None => match store.load(referrer_id)? {
+                    Some(ent) => {
+                        positions.insert(referrer_id, others.len());
+                        others.push((ent, false));
+                        others.len() - 1
+                    }
+                    None => continue,
+                },
+            };
+
+            if others[position].0.edges().into_iter().any(|edge| {
+                edge.has_restrict_deletion_policy() && edge.to_ids().contains(&removed_id)
+            }) {
+                restricted.push(referrer_id);
+            }
+        }
+
+        if !restricted.is_empty() {
+            return Err(S::abort(DatabaseError::RestrictedRemoval {
+                id: removed_id,
+                referrers: restricted,
+            }));
+        }
+    }
+
+    let mut updated = Vec::new();
+    for (mut ent, is_shallow) in others {
+        let names = inverse_names.remove(&ent.id()).unwrap_or_default();
+        let mut changed = false;
+        for mut edge in ent.edges() {
+            if !is_shallow && !edge.has_nullify_deletion_policy() && !names.contains(edge.name()) {
+                continue;
+            }
+
+            let total = edge.to_ids().len();
+            if edge
+                .value_mut()
+                .remove_ids(removed_ids.iter().copied())
+                .is_ok()
+                && edge.to_ids().len() < total
+            {
+                let name = edge.name().to_string();
+                changed |= ent.update_edge(&name, edge.into_value()).is_ok();
+            }
+        }
+        if changed {
+            updated.push(ent);
+        }
+    }
+
+    let updated_ids = updated.iter().map(|ent| ent.id()).collect();
+    for ent in updated {
+        store.store(ent)?;
+    }
+    for ent in removed.iter() {
+        store.delete(ent.as_ref())?;
+    }
+
+    Ok(Some(Removal::new(removed_ids, updated_ids)))
+}
+
+/// Updates the edges that are declared as the inverse of the edges of an ent
+/// that was just stored, given the version of the ent that was stored before
+/// it, if any
+///
+/// 1. Every ent newly reached through an edge that declares an inverse has
+///    the id of the stored ent added to its inverse edge, replacing the id
+///    held by an edge with at most one id
+/// 2. Every ent no longer reached through an edge that declares an inverse
+///    has the id of the stored ent taken out of its inverse edge, except
+///    where that edge must always have exactly one id
+///
+/// Each updated ent is stored and has its own inverse edges synced in turn,
+/// so an id replaced in an edge with at most one id is also taken out of the
+/// inverse edge of the ent it pointed at. Each change is applied once, so
+/// edges that are the inverse of one another stop syncing once they agree.
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+pub(crate) fn sync_inverse_edges<S: CascadeStore>(
+    store: &S,
+    old: Option<Box<dyn Ent>>,
+    new: Box<dyn Ent>,
+) -> Result<(), S::Error> {
+    let mut pending = vec![(old, new)];
+    let mut applied = HashSet::new();
+    while let Some((old, new)) = pending.pop() {
+        let id = new.id();
+        for (edge_id, inverse, is_added) in inverse_edge_changes(old.as_deref(), new.as_ref()) {
+            if !applied.insert((id, edge_id, inverse.clone(), is_added)) {
+                continue;
+            }
 
-        // NOTE: Logically, this should be impossible to reach since we only
-        //       call this when we know that the filter is not a transformation
-        Filter::IntoEdge(_) => unreachable!("Bug: Transformation in filter"),
+            let ent = match store.load(edge_id)? {
+                Some(ent) => ent,
+                None => continue,
+            };
+            let mut value = match ent.edge(&inverse) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let has_id = value.to_ids().contains(&id);
+            let is_ok = match (is_added, &mut value) {
+                (true, _) if has_id => false,
+                (true, EdgeValue::MaybeOne(maybe_id)) => {
+                    *maybe_id = Some(id);
+                    true
+                }
+                (true, _) => value.add_ids(vec![id]).is_ok(),
+                (false, _) => has_id && value.remove_ids(vec![id]).is_ok(),
+            };
+            if !is_ok {
+                continue;
+            }
+
+            let mut updated = dyn_clone::clone_box(ent.as_ref());
+            if updated.update_edge(&inverse, value).is_ok() {
+                store.store(dyn_clone::clone_box(updated.as_ref()))?;
+                pending.push((Some(ent), updated));
+            }
+        }
     }
+
+    Ok(())
 }
 
-fn with_ent<D: KeyValueDatabase, F: Fn(Box<dyn Ent>) -> bool>(db: &D, id: &Id, f: F) -> bool {
-    db.get(*id)
-        .map(|maybe_ent| maybe_ent.map(f).unwrap_or_default())
-        .unwrap_or_default()
+/// Returns the ids of ents added to or taken out of the edges of an ent that
+/// declare an inverse, paired with the name of the inverse edge and whether
+/// the id was added, going from the old version of the ent to the new one
+#[cfg(any(feature = "inmemory_db", feature = "sled_db"))]
+fn inverse_edge_changes(old: Option<&dyn Ent>, new: &dyn Ent) -> Vec<(Id, String, bool)> {
+    let mut changes = Vec::new();
+    let new_edges = new.edges();
+    for edge in new_edges.iter() {
+        if let Some(inverse) = edge.inverse() {
+            let old_ids = old
+                .and_then(|ent| ent.edge(edge.name()))
+                .map(|value| value.to_ids())
+                .unwrap_or_default();
+            let new_ids = edge.to_ids();
+
+            for id in old_ids.iter().filter(|id| !new_ids.contains(id)) {
+                changes.push((*id, inverse.to_string(), false));
+            }
+            for id in new_ids.iter().filter(|id| !old_ids.contains(id)) {
+                changes.push((*id, inverse.to_string(), true));
+            }
+        }
+    }
+
+    if let Some(old) = old {
+        for edge in old.edges() {
+            if let Some(inverse) = edge.inverse() {
+                if new_edges.iter().all(|e| e.name() != edge.name()) {
+                    for id in edge.to_ids() {
+                        changes.push((id, inverse.to_string(), false));
+                    }
+                }
+            }
+        }
+    }
+
+    changes
 }
 
 #[cfg(test)]
@@ -193,7 +1260,7 @@ mod tests {
 
     use super::*;
     use crate::*;
-    use std::collections::HashMap;
+    use std::{collections::HashMap, ops::Bound};
     use {Predicate as P, TypedPredicate as TP};
 
     macro_rules! impl_tests {
@@ -488,6 +1555,60 @@ mod tests {
                 query_and_assert(&db, q, &[2]);
             }
 
+            #[test]
+            fn find_all_should_use_index_for_timestamps() {
+                let db = $new_db;
+
+                // Create ents with enough time split between them for us to
+                // properly test timestamps
+                for i in 100..=104 {
+                    db.insert(Box::from(UntypedEnt::empty_with_id(i)))
+                        .expect(&format!("Failed to insert ent {}", i));
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                let created = |id| db.get(id).unwrap().expect("Missing ent").created();
+                let last_updated = |id| db.get(id).unwrap().expect("Missing ent").last_updated();
+
+                // Index should be able to answer lookups for timestamps
+                let range = (Bound::Excluded(created(102)), Bound::Unbounded);
+                assert_eq!(
+                    db.ids_for_created_range(range),
+                    vec![103, 104].into_iter().collect()
+                );
+                let range = (Bound::Unbounded, Bound::Included(last_updated(101)));
+                assert_eq!(
+                    db.ids_for_last_updated_range(range),
+                    vec![100, 101].into_iter().collect()
+                );
+
+                let q = Query::default().where_created(TP::less_than(created(102)));
+                query_and_assert(&db, q, &[100, 101]);
+
+                let q = Query::default().where_created(TP::in_range(created(101)..=created(103)));
+                query_and_assert(&db, q, &[101, 102, 103]);
+
+                let q = Query::default().where_last_updated(
+                    TP::equals(last_updated(100)) | TP::greater_than_or_equals(last_updated(104)),
+                );
+                query_and_assert(&db, q, &[100, 104]);
+
+                // Updating an ent should move it within the last updated index
+                // while leaving it where it was in the created index
+                let ent = db.get(100).unwrap().expect("Missing ent 100");
+                db.insert(ent).expect("Failed to update ent 100");
+                let q = Query::default().where_last_updated(TP::greater_than(last_updated(104)));
+                query_and_assert(&db, q, &[100]);
+                let q = Query::default().where_created(TP::less_than(created(101)));
+                query_and_assert(&db, q, &[100]);
+
+                // Removing an ent should remove it from the timestamp indexes
+                let _ = db.remove(104).unwrap();
+                let q = Query::default().where_created(TP::greater_than(created(103)));
+                query_and_assert(&db, q, &[]);
+                let range = (Bound::Unbounded, Bound::Unbounded);
+                assert_eq!(db.ids_for_last_updated_range(range).len(), 4);
+            }
+
             #[test]
             fn find_all_should_support_filtering_by_field() {
                 let db = new_test_database();
@@ -504,12 +1625,207 @@ mod tests {
             }
 
             #[test]
-            fn find_all_should_support_filtering_by_edge() {
-                let db = new_test_database();
+            fn find_all_should_use_index_for_indexed_fields() {
+                let db = $new_db;
+                let indexed_field =
+                    |value| Field::new_with_attributes("x", value, vec![FieldAttribute::Indexed]);
+                for (id, value) in vec![(100, 10), (101, 20), (102, 30), (103, 40), (104, 50)] {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(
+                            id,
+                            vec![indexed_field(value)],
+                            vec![],
+                        )))
+                        .unwrap();
+                }
 
-                // If ent's edge passes condition, it will be included in return
-                let q = Query::default().where_edge("a", Filter::Id(TP::equals(3)));
-                query_and_assert(&db, q, &[12]);
+                // Index should be able to answer lookups for the field
+                let key = IndexKey::from_value(&Value::from(30)).unwrap();
+                let range = (Bound::Included(key.clone()), Bound::Included(key));
+                assert_eq!(
+                    db.ids_for_field_range("x", range),
+                    Some(vec![102].into_iter().collect())
+                );
+
+                let q = Query::default().where_field("x", P::equals(30));
+                query_and_assert(&db, q, &[102]);
+
+                let q = Query::default().where_field("x", P::in_set(vec![10, 50, 60]));
+                query_and_assert(&db, q, &[100, 104]);
+
+                let q = Query::default().where_field("x", P::equals(20) | P::equals(40));
+                query_and_assert(&db, q, &[101, 103]);
+
+                let q = Query::default().where_field("x", P::greater_than(30));
+                query_and_assert(&db, q, &[103, 104]);
+
+                let q = Query::default().where_field("x", P::less_than_or_equals(20));
+                query_and_assert(&db, q, &[100, 101]);
+
+                let q = Query::default().where_field("x", P::in_range(20..=40));
+                query_and_assert(&db, q, &[101, 102, 103]);
+
+                // Index should be applied to ents already in the pipeline
+                let q = Query::default()
+                    .where_id(TP::greater_than(101))
+                    .where_field("x", P::less_than(45));
+                query_and_assert(&db, q, &[102, 103]);
+
+                // Overwriting an ent should replace its index entries
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        100,
+                        vec![indexed_field(35)],
+                        vec![],
+                    )))
+                    .unwrap();
+                let q = Query::default().where_field("x", P::equals(10));
+                query_and_assert(&db, q, &[]);
+                let q = Query::default().where_field("x", P::in_range(30..=40));
+                query_and_assert(&db, q, &[100, 102, 103]);
+
+                // Removing an ent should remove its index entries
+                let _ = db.remove(104).unwrap();
+                let q = Query::default().where_field("x", P::greater_than(40));
+                query_and_assert(&db, q, &[]);
+
+                // Once any ent stores the field without indexing it, the
+                // index can no longer answer lookups for the field
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        200,
+                        vec![Field::new("x", 30)],
+                        vec![],
+                    )))
+                    .unwrap();
+                let key = IndexKey::from_value(&Value::from(30)).unwrap();
+                let range = (Bound::Included(key.clone()), Bound::Included(key));
+                assert_eq!(db.ids_for_field_range("x", range), None);
+
+                let q = Query::default().where_field("x", P::equals(30));
+                query_and_assert(&db, q, &[102, 200]);
+            }
+
+            #[test]
+            fn find_all_should_support_searching_searchable_fields() {
+                let db = $new_db;
+                let searchable_field =
+                    |text| Field::new_with_attributes("s", text, vec![FieldAttribute::Searchable]);
+                for (id, text) in vec![
+                    (100, "The quick brown fox"),
+                    (101, "A lazy dog, a lazy afternoon"),
+                    (102, "Quick, lazy FOX!"),
+                    (103, "Nothing to see here"),
+                ] {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(
+                            id,
+                            vec![searchable_field(text)],
+                            vec![],
+                        )))
+                        .unwrap();
+                }
+
+                // Index should be able to answer lookups for each word
+                assert_eq!(
+                    db.ids_for_search_token("s", "fox"),
+                    Some(vec![100, 102].into_iter().collect())
+                );
+                assert_eq!(db.ids_for_search_token("s", "cat"), Some(EntIdSet::new()));
+
+                // Words are matched regardless of case or punctuation
+                let q = Query::default().where_search("s", "FOX");
+                query_and_assert(&db, q, &[100, 102]);
+
+                let q = Query::default().where_search("s", "lazy cat");
+                query_and_assert(&db, q, &[101, 102]);
+
+                let q = Query::default().where_search("s", "cat");
+                query_and_assert(&db, q, &[]);
+
+                let plan = db
+                    .explain(Query::default().where_search("s", "fox"))
+                    .unwrap();
+                assert_eq!(
+                    plan.to_string(),
+                    "1. look up `search(s) \"fox\"` using index on search(s) (exact) (~2 candidates)\n"
+                );
+
+                // Matches are ordered by the distinct words they contain and
+                // then by how often those words appear
+                let q = Query::default().where_matches("s", "quick lazy fox");
+                let ids: Vec<Id> = db.find_all(q).unwrap().iter().map(|ent| ent.id()).collect();
+                assert_eq!(ids, vec![102, 100, 101]);
+
+                let q = Query::default().where_matches("s", "lazy fox");
+                let ids: Vec<Id> = db.find_all(q).unwrap().iter().map(|ent| ent.id()).collect();
+                assert_eq!(ids, vec![102, 101, 100]);
+
+                // Overwriting or removing an ent should update its words
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        100,
+                        vec![searchable_field("A slow brown cat")],
+                        vec![],
+                    )))
+                    .unwrap();
+                let _ = db.remove(102).unwrap();
+                let q = Query::default().where_search("s", "fox cat");
+                query_and_assert(&db, q, &[100]);
+
+                // Once any ent stores the field without it being searchable,
+                // the index can no longer answer lookups for the field
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        200,
+                        vec![Field::new("s", "Another lazy cat")],
+                        vec![],
+                    )))
+                    .unwrap();
+                assert_eq!(db.ids_for_search_token("s", "cat"), None);
+
+                let q = Query::default().where_search("s", "cat");
+                query_and_assert(&db, q, &[100, 200]);
+            }
+
+            #[test]
+            fn find_all_should_support_filtering_by_nested_field_path() {
+                let db = new_test_database();
+
+                let q = Query::default().where_field("f.a", P::equals(3));
+                query_and_assert(&db, q, &[7]);
+
+                let q = Query::default().where_field("f[1]", P::equals(2));
+                query_and_assert(&db, q, &[8]);
+
+                let q = Query::default().where_field("f.b[0]", P::equals(3));
+                query_and_assert(&db, q, &[9]);
+
+                let q = Query::default().where_field("f.a[1]", P::equals(2));
+                query_and_assert(&db, q, &[9]);
+
+                // Paths that do not resolve never match, even when negated
+                let q = Query::default().where_field("f.c", P::IsNone);
+                query_and_assert(&db, q, &[]);
+
+                let q = Query::default().where_field("a.b", P::always());
+                query_and_assert(&db, q, &[]);
+
+                // Paths can also be used to order results
+                let q = Query::default()
+                    .where_field("f.a", P::always())
+                    .order_by_field("f.a", OrderDirection::Descending);
+                let ids: Vec<Id> = db.find_all(q).unwrap().iter().map(|ent| ent.id()).collect();
+                assert_eq!(ids, vec![7, 9]);
+            }
+
+            #[test]
+            fn find_all_should_support_filtering_by_edge() {
+                let db = new_test_database();
+
+                // If ent's edge passes condition, it will be included in return
+                let q = Query::default().where_edge("a", Filter::Id(TP::equals(3)));
+                query_and_assert(&db, q, &[12]);
 
                 // If already have ents in pipeline, they will be filtered by "edge"
                 let q = Query::default()
@@ -518,6 +1834,104 @@ mod tests {
                 query_and_assert(&db, q, &[10, 12]);
             }
 
+            #[test]
+            fn find_all_should_support_filtering_by_edge_count() {
+                let db = new_test_database();
+
+                let q = Query::default().where_edge_count("b", TP::greater_than(3));
+                query_and_assert(&db, q, &[11]);
+
+                let q = Query::default().where_edge_count("b", TP::equals(0));
+                query_and_assert(&db, q, &[12]);
+
+                // Ents without the edge never match, even for a total of zero
+                let q = Query::default().where_edge_count("c", TP::equals(0));
+                query_and_assert(&db, q, &[10]);
+
+                let q = Query::default().where_edge_count("c", TP::always());
+                query_and_assert(&db, q, &[10, 12]);
+
+                // Totals are looked up exactly using the edge count index
+                let plan = db
+                    .explain(Query::default().where_edge_count("b", TP::greater_than(3)))
+                    .unwrap();
+                assert_eq!(
+                    plan.to_string(),
+                    "1. look up `edge_count(b) > 3` using index on edge_count(b) (exact) (~1 candidate)\n"
+                );
+
+                // The index follows changes to the edges of an ent
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        12,
+                        vec![],
+                        vec![Edge::new("b", vec![1, 2, 3, 4])],
+                    )))
+                    .unwrap();
+                let q = Query::default().where_edge_count("b", TP::greater_than(3));
+                query_and_assert(&db, q, &[11, 12]);
+
+                let q = Query::default().where_edge_count("b", TP::in_range(3..=4));
+                query_and_assert(&db, q, &[10, 12]);
+            }
+
+            #[test]
+            fn find_all_should_support_combining_filters() {
+                let db = new_test_database();
+
+                let q = Query::default().where_or(vec![
+                    Filter::where_field("a", P::equals(1)),
+                    Filter::where_edge("a", Filter::Id(TP::equals(3))),
+                ]);
+                query_and_assert(&db, q, &[4, 12]);
+
+                let q = Query::default().where_and(vec![
+                    Filter::where_id(TP::less_than(7)),
+                    Filter::where_field("b", P::greater_than(2)),
+                ]);
+                query_and_assert(&db, q, &[5, 6]);
+
+                let q = Query::default().where_not(Filter::where_field("a", P::greater_than(1)));
+                query_and_assert(&db, q, &[1, 2, 3, 4, 7, 8, 9, 10, 11, 12]);
+
+                // Combinations can be nested using operators
+                let q = Query::default().chain(
+                    Filter::where_id(TP::equals(1))
+                        | (Filter::where_field("a", P::greater_than(1))
+                            & !Filter::where_field("b", P::equals(6))),
+                );
+                query_and_assert(&db, q, &[1, 5]);
+
+                // Combinations should work with lookups of ids by type
+                let t = db
+                    .get(1)
+                    .unwrap()
+                    .expect("Missing ent 1")
+                    .r#type()
+                    .to_string();
+                let q = Query::default().chain(
+                    Filter::where_type(TP::equals(t)) & !Filter::where_id(TP::less_than(11)),
+                );
+                query_and_assert(&db, q, &[11, 12]);
+
+                // If already have ents in pipeline, they will be filtered by
+                // the combination
+                let q = Query::default()
+                    .where_id(TP::greater_than(4))
+                    .where_or(vec![
+                        Filter::where_id(TP::equals(1) | TP::equals(12)),
+                        Filter::where_field("a", P::equals(3)),
+                    ]);
+                query_and_assert(&db, q, &[5, 12]);
+
+                // A transformation nested in a combination never matches
+                let q = Query::default().where_or(vec![
+                    Filter::where_into_edge("b"),
+                    Filter::where_id(TP::equals(2)),
+                ]);
+                query_and_assert(&db, q, &[2]);
+            }
+
             #[test]
             fn find_all_should_support_transforming_into_edge() {
                 let db = new_test_database();
@@ -536,6 +1950,1143 @@ mod tests {
                     .where_into_edge("b");
                 query_and_assert(&db, q, &[3, 4, 5]);
             }
+
+            /// Adds ents 13-16 to the database, where each has a "next" edge
+            /// that forms a cycle between 14, 15, and 16 and only 16 has a
+            /// field, which is "a" of 7
+            fn insert_next_edges<T: KeyValueDatabase>(db: &T) {
+                for (id, next) in vec![(13, vec![14, 15]), (14, vec![15]), (15, vec![16])] {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(
+                            id,
+                            vec![],
+                            vec![Edge::new("next", next)],
+                        )))
+                        .unwrap();
+                }
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        16,
+                        vec![Field::new("a", 7)],
+                        vec![Edge::new("next", vec![14])],
+                    )))
+                    .unwrap();
+            }
+
+            #[test]
+            fn find_all_should_support_transforming_into_edge_recursively() {
+                let db = new_test_database();
+                insert_next_edges(&db);
+
+                // Will follow the edge until no new ents are reached, where
+                // ents are included only if reached by at least one hop
+                let q = Query::default()
+                    .where_id(TP::equals(13))
+                    .where_into_edge_recursive("next", None);
+                query_and_assert(&db, q, &[14, 15, 16]);
+
+                // Will stop following the edge after the maximum hops
+                let q = Query::default()
+                    .where_id(TP::equals(13))
+                    .where_into_edge_recursive("next", Some(1));
+                query_and_assert(&db, q, &[14, 15]);
+
+                let q = Query::default()
+                    .where_id(TP::equals(13))
+                    .where_into_edge_recursive("next", Some(0));
+                query_and_assert(&db, q, &[]);
+
+                // Will include the starting ent if the edge cycles back to it
+                let q = Query::default()
+                    .where_id(TP::equals(14))
+                    .where_into_edge_recursive("next", None);
+                query_and_assert(&db, q, &[14, 15, 16]);
+
+                // Will continue filtering the ents that were reached
+                let q = Query::default()
+                    .where_id(TP::equals(13))
+                    .where_into_edge_recursive("next", None)
+                    .where_field("a", P::equals(7));
+                query_and_assert(&db, q, &[16]);
+
+                let plan = db
+                    .explain(
+                        Query::default()
+                            .where_id(TP::equals(13))
+                            .where_into_edge_recursive("next", Some(2)),
+                    )
+                    .unwrap();
+                assert_eq!(
+                    plan.steps()[1],
+                    PlanStep::IntoEdgeRecursive {
+                        name: String::from("next"),
+                        max_depth: Some(2),
+                    }
+                );
+            }
+
+            #[test]
+            fn find_all_should_support_filtering_by_recursive_edge() {
+                let db = new_test_database();
+                insert_next_edges(&db);
+
+                // Will match ents that can reach a matching ent transitively,
+                // including 16 itself through the cycle back to it
+                let q = Query::default().where_edge_recursive(
+                    "next",
+                    None,
+                    Filter::where_field("a", P::equals(7)),
+                );
+                query_and_assert(&db, q, &[13, 14, 15, 16]);
+
+                let q = Query::default().where_edge_recursive(
+                    "next",
+                    Some(1),
+                    Filter::where_field("a", P::equals(7)),
+                );
+                query_and_assert(&db, q, &[15]);
+
+                let q = Query::default().where_edge_recursive(
+                    "next",
+                    Some(2),
+                    Filter::where_field("a", P::equals(7)),
+                );
+                query_and_assert(&db, q, &[13, 14, 15]);
+            }
+
+            #[test]
+            fn traverse_should_return_reached_ids_with_their_depth() {
+                let db = new_test_database();
+                insert_next_edges(&db);
+
+                assert_eq!(
+                    db.traverse(13, "next", None).unwrap(),
+                    vec![(14, 1), (15, 1), (16, 2)]
+                );
+                assert_eq!(
+                    db.traverse(14, "next", None).unwrap(),
+                    vec![(15, 1), (16, 2), (14, 3)]
+                );
+                assert_eq!(db.traverse(13, "next", Some(1)).unwrap(), vec![(14, 1), (15, 1)]);
+                assert_eq!(db.traverse(13, "b", None).unwrap(), vec![]);
+                assert_eq!(db.traverse(999, "next", None).unwrap(), vec![]);
+            }
+
+            #[test]
+            fn shortest_path_should_return_fewest_hops_between_ids() {
+                let db = new_test_database();
+                insert_next_edges(&db);
+
+                assert_eq!(
+                    db.shortest_path(13, 16, "next").unwrap(),
+                    Some(vec![13, 15, 16])
+                );
+                assert_eq!(
+                    db.shortest_path(16, 15, "next").unwrap(),
+                    Some(vec![16, 14, 15])
+                );
+                assert_eq!(db.shortest_path(13, 13, "next").unwrap(), Some(vec![13]));
+                assert_eq!(db.shortest_path(16, 13, "next").unwrap(), None);
+                assert_eq!(db.shortest_path(999, 999, "next").unwrap(), None);
+            }
+
+            #[test]
+            fn referrers_should_return_ids_of_ents_with_edge_pointing_at_id() {
+                let db = new_test_database();
+                insert_next_edges(&db);
+
+                assert_eq!(db.referrers(15, "next").unwrap(), vec![13, 14]);
+                assert_eq!(db.referrers(14, "next").unwrap(), vec![13, 16]);
+                assert!(db.referrers(13, "next").unwrap().is_empty());
+                assert!(db.referrers(15, "b").unwrap().is_empty());
+
+                // Referrers should follow ents as their edges change and as
+                // they are removed
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        14,
+                        vec![],
+                        vec![Edge::new("next", vec![16])],
+                    )))
+                    .unwrap();
+                assert!(db.remove(13).unwrap());
+                assert!(db.referrers(15, "next").unwrap().is_empty());
+                assert_eq!(db.referrers(16, "next").unwrap(), vec![14, 15]);
+                assert_eq!(db.referrers(14, "next").unwrap(), vec![16]);
+            }
+
+            #[test]
+            fn find_all_should_look_up_edges_pointing_at_ids_using_referrer_index() {
+                let db = new_test_database();
+                insert_next_edges(&db);
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        17,
+                        vec![],
+                        vec![Edge::new("next", vec![999])],
+                    )))
+                    .unwrap();
+
+                let q = Query::default().where_edge("next", Filter::Id(TP::equals(15)));
+                query_and_assert(&db, q, &[13, 14]);
+
+                // Edges pointing at ents that do not exist still match
+                let q = Query::default().where_edge(
+                    "next",
+                    Filter::Id(TP::or(vec![TP::equals(16), TP::equals(999)])),
+                );
+                query_and_assert(&db, q, &[15, 17]);
+
+                // Other filters of the edge are checked against each ent
+                let q = Query::default().where_edge("next", Filter::Id(TP::greater_than(15)));
+                query_and_assert(&db, q, &[15, 17]);
+
+                let plan = db
+                    .explain(Query::default().where_edge("next", Filter::Id(TP::equals(15))))
+                    .unwrap();
+                assert_eq!(
+                    plan.to_string(),
+                    "1. look up `edge(next).id = 15` using index on referrers(next) (exact) (~2 candidates)\n"
+                );
+            }
+
+            #[test]
+            fn find_all_should_apply_filters_in_planned_order() {
+                let db = new_test_database();
+                let ts = <UntypedEnt as EntType>::type_str();
+
+                // Filters on edges given first are still applied after the
+                // filters answered by an index, producing the same results
+                let q = Query::default()
+                    .where_edge("b", Filter::Id(TP::equals(6)))
+                    .where_type(TP::equals(ts.to_string()))
+                    .where_id(TP::greater_than(9));
+                query_and_assert(&db, q, &[11]);
+
+                let q = Query::default()
+                    .where_field("a", P::greater_than(1))
+                    .where_id(TP::less_than(6));
+                query_and_assert(&db, q, &[5]);
+
+                // Filters are not moved across a transformation into an edge
+                let q = Query::default()
+                    .where_id(TP::equals(10))
+                    .where_into_edge("b")
+                    .where_id(TP::greater_than(3));
+                query_and_assert(&db, q, &[4, 5]);
+
+                let q = Query::default()
+                    .where_field("a", P::greater_than(1))
+                    .where_id(TP::less_than(6));
+                let ids: Vec<Id> = db
+                    .find_iter(q)
+                    .unwrap()
+                    .map(|ent| ent.unwrap().id())
+                    .collect();
+                assert_eq!(ids, vec![5]);
+            }
+
+            #[test]
+            fn explain_should_describe_plan_with_indexes_and_estimates() {
+                let db = new_test_database();
+                let ts = <UntypedEnt as EntType>::type_str();
+
+                let q = Query::default()
+                    .where_edge("b", Filter::Id(TP::equals(6)))
+                    .where_type(TP::equals(ts.to_string()))
+                    .where_id(TP::equals(10) | TP::equals(11));
+                let plan = db.explain(q).unwrap();
+                assert_eq!(
+                    plan.steps().iter().map(PlanStep::estimate).collect::<Vec<_>>(),
+                    vec![Some(1), Some(1), Some(1)],
+                );
+                assert_eq!(
+                    plan.to_string(),
+                    concat!(
+                        "1. look up `edge(b).id = 6` using index on referrers(b) (exact) (~1 candidate)\n",
+                        "2. look up `id or(= 10, = 11)` using index on id (exact) (~1 candidate)\n",
+                        concat!(
+                            "3. look up `type = \"entity::ent::UntypedEnt\"` ",
+                            "using index on type (exact) (~1 candidate)\n",
+                        ),
+                    )
+                );
+
+                // Edges checked by anything other than specific ids still
+                // load the ents of each edge
+                let q = Query::default()
+                    .where_edge("b", Filter::Id(TP::greater_than(5)))
+                    .where_id(TP::equals(10) | TP::equals(11));
+                let plan = db.explain(q).unwrap();
+                assert_eq!(
+                    plan.to_string(),
+                    concat!(
+                        "1. look up `id or(= 10, = 11)` using index on id (exact) (~2 candidates)\n",
+                        "2. check `edge(b).id > 5`, loading each ent and the ents of its edges (~2 candidates)\n",
+                    )
+                );
+
+                // Without any index, every ent is scanned
+                let q = Query::default()
+                    .where_field("a", P::equals(3))
+                    .where_into_edge("b");
+                let plan = db.explain(q).unwrap();
+                assert_eq!(
+                    plan.to_string(),
+                    concat!(
+                        "1. scan all ents (~12 candidates)\n",
+                        "2. check `field(a) = 3i32`, loading each ent (~12 candidates)\n",
+                        "3. replace each candidate with the ents of edge b (unknown candidates)\n",
+                    )
+                );
+                assert_eq!(plan.estimate(), None);
+            }
+
+            #[test]
+            fn find_all_should_support_ordering_and_windowing_results() {
+                let db = new_test_database();
+                let find_ids = |q: Query| {
+                    db.find_all(q)
+                        .expect("Failed to retrieve ents")
+                        .iter()
+                        .map(|ent| ent.id())
+                        .collect::<Vec<Id>>()
+                };
+
+                // By default, results are ordered by id
+                let q = Query::default().where_id(TP::less_than(5));
+                assert_eq!(find_ids(q), vec![1, 2, 3, 4]);
+
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_id(OrderDirection::Descending)
+                    .limit(3);
+                assert_eq!(find_ids(q), vec![12, 11, 10]);
+
+                let q = Query::default().where_id(TP::always()).offset(10).limit(5);
+                assert_eq!(find_ids(q), vec![11, 12]);
+
+                // Ents missing the field are placed last, ordered by id
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_field("a", OrderDirection::Descending)
+                    .limit(4);
+                assert_eq!(find_ids(q), vec![6, 5, 4, 1]);
+
+                let q = Query::default()
+                    .where_field("a", P::greater_than(1))
+                    .order_by_field("a", OrderDirection::Ascending);
+                assert_eq!(find_ids(q), vec![5, 6]);
+
+                // Update ents with enough time split between them for us to
+                // properly test ordering by last updated time
+                for i in vec![3, 1, 2] {
+                    let ent = db.get(i).unwrap().expect(&format!("Missing ent {}", i));
+                    db.insert(ent)
+                        .expect(&format!("Failed to update ent {}", i));
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_last_updated(OrderDirection::Descending)
+                    .limit(3);
+                assert_eq!(find_ids(q), vec![2, 1, 3]);
+
+                let q = Query::default()
+                    .where_id(TP::less_than(4))
+                    .order_by_last_updated(OrderDirection::Ascending)
+                    .offset(1);
+                assert_eq!(find_ids(q), vec![1, 2]);
+            }
+
+            #[test]
+            fn find_iter_should_produce_ents_resumable_from_cursor() {
+                let db = new_test_database();
+                let ids_of = |ents: Vec<DatabaseResult<Box<dyn Ent>>>| {
+                    ents.into_iter()
+                        .map(|ent| ent.expect("Failed to retrieve ent").id())
+                        .collect::<Vec<Id>>()
+                };
+
+                // Stop partway through the results, and then continue from
+                // the last ent returned using its cursor
+                let q = Query::default().where_id(TP::always());
+                let mut iter = db.find_iter(q.clone()).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.by_ref().take(5).collect()), vec![1, 2, 3, 4, 5]);
+
+                let cursor = iter.into_cursor().expect("Missing cursor");
+                let cursor: Cursor = cursor.to_string().parse().expect("Bad cursor");
+                let iter = db.find_iter(q.after(cursor)).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.collect()), vec![6, 7, 8, 9, 10, 11, 12]);
+
+                // Filters, offset, and limit apply while iterating
+                let q = Query::default()
+                    .where_field("a", P::greater_than(1))
+                    .order_by_id(OrderDirection::Descending)
+                    .limit(1);
+                let iter = db.find_iter(q).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.collect()), vec![6]);
+
+                let q = Query::default()
+                    .where_id(TP::equals(3) | TP::equals(5) | TP::equals(9))
+                    .order_by_id(OrderDirection::Descending)
+                    .offset(1);
+                let iter = db.find_iter(q).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.collect()), vec![5, 3]);
+
+                // Ordering by something other than id should still resume
+                let q = Query::default()
+                    .where_id(TP::less_than(7))
+                    .order_by_field("a", OrderDirection::Descending);
+                let mut iter = db.find_iter(q.clone()).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.by_ref().take(4).collect()), vec![6, 5, 4, 1]);
+
+                let q = q.after(iter.into_cursor().expect("Missing cursor"));
+                let iter = db.find_iter(q.clone()).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.collect()), vec![2, 3]);
+                query_and_assert(&db, q, &[2, 3]);
+
+                // Transforming into edges should still be supported
+                let q = Query::default()
+                    .where_id(TP::equals(10) | TP::equals(12))
+                    .where_into_edge("b");
+                let iter = db.find_iter(q).expect("Failed to find ents");
+                assert_eq!(ids_of(iter.collect()), vec![3, 4, 5]);
+            }
+
+            #[test]
+            fn find_iter_should_fail_if_cursor_does_not_match_ordering() {
+                let db = new_test_database();
+
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_created(OrderDirection::Ascending);
+                let cursor = q.cursor_for(&UntypedEnt::empty_with_id(3));
+
+                let q = Query::default().where_id(TP::always()).after(cursor);
+                match db.find_iter(q.clone()) {
+                    Err(DatabaseError::InvalidCursor) => {}
+                    x => panic!("Unexpected result: {:?}", x.map(|_| ())),
+                }
+                match db.find_all(q) {
+                    Err(DatabaseError::InvalidCursor) => {}
+                    x => panic!("Unexpected result: {:?}", x.map(|_| ())),
+                }
+            }
+
+            #[test]
+            fn find_iter_should_see_changes_to_ents_reached_through_edges() {
+                let db = $new_db;
+                let insert = |ent: UntypedEnt| {
+                    let _ = db.insert(Box::from(ent)).unwrap();
+                };
+                let target = |value| {
+                    UntypedEnt::from_collections(100, vec![Field::new("x", value)], vec![])
+                };
+                insert(target(1));
+                for id in 1..=3 {
+                    insert(UntypedEnt::from_collections(id, vec![], vec![Edge::new("e", 100)]));
+                }
+
+                let q = Query::default()
+                    .where_id(TP::less_than(100))
+                    .where_edge("e", Filter::Field(String::from("x"), P::equals(1)));
+                let mut iter = db.find_iter(q).expect("Failed to find ents");
+                assert_eq!(iter.next().map(|ent| ent.unwrap().id()), Some(1));
+
+                // Ents after the change see the ent it made through the edge
+                insert(target(2));
+                assert!(iter.next().is_none());
+            }
+
+            #[test]
+            fn project_should_return_values_of_fields_for_matching_ents() {
+                let db = new_test_database();
+                let values = |entries: Vec<(&str, Value)>| {
+                    entries
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value))
+                        .collect::<HashMap<String, Value>>()
+                };
+                let fields = |names: &[&str]| {
+                    names.iter().map(|x| x.to_string()).collect::<Vec<String>>()
+                };
+
+                let q = Query::default().where_field("a", P::greater_than(1));
+                assert_eq!(
+                    db.project(q, fields(&["a", "b"])).unwrap(),
+                    vec![
+                        (5, values(vec![("a", Value::from(3)), ("b", Value::from(4))])),
+                        (6, values(vec![("a", Value::from(5)), ("b", Value::from(6))])),
+                    ]
+                );
+
+                // Names can be paths into fields, and names without a value
+                // are left out
+                let q = Query::default().where_id(TP::equals(3) | TP::equals(7));
+                assert_eq!(
+                    db.project(q, fields(&["f.a", "f.c", "a"])).unwrap(),
+                    vec![(3, values(vec![])), (7, values(vec![("f.a", Value::from(3))]))]
+                );
+
+                // Ordering and windowing apply the same as finding ents
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_id(OrderDirection::Descending)
+                    .offset(6)
+                    .limit(2);
+                assert_eq!(
+                    db.project(q, fields(&["a"])).unwrap(),
+                    vec![(6, values(vec![("a", Value::from(5))])), (5, values(vec![("a", Value::from(3))]))]
+                );
+
+                let q = Query::default()
+                    .where_id(TP::less_than(7))
+                    .order_by_field("b", OrderDirection::Ascending)
+                    .limit(2);
+                assert_eq!(
+                    db.project(q, fields(&["b"])).unwrap(),
+                    vec![(4, values(vec![("b", Value::from(2))])), (5, values(vec![("b", Value::from(4))]))]
+                );
+            }
+
+            #[test]
+            fn count_should_return_total_ents_that_match_query() {
+                let db = new_test_database();
+
+                let q = Query::default().where_id(TP::always());
+                assert_eq!(db.count(q).unwrap(), 12);
+
+                // Ids that do not exist are not counted
+                let q = Query::default().where_id(TP::equals(3) | TP::equals(999));
+                assert_eq!(db.count(q).unwrap(), 1);
+
+                let q = Query::default().where_field("a", P::greater_than(1));
+                assert_eq!(db.count(q).unwrap(), 2);
+
+                let q = Query::default()
+                    .where_field("a", P::greater_than(1))
+                    .offset(1);
+                assert_eq!(db.count(q).unwrap(), 1);
+
+                let q = Query::default().where_id(TP::always()).limit(5);
+                assert_eq!(db.count(q).unwrap(), 5);
+
+                let q = Query::default()
+                    .where_id(TP::equals(10) | TP::equals(12))
+                    .where_into_edge("b");
+                assert_eq!(db.count(q).unwrap(), 3);
+
+                // Resuming from a cursor counts only the remaining ents
+                let q = Query::default()
+                    .where_field("a", P::greater_than(0))
+                    .order_by_field("a", OrderDirection::Descending);
+                let cursor = q.cursor_for(&UntypedEnt::from_collections(
+                    5,
+                    vec![Field::new("a", 3)],
+                    vec![],
+                ));
+                assert_eq!(db.count(q.after(cursor)).unwrap(), 1);
+
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_id(OrderDirection::Ascending);
+                let cursor = q.cursor_for(&UntypedEnt::empty_with_id(9));
+                assert_eq!(db.count(q.after(cursor.clone())).unwrap(), 3);
+
+                let q = Query::default().where_id(TP::always()).after(cursor);
+                match db.count(q) {
+                    Err(DatabaseError::InvalidCursor) => {}
+                    x => panic!("Unexpected result: {:?}", x),
+                }
+            }
+
+            #[test]
+            fn exists_should_return_true_if_any_ent_matches_query() {
+                let db = new_test_database();
+
+                let q = Query::default().where_id(TP::equals(3));
+                assert!(db.exists(q).unwrap());
+
+                let q = Query::default().where_id(TP::equals(999));
+                assert!(!db.exists(q).unwrap());
+
+                let q = Query::default().where_field("a", P::equals(5));
+                assert!(db.exists(q).unwrap());
+
+                let q = Query::default().where_field("a", P::equals(7));
+                assert!(!db.exists(q).unwrap());
+
+                let q = Query::default().where_id(TP::equals(3)).offset(1);
+                assert!(!db.exists(q).unwrap());
+
+                let q = Query::default().where_id(TP::always()).limit(0);
+                assert!(!db.exists(q).unwrap());
+            }
+
+            #[test]
+            fn find_first_should_return_first_ent_that_matches_query() {
+                let db = new_test_database();
+
+                let q = Query::default()
+                    .where_field("a", P::greater_than(1))
+                    .order_by_field("a", OrderDirection::Descending);
+                assert_eq!(db.find_first(q).unwrap().map(|ent| ent.id()), Some(6));
+
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_id(OrderDirection::Descending);
+                assert_eq!(db.find_first(q).unwrap().map(|ent| ent.id()), Some(12));
+
+                let q = Query::default().where_id(TP::always()).offset(2);
+                assert_eq!(db.find_first(q).unwrap().map(|ent| ent.id()), Some(3));
+
+                let q = Query::default().where_id(TP::equals(999));
+                assert!(db.find_first(q).unwrap().is_none());
+            }
+
+            #[test]
+            fn aggregate_should_compute_value_over_ents_that_match_query() {
+                let db = new_test_database();
+                let q = Query::default().where_id(TP::always());
+
+                let value = db.aggregate(q.clone(), Aggregate::count()).unwrap();
+                assert_eq!(value, Value::from(12usize));
+
+                let value = db.aggregate(q.clone(), Aggregate::sum("a")).unwrap();
+                assert_eq!(value, Value::from(9));
+
+                let value = db.aggregate(q.clone(), Aggregate::min("a")).unwrap();
+                assert_eq!(value, Value::from(1));
+
+                let value = db.aggregate(q.clone(), Aggregate::max("a")).unwrap();
+                assert_eq!(value, Value::from(5));
+
+                let value = db.aggregate(q.clone(), Aggregate::average("a")).unwrap();
+                assert_eq!(value, Value::from(3.0));
+
+                // Fields that are missing from every ent produce nothing
+                let value = db.aggregate(q, Aggregate::max("missing")).unwrap();
+                assert_eq!(value, Value::Optional(None));
+
+                // Windows are applied before aggregating
+                let q = Query::default()
+                    .where_id(TP::always())
+                    .order_by_field("a", OrderDirection::Descending)
+                    .limit(2);
+                let value = db.aggregate(q, Aggregate::sum("a")).unwrap();
+                assert_eq!(value, Value::from(8));
+            }
+
+            #[test]
+            fn aggregate_by_should_compute_value_for_each_group() {
+                let db = new_test_database();
+                let q = Query::default().where_id(TP::always());
+
+                let groups = db
+                    .aggregate_by(
+                        q.clone(),
+                        Aggregate::count(),
+                        GroupBy::Field(String::from("a")),
+                    )
+                    .unwrap();
+                assert_eq!(
+                    groups,
+                    vec![
+                        (Value::Optional(None), Value::from(9usize)),
+                        (Value::from(1), Value::from(1usize)),
+                        (Value::from(3), Value::from(1usize)),
+                        (Value::from(5), Value::from(1usize)),
+                    ]
+                );
+
+                let groups = db
+                    .aggregate_by(q.clone(), Aggregate::sum("b"), GroupBy::Type)
+                    .unwrap();
+                assert_eq!(
+                    groups,
+                    vec![(Value::from(UntypedEnt::type_str()), Value::from(12))]
+                );
+
+                let q = Query::default().where_id(TP::equals(999));
+                let groups = db
+                    .aggregate_by(q, Aggregate::count(), GroupBy::Type)
+                    .unwrap();
+                assert!(groups.is_empty());
+            }
+
+            #[test]
+            fn remove_cascading_should_apply_deletion_policies_of_edges() {
+                let db = $new_db;
+                let edge = |name, ids: Vec<Id>, policy| {
+                    Edge::new_with_deletion_policy(name, ids, policy)
+                };
+                let insert = |id, edges| {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(id, vec![], edges)))
+                        .unwrap();
+                };
+
+                // 100 -> 101 -> 102 -> 100 form a cycle of deep deletions,
+                // while 102 also deeply refers to an ent that does not exist
+                insert(
+                    100,
+                    vec![
+                        edge("child", vec![101], EdgeDeletionPolicy::DeepDelete),
+                        edge("owner", vec![200], EdgeDeletionPolicy::ShallowDelete),
+                    ],
+                );
+                insert(
+                    101,
+                    vec![edge("child", vec![102], EdgeDeletionPolicy::DeepDelete)],
+                );
+                insert(
+                    102,
+                    vec![
+                        edge("child", vec![100, 999], EdgeDeletionPolicy::DeepDelete),
+                        edge("owner", vec![200, 201], EdgeDeletionPolicy::ShallowDelete),
+                    ],
+                );
+
+                // 200 refers back to removed ents alongside one that remains,
+                // while 201 has an edge that cannot lose its only id
+                insert(
+                    200,
+                    vec![edge("items", vec![100, 300, 102], EdgeDeletionPolicy::Nothing)],
+                );
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        201,
+                        vec![],
+                        vec![Edge::new("item", EdgeValue::One(101))],
+                    )))
+                    .unwrap();
+                insert(300, vec![]);
+
+                let removal = db.remove_cascading(100).unwrap().expect("Missing removal");
+                assert_eq!(removal.removed(), &[100, 101, 102]);
+                assert_eq!(removal.cascaded(), &[101, 102]);
+                assert_eq!(removal.updated(), &[200]);
+
+                for id in &[100, 101, 102] {
+                    assert!(db.get(*id).unwrap().is_none(), "Ent {} not removed", id);
+                }
+                assert_eq!(
+                    db.get(200).unwrap().unwrap().edge("items"),
+                    Some(EdgeValue::Many(vec![300]))
+                );
+                assert_eq!(
+                    db.get(201).unwrap().unwrap().edge("item"),
+                    Some(EdgeValue::One(101))
+                );
+                assert!(db.get(300).unwrap().is_some());
+
+                // Indexes should follow the updated and removed ents
+                let q = Query::default().where_edge_count("items", TP::equals(1));
+                query_and_assert(&db, q, &[200]);
+                let q = Query::default().where_edge_count("child", TP::always());
+                query_and_assert(&db, q, &[]);
+
+                // Removing an ent that does not exist changes nothing
+                assert_eq!(db.remove_cascading(100).unwrap(), None);
+                assert!(!db.remove(100).unwrap());
+            }
+
+            #[test]
+            fn remove_cascading_should_apply_restrict_and_nullify_policies_of_referrers() {
+                let db = $new_db;
+                let edge = |name, value: EdgeValue, policy| {
+                    Edge::new_with_deletion_policy(name, value, policy)
+                };
+                let insert = |id, edges| {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(id, vec![], edges)))
+                        .unwrap();
+                };
+
+                // 100 deeply removes 101, which refers back to 100 in a way
+                // that would restrict its removal if 101 were to remain
+                insert(
+                    100,
+                    vec![edge(
+                        "child",
+                        EdgeValue::One(101),
+                        EdgeDeletionPolicy::DeepDelete,
+                    )],
+                );
+                insert(
+                    101,
+                    vec![edge(
+                        "parent",
+                        EdgeValue::One(100),
+                        EdgeDeletionPolicy::Restrict,
+                    )],
+                );
+                insert(
+                    200,
+                    vec![edge(
+                        "items",
+                        EdgeValue::Many(vec![101, 300]),
+                        EdgeDeletionPolicy::Restrict,
+                    )],
+                );
+                insert(
+                    201,
+                    vec![edge(
+                        "item",
+                        EdgeValue::MaybeOne(Some(100)),
+                        EdgeDeletionPolicy::Nullify,
+                    )],
+                );
+                insert(
+                    202,
+                    vec![edge(
+                        "items",
+                        EdgeValue::Many(vec![100, 300, 101]),
+                        EdgeDeletionPolicy::Nullify,
+                    )],
+                );
+                insert(
+                    203,
+                    vec![edge(
+                        "item",
+                        EdgeValue::One(100),
+                        EdgeDeletionPolicy::Nullify,
+                    )],
+                );
+                insert(300, vec![]);
+
+                // Nothing should change while 200 still points at 101
+                match db.remove_cascading(100) {
+                    Err(DatabaseError::RestrictedRemoval { id, referrers }) => {
+                        assert_eq!(id, 101);
+                        assert_eq!(referrers, vec![200]);
+                    }
+                    x => panic!("Unexpected result: {:?}", x),
+                }
+                assert!(db.get(100).unwrap().is_some());
+                assert!(db.get(101).unwrap().is_some());
+                assert_eq!(
+                    db.get(201).unwrap().unwrap().edge("item"),
+                    Some(EdgeValue::MaybeOne(Some(100)))
+                );
+
+                // Once 200 no longer points at 101, the removal goes through
+                insert(
+                    200,
+                    vec![edge(
+                        "items",
+                        EdgeValue::Many(vec![300]),
+                        EdgeDeletionPolicy::Restrict,
+                    )],
+                );
+                let removal = db.remove_cascading(100).unwrap().expect("Missing removal");
+                assert_eq!(removal.removed(), &[100, 101]);
+                assert_eq!(removal.updated(), &[201, 202]);
+
+                assert_eq!(
+                    db.get(201).unwrap().unwrap().edge("item"),
+                    Some(EdgeValue::MaybeOne(None))
+                );
+                assert_eq!(
+                    db.get(202).unwrap().unwrap().edge("items"),
+                    Some(EdgeValue::Many(vec![300]))
+                );
+                assert_eq!(
+                    db.get(203).unwrap().unwrap().edge("item"),
+                    Some(EdgeValue::One(100))
+                );
+            }
+
+            #[test]
+            fn commit_transaction_should_apply_restrict_policies_of_earlier_operations() {
+                let db = $new_db;
+                let referrer = |id| {
+                    UntypedEnt::from_collections(
+                        id,
+                        vec![],
+                        vec![Edge::new_with_deletion_policy(
+                            "owner",
+                            EdgeValue::MaybeOne(Some(100)),
+                            EdgeDeletionPolicy::Restrict,
+                        )],
+                    )
+                };
+                let _ = db
+                    .insert(Box::from(UntypedEnt::empty_with_id(100)))
+                    .unwrap();
+                let _ = db.insert(Box::from(referrer(200))).unwrap();
+
+                // Inserting another referrer before the removal restricts it,
+                // leaving the database as it was
+                let result = db.transaction(|tx| {
+                    tx.insert(Box::from(UntypedEnt::empty_with_id(300)));
+                    tx.remove(200);
+                    tx.insert(Box::from(referrer(201)));
+                    tx.remove(100);
+                    Ok(())
+                });
+                match result {
+                    Err(DatabaseError::RestrictedRemoval { id, referrers }) => {
+                        assert_eq!(id, 100);
+                        assert_eq!(referrers, vec![201]);
+                    }
+                    x => panic!("Unexpected result: {:?}", x),
+                }
+                for id in &[100, 200] {
+                    assert!(db.get(*id).unwrap().is_some(), "Ent {} missing", id);
+                }
+                for id in &[201, 300] {
+                    assert!(db.get(*id).unwrap().is_none(), "Ent {} inserted", id);
+                }
+
+                // Removing the referrer before the ent lets the removal through
+                let _ = db
+                    .transaction(|tx| {
+                        tx.remove(200);
+                        tx.remove(100);
+                        Ok(())
+                    })
+                    .unwrap();
+                assert!(db.get(100).unwrap().is_none());
+                assert!(db.get(200).unwrap().is_none());
+            }
+
+            #[test]
+            fn insert_should_sync_edges_declared_as_inverse_of_changed_edges() {
+                let db = $new_db;
+                let insert = |id, edges| {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(id, vec![], edges)))
+                        .unwrap();
+                };
+                let group = |id| {
+                    insert(
+                        id,
+                        vec![
+                            Edge::new("members", EdgeValue::Many(vec![])).with_inverse("groups"),
+                            Edge::new("owner", EdgeValue::MaybeOne(None)).with_inverse("owned"),
+                        ],
+                    )
+                };
+                let user = |id, groups, owned| {
+                    insert(
+                        id,
+                        vec![
+                            Edge::new("groups", EdgeValue::Many(groups)).with_inverse("members"),
+                            Edge::new("owned", EdgeValue::Many(owned)).with_inverse("owner"),
+                        ],
+                    )
+                };
+                let edge = |id, name| db.get(id).unwrap().unwrap().edge(name).unwrap();
+
+                group(100);
+                group(101);
+
+                // Joining groups adds the user as a member of each
+                user(200, vec![100, 101], vec![]);
+                assert_eq!(edge(100, "members"), EdgeValue::Many(vec![200]));
+                assert_eq!(edge(101, "members"), EdgeValue::Many(vec![200]));
+
+                // Leaving a group takes the user out of its members
+                user(200, vec![101], vec![]);
+                assert_eq!(edge(100, "members"), EdgeValue::Many(vec![]));
+                assert_eq!(edge(101, "members"), EdgeValue::Many(vec![200]));
+
+                // Syncing in the other direction works the same way
+                user(201, vec![], vec![100]);
+                assert_eq!(edge(100, "owner"), EdgeValue::MaybeOne(Some(201)));
+
+                // Taking over an edge with at most one id takes the ent out of
+                // the edge of its previous owner
+                user(202, vec![], vec![100]);
+                assert_eq!(edge(100, "owner"), EdgeValue::MaybeOne(Some(202)));
+                assert_eq!(edge(201, "owned"), EdgeValue::Many(vec![]));
+
+                // Updating the group keeps its members in sync, too
+                insert(
+                    101,
+                    vec![
+                        Edge::new("members", EdgeValue::Many(vec![201])).with_inverse("groups"),
+                        Edge::new("owner", EdgeValue::MaybeOne(None)).with_inverse("owned"),
+                    ],
+                );
+                assert_eq!(edge(200, "groups"), EdgeValue::Many(vec![]));
+                assert_eq!(edge(201, "groups"), EdgeValue::Many(vec![101]));
+
+                // Inserts within a transaction see each other's changes
+                db.transaction(|tx| {
+                    tx.insert(Box::from(UntypedEnt::from_collections(
+                        203,
+                        vec![],
+                        vec![Edge::new("groups", EdgeValue::Many(vec![100, 101]))
+                            .with_inverse("members")],
+                    )));
+                    tx.insert(Box::from(UntypedEnt::from_collections(
+                        204,
+                        vec![],
+                        vec![Edge::new("groups", EdgeValue::Many(vec![101]))
+                            .with_inverse("members")],
+                    )));
+                    Ok(())
+                })
+                .unwrap();
+                assert_eq!(edge(100, "members"), EdgeValue::Many(vec![203]));
+                assert_eq!(edge(101, "members"), EdgeValue::Many(vec![201, 203, 204]));
+            }
+
+            #[test]
+            fn remove_cascading_should_take_removed_ids_out_of_inverse_edges() {
+                let db = $new_db;
+                let insert = |id, edges| {
+                    let _ = db
+                        .insert(Box::from(UntypedEnt::from_collections(id, vec![], edges)))
+                        .unwrap();
+                };
+                let edge = |id, name| db.get(id).unwrap().unwrap().edge(name).unwrap();
+
+                insert(
+                    100,
+                    vec![Edge::new("members", EdgeValue::Many(vec![])).with_inverse("groups")],
+                );
+                insert(
+                    101,
+                    vec![Edge::new("owner", EdgeValue::MaybeOne(None)).with_inverse("owned")],
+                );
+                insert(
+                    200,
+                    vec![
+                        Edge::new("groups", EdgeValue::Many(vec![100])).with_inverse("members"),
+                        Edge::new("owned", EdgeValue::Many(vec![101])).with_inverse("owner"),
+                    ],
+                );
+                insert(
+                    201,
+                    vec![Edge::new("groups", EdgeValue::Many(vec![100])).with_inverse("members")],
+                );
+                assert_eq!(edge(100, "members"), EdgeValue::Many(vec![200, 201]));
+                assert_eq!(edge(101, "owner"), EdgeValue::MaybeOne(Some(200)));
+
+                let removal = db.remove_cascading(200).unwrap().expect("Missing removal");
+                assert_eq!(removal.removed(), &[200]);
+                let mut updated = removal.updated().to_vec();
+                updated.sort_unstable();
+                assert_eq!(updated, vec![100, 101]);
+                assert_eq!(edge(100, "members"), EdgeValue::Many(vec![201]));
+                assert_eq!(edge(101, "owner"), EdgeValue::MaybeOne(None));
+            }
+
+            #[test]
+            fn commit_transaction_should_apply_deletion_policies_of_removed_ents() {
+                let db = $new_db;
+                let _ = db
+                    .insert(Box::from(UntypedEnt::from_collections(
+                        100,
+                        vec![],
+                        vec![Edge::new_with_deletion_policy(
+                            "child",
+                            vec![101],
+                            EdgeDeletionPolicy::DeepDelete,
+                        )],
+                    )))
+                    .unwrap();
+                let _ = db
+                    .insert(Box::from(UntypedEnt::empty_with_id(101)))
+                    .unwrap();
+
+                let _ = db
+                    .transaction(|tx| {
+                        tx.remove(100);
+                        Ok(())
+                    })
+                    .unwrap();
+                assert!(db.get(100).unwrap().is_none());
+                assert!(db.get(101).unwrap().is_none());
+            }
+
+            #[test]
+            fn reserve_id_should_return_an_id_not_used_by_later_inserts() {
+                let db = $new_db;
+
+                let reserved_id = db.reserve_id().expect("Failed to reserve id");
+                let id = db
+                    .insert(Box::from(UntypedEnt::empty_with_id(EPHEMERAL_ID)))
+                    .expect("Failed to insert ent");
+                assert_ne!(reserved_id, id);
+                assert!(db.get(reserved_id).unwrap().is_none());
+            }
+
+            #[test]
+            fn commit_transaction_should_apply_all_operations_in_order() {
+                let db = $new_db;
+
+                let _ = db.insert(Box::from(UntypedEnt::empty_with_id(1))).unwrap();
+
+                let mut tx = Transaction::new();
+                tx.insert(Box::from(UntypedEnt::empty_with_id(20)))
+                    .insert(Box::from(UntypedEnt::empty_with_id(EPHEMERAL_ID)))
+                    .remove(1)
+                    .remove(20);
+
+                let ids = db
+                    .commit_transaction(tx)
+                    .expect("Failed to commit transaction");
+                assert_eq!(ids.len(), 2);
+                assert_eq!(ids[0], 20);
+                assert_ne!(ids[1], EPHEMERAL_ID);
+
+                assert!(db.get(1).unwrap().is_none(), "Ent 1 not removed");
+                assert!(db.get(20).unwrap().is_none(), "Ent 20 not removed");
+                assert!(db.get(ids[1]).unwrap().is_some(), "New ent missing");
+            }
+
+            #[test]
+            fn commit_transaction_should_apply_no_operations_if_any_fail() {
+                let db = $new_db;
+
+                // Consume all ids so that any further allocation will fail
+                let _ = db
+                    .insert(Box::from(UntypedEnt::empty_with_id(Id::MAX)))
+                    .unwrap();
+
+                let mut tx = Transaction::new();
+                tx.insert(Box::from(UntypedEnt::empty_with_id(20)))
+                    .insert(Box::from(UntypedEnt::empty_with_id(EPHEMERAL_ID)))
+                    .remove(Id::MAX);
+
+                match db.commit_transaction(tx) {
+                    Err(DatabaseError::EntCapacityReached) => {}
+                    x => panic!("Unexpected result: {:?}", x.map(|_| ())),
+                }
+
+                assert!(
+                    db.get(Id::MAX).unwrap().is_some(),
+                    "Ent unexpectedly removed"
+                );
+                assert!(db.get(20).unwrap().is_none(), "Ent unexpectedly inserted");
+            }
+
+            #[test]
+            fn transaction_should_apply_nothing_if_closure_fails() {
+                let db = $new_db;
+
+                let result = db.transaction(|tx| {
+                    tx.insert(Box::from(UntypedEnt::empty_with_id(20)));
+                    Err(DatabaseError::EntCapacityReached)
+                });
+                assert!(result.is_err(), "Transaction unexpectedly succeeded");
+                assert!(db.get(20).unwrap().is_none(), "Ent unexpectedly inserted");
+
+                let ids = db
+                    .transaction(|tx| {
+                        tx.insert(Box::from(UntypedEnt::empty_with_id(20)));
+                        Ok(())
+                    })
+                    .expect("Failed to commit transaction");
+                assert_eq!(ids, vec![20]);
+                assert!(db.get(20).unwrap().is_some(), "Ent missing");
+            }
         };
     }
 
@@ -1,12 +1,28 @@
-use super::{EntIdSet, KeyValueDatabase, KeyValueDatabaseExecutor};
+use super::{
+    index::{self, EntFieldIndexKeys},
+    CascadeStore, EdgeCountRange, EntIdSet, IndexKey, IndexRange, KeyValueDatabase,
+    KeyValueDatabaseExecutor, TimestampRange,
+};
 use crate::{
     alloc::{IdAllocator, EPHEMERAL_ID},
-    database::{Database, DatabaseError, DatabaseResult},
-    ent::EdgeDeletionPolicy,
-    Ent, Id, Query,
+    database::{
+        project_fields, Database, DatabaseError, DatabaseResult, FindIter, QueryPlan, Removal,
+        Transaction, TransactionOp,
+    },
+    Aggregate, Ent, FieldPath, GroupBy, Id, OrderDirection, Query, Value,
 };
 use derive_more::Constructor;
-use std::collections::HashSet;
+use sled::{
+    transaction::{
+        ConflictableTransactionError, ConflictableTransactionResult, TransactionError,
+        TransactionalTree,
+    },
+    Transactional,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Bound,
+};
 
 /// Represents a sled database that performs synchronous insertion,
 /// retrieval, and removal. Sled maintains disk-backed data, so the `serde`
@@ -29,8 +45,140 @@ fn ivec_to_id(ivec: sled::IVec) -> Option<Id> {
 
 const ENTS_OF_TYPE: &str = "ents_of_type";
 const ID_ALLOCATOR: &str = "id_allocator";
+const FIELD_INDEX: &str = "field_index";
+const UNINDEXED_FIELDS: &str = "unindexed_fields";
+const CREATED_INDEX: &str = "created_index";
+const LAST_UPDATED_INDEX: &str = "last_updated_index";
+const EDGE_COUNT_INDEX: &str = "edge_count_index";
+const FIELD_VALUES: &str = "field_values";
+const SEARCH_INDEX: &str = "search_index";
+const UNSEARCHABLE_FIELDS: &str = "unsearchable_fields";
+const REFERRER_INDEX: &str = "referrer_index";
+const INDEX_STATUS: &str = "index_status";
+
+/// Names of the trees holding indexes built from the stored ents, each of
+/// which is marked within the index status tree once it holds every ent
+const INDEX_TREES: &[&str] = &[
+    FIELD_INDEX,
+    UNINDEXED_FIELDS,
+    CREATED_INDEX,
+    LAST_UPDATED_INDEX,
+    EDGE_COUNT_INDEX,
+    SEARCH_INDEX,
+    UNSEARCHABLE_FIELDS,
+    REFERRER_INDEX,
+];
+
+/// Key within the field values tree marking that the database stores the
+/// values of the fields of ents individually, which is shorter than the key
+/// of every field value and id
+const FIELD_VALUES_ENABLED: &[u8] = &[];
+
+/// Produces the key within a timestamp index tree for an ent, where the
+/// timestamp is followed by the id so that keys are ordered by timestamp
+fn timestamp_index_key(timestamp: u64, id: Id) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(8 + std::mem::size_of::<Id>());
+    bytes.extend(&timestamp.to_be_bytes());
+    bytes.extend(&id.to_be_bytes());
+    bytes
+}
+
+/// Retrieves the id from the end of a key within a timestamp index tree
+fn timestamp_index_key_to_id(key: &[u8]) -> Option<Id> {
+    use std::convert::TryInto;
+    key.get(8..)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(Id::from_be_bytes)
+}
+
+/// Produces the key within the field index tree for the value key of the
+/// named field, prefixing the value key with the length and bytes of the
+/// name so each field occupies a contiguous, ordered range of the tree
+fn field_index_key(name: &str, key: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 + name.len() + key.len());
+    bytes.extend(&(name.len() as u32).to_be_bytes());
+    bytes.extend(name.as_bytes());
+    bytes.extend(key);
+    bytes
+}
+
+/// Produces the key within the edge count index tree for an ent, where the
+/// length and bytes of the edge's name are followed by the total ids on the
+/// edge and then the id so that keys are ordered by total within each edge
+fn edge_count_index_key(name: &str, count: usize, id: Id) -> Vec<u8> {
+    let mut bytes = field_index_key(name, &(count as u64).to_be_bytes());
+    bytes.extend(&id.to_be_bytes());
+    bytes
+}
+
+/// Retrieves the id from the end of a key within the edge count index tree
+fn edge_count_index_key_to_id(name: &str, key: &[u8]) -> Option<Id> {
+    use std::convert::TryInto;
+    key.get(4 + name.len() + 8..)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(Id::from_be_bytes)
+}
+
+/// Produces the key within the field values tree for the named field of an
+/// ent, where the id is followed by the name so that the fields of each ent
+/// occupy a contiguous range of the tree
+///
+/// The id on its own is also stored as a key to mark that the ent's fields
+/// are available from the tree, as ents stored before the database opted
+/// into storing field values will not have their fields within it.
+fn field_value_key(id: Id, name: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(std::mem::size_of::<Id>() + name.len());
+    bytes.extend(&id.to_be_bytes());
+    bytes.extend(name.as_bytes());
+    bytes
+}
+
+/// Produces the key within the referrer index tree for the ents whose edge of
+/// the given name points at the ent with the id, where the length and bytes
+/// of the name are followed by the id
+///
+/// The id on its own is also stored as a key for the ents with any edge
+/// pointing at the ent, which never matches a key with a name as it is
+/// shorter than every such key.
+fn referrer_index_key(id: Id, name: &str) -> Vec<u8> {
+    field_index_key(name, &id.to_be_bytes())
+}
+
+/// Represents the trees of the database as seen from within a single
+/// transaction, where all changes are applied together or not at all
+struct TxTrees<'a> {
+    ents: &'a TransactionalTree,
+    ents_of_type: &'a TransactionalTree,
+    id_allocator: &'a TransactionalTree,
+    field_index: &'a TransactionalTree,
+    unindexed_fields: &'a TransactionalTree,
+    created_index: &'a TransactionalTree,
+    last_updated_index: &'a TransactionalTree,
+    edge_count_index: &'a TransactionalTree,
+    field_values: &'a TransactionalTree,
+    search_index: &'a TransactionalTree,
+    unsearchable_fields: &'a TransactionalTree,
+    referrer_index: &'a TransactionalTree,
+}
 
 impl SledDatabase {
+    /// Opts into storing the value of each field of an ent individually
+    /// alongside the ent whenever it is inserted, which lets
+    /// [`Database::project`] decode only the requested values at the cost of
+    /// storing every value twice
+    ///
+    /// The choice is persisted within the database itself. Ents inserted
+    /// before opting in are still decoded in full when projected until they
+    /// are next inserted.
+    pub fn store_field_values(&self) -> DatabaseResult<()> {
+        self.field_values_tree()?
+            .insert(FIELD_VALUES_ENABLED, &[])
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })?;
+        Ok(())
+    }
+
     /// Returns sled tree for id allocator
     fn id_allocator_tree(&self) -> DatabaseResult<sled::Tree> {
         self.0
@@ -49,235 +197,722 @@ impl SledDatabase {
         f: F,
     ) -> DatabaseResult<Option<Id>> {
         self.id_allocator_tree()?
-            .transaction(move |tx_db| {
-                let mut id_alloc = match tx_db.get([0])? {
-                    Some(ivec) => match bincode::deserialize::<IdAllocator>(&ivec) {
-                        Ok(x) => x,
-                        Err(x) => {
-                            sled::transaction::abort(x)?;
-                            return Ok(None);
-                        }
-                    },
-                    None => IdAllocator::new(),
-                };
-
-                let maybe_id = f(&mut id_alloc);
-
-                let id_alloc_bytes = match bincode::serialize(&id_alloc) {
-                    Ok(x) => x,
-                    Err(x) => {
-                        sled::transaction::abort(x)?;
-                        return Ok(maybe_id);
-                    }
-                };
+            .transaction(|tx_db| tx_with_id_allocator(tx_db, &f))
+            .map_err(from_transaction_error)
+    }
 
-                tx_db.insert(&[0], id_alloc_bytes)?;
-                Ok(maybe_id)
+    /// Returns sled tree for ent types
+    fn ent_type_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(ENTS_OF_TYPE)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
             })
+    }
+
+    /// Returns sled tree for field indexes
+    fn field_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(FIELD_INDEX)
             .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
             })
     }
 
-    /// Returns sled tree for ent types
-    fn ent_type_tree(&self) -> DatabaseResult<sled::Tree> {
+    /// Returns sled tree for names of fields that are not indexed
+    fn unindexed_fields_tree(&self) -> DatabaseResult<sled::Tree> {
         self.0
-            .open_tree(ENTS_OF_TYPE)
+            .open_tree(UNINDEXED_FIELDS)
             .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
             })
     }
 
-    /// Provides a mutable reference to the id set associated with an ent type.
-    /// Any changes made to the set are persisted back to disk.
-    fn with_ent_type_set<F: Fn(&mut EntIdSet)>(&self, r#type: &str, f: F) -> DatabaseResult<()> {
-        self.ent_type_tree()?
-            .transaction(move |tx_db| {
-                let mut set = match tx_db.get(r#type)? {
-                    Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
-                        Ok(x) => x,
-                        Err(x) => {
-                            sled::transaction::abort(x)?;
-                            return Ok(());
-                        }
-                    },
-                    None => HashSet::new(),
-                };
-
-                f(&mut set);
-
-                let set_bytes = match bincode::serialize(&set) {
-                    Ok(x) => x,
-                    Err(x) => {
-                        sled::transaction::abort(x)?;
-                        return Ok(());
-                    }
-                };
+    /// Returns sled tree for the creation timestamps of ents
+    fn created_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(CREATED_INDEX)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
+    }
 
-                tx_db.insert(r#type, set_bytes)?;
-                Ok(())
+    /// Returns sled tree for the last updated timestamps of ents
+    fn last_updated_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(LAST_UPDATED_INDEX)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
             })
+    }
+
+    /// Returns sled tree for the totals of the edges of ents
+    fn edge_count_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(EDGE_COUNT_INDEX)
             .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
             })
     }
-}
 
-impl Database for SledDatabase {
-    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
-        KeyValueDatabaseExecutor::from(self).get_all(ids)
+    /// Returns sled tree for the values of the fields of ents
+    fn field_values_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(FIELD_VALUES)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
     }
 
-    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
-        KeyValueDatabaseExecutor::from(self).find_all(query)
+    /// Returns sled tree for the words of the searchable fields of ents
+    fn search_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(SEARCH_INDEX)
+            .map_err(|e| DatabaseError::Connection {
+                source: Box::from(e),
+            })
     }
 
-    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
-        let maybe_ivec = self
-            .0
-            .get(id_to_ivec(id))
+    /// Returns sled tree for names of fields that are not searchable
+    fn unsearchable_fields_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(UNSEARCHABLE_FIELDS)
             .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
-            })?;
+            })
+    }
 
-        maybe_ivec
-            .map(|ivec| bincode::deserialize(ivec.as_ref()))
-            .transpose()
-            .map_err(|e| DatabaseError::CorruptedEnt {
-                id,
+    /// Returns sled tree for the ids of ents with edges pointing at each ent
+    fn referrer_index_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(REFERRER_INDEX)
+            .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
             })
     }
 
-    fn remove(&self, id: Id) -> DatabaseResult<bool> {
-        if let Some(ent) = self
-            .0
-            .remove(id_to_ivec(id))
+    /// Returns sled tree marking the index trees that hold every stored ent
+    fn index_status_tree(&self) -> DatabaseResult<sled::Tree> {
+        self.0
+            .open_tree(INDEX_STATUS)
             .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
-            })?
-            .map(|ivec| bincode::deserialize::<Box<dyn Ent>>(ivec.as_ref()))
-            .transpose()
-            .map_err(|e| DatabaseError::CorruptedEnt {
-                id,
+            })
+    }
+
+    /// Adds every stored ent to the indexes unless each index tree is marked
+    /// as already holding every ent, bringing databases written before an
+    /// index existed up to date
+    ///
+    /// Each ent is indexed within its own transaction and indexing an ent
+    /// that is already indexed changes nothing, so this is safe to run
+    /// alongside other changes to the database.
+    fn ensure_indexes(&self) -> DatabaseResult<()> {
+        let status = self.index_status_tree()?;
+        let mut is_complete = true;
+        for name in INDEX_TREES {
+            is_complete &= status
+                .contains_key(name)
+                .map_err(|e| DatabaseError::Connection {
+                    source: Box::from(e),
+                })?;
+        }
+        if is_complete {
+            return Ok(());
+        }
+
+        for key in self.0.iter().keys() {
+            let key = key.map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
-            })?
-        {
-            for edge in ent.edges() {
-                match edge.deletion_policy() {
-                    // If shallow deletion, we only want to remove the connections
-                    // back to this ent from the corresponding ents
-                    EdgeDeletionPolicy::ShallowDelete => {
-                        for edge_id in edge.to_ids() {
-                            self.0
-                                .transaction(|tx_db| {
-                                    let maybe_ivec = tx_db.get(id_to_ivec(id))?;
-                                    let result = maybe_ivec
-                                        .map(|ivec| {
-                                            bincode::deserialize::<Box<dyn Ent>>(ivec.as_ref())
-                                        })
-                                        .transpose()
-                                        .map_err(|e| DatabaseError::CorruptedEnt {
-                                            id,
-                                            source: Box::from(e),
-                                        });
-                                    match result {
-                                        Ok(Some(mut ent)) => {
-                                            for mut edge in ent.edges() {
-                                                let _ = edge.value_mut().remove_ids(Some(edge_id));
-                                                let name = edge.name().to_string();
-                                                let _ = ent.update_edge(&name, edge.into_value());
-                                            }
-                                            match bincode::serialize(&ent) {
-                                                Ok(bytes) => tx_db.insert(id_to_ivec(id), bytes)?,
-                                                Err(x) => sled::transaction::abort(
-                                                    DatabaseError::CorruptedEnt {
-                                                        id: ent.id(),
-                                                        source: x,
-                                                    },
-                                                )?,
-                                            };
-                                        }
-                                        Ok(None) => {}
-                                        Err(x) => {
-                                            sled::transaction::abort(x)?;
-                                        }
-                                    };
-                                    Ok(())
-                                })
-                                .map_err(|e| DatabaseError::Connection {
-                                    source: Box::from(e),
-                                })?;
-                        }
-                    }
-                    // If deep deletion, we want to remove the ents connected
-                    // by the edge
-                    EdgeDeletionPolicy::DeepDelete => {
-                        for id in edge.to_ids() {
-                            let _ = self.remove(id);
-                        }
+            })?;
+
+            if let Some(id) = ivec_to_id(key) {
+                self.with_transaction(|trees| {
+                    if let Some(ent) = tx_get(trees, id)? {
+                        tx_index(trees, ent.as_ref())?;
                     }
-                    // If deletion policy is nothing, then do nothing
-                    EdgeDeletionPolicy::Nothing => {}
-                }
+                    Ok(())
+                })?;
+            }
+        }
+
+        for name in INDEX_TREES {
+            status
+                .insert(name, &[])
+                .map_err(|e| DatabaseError::Connection {
+                    source: Box::from(e),
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Produces an executor of queries against the database once its indexes
+    /// hold every stored ent
+    fn executor(&self) -> DatabaseResult<KeyValueDatabaseExecutor<'_, Self>> {
+        self.ensure_indexes()?;
+        Ok(KeyValueDatabaseExecutor::from(self))
+    }
+
+    /// Runs the given function within a single transaction spanning all of
+    /// the trees of the database, persisting the changes only if the
+    /// function succeeds
+    fn with_transaction<T, F>(&self, f: F) -> DatabaseResult<T>
+    where
+        F: Fn(&TxTrees) -> ConflictableTransactionResult<T, DatabaseError>,
+    {
+        let ent_type_tree = self.ent_type_tree()?;
+        let id_allocator_tree = self.id_allocator_tree()?;
+        let field_index_tree = self.field_index_tree()?;
+        let unindexed_fields_tree = self.unindexed_fields_tree()?;
+        let created_index_tree = self.created_index_tree()?;
+        let last_updated_index_tree = self.last_updated_index_tree()?;
+        let edge_count_index_tree = self.edge_count_index_tree()?;
+        let field_values_tree = self.field_values_tree()?;
+        let search_index_tree = self.search_index_tree()?;
+        let unsearchable_fields_tree = self.unsearchable_fields_tree()?;
+        let referrer_index_tree = self.referrer_index_tree()?;
+
+        (
+            &*self.0,
+            &ent_type_tree,
+            &id_allocator_tree,
+            &field_index_tree,
+            &unindexed_fields_tree,
+            &created_index_tree,
+            &last_updated_index_tree,
+            &edge_count_index_tree,
+            &field_values_tree,
+            &search_index_tree,
+            &unsearchable_fields_tree,
+            &referrer_index_tree,
+        )
+            .transaction(
+                |(
+                    ents,
+                    ents_of_type,
+                    id_allocator,
+                    field_index,
+                    unindexed_fields,
+                    created_index,
+                    last_updated_index,
+                    edge_count_index,
+                    field_values,
+                    search_index,
+                    unsearchable_fields,
+                    referrer_index,
+                )| {
+                    f(&TxTrees {
+                        ents,
+                        ents_of_type,
+                        id_allocator,
+                        field_index,
+                        unindexed_fields,
+                        created_index,
+                        last_updated_index,
+                        edge_count_index,
+                        field_values,
+                        search_index,
+                        unsearchable_fields,
+                        referrer_index,
+                    })
+                },
+            )
+            .map_err(from_transaction_error)
+    }
+}
+
+/// Converts a sled transaction error into a database error, unwrapping
+/// errors that caused the transaction to be aborted
+fn from_transaction_error(x: TransactionError<DatabaseError>) -> DatabaseError {
+    match x {
+        TransactionError::Abort(x) => x,
+        TransactionError::Storage(x) => DatabaseError::Connection {
+            source: Box::from(x),
+        },
+    }
+}
+
+/// Provides a mutable reference to the id allocator within a transaction,
+/// returning an optional id in the case that we want to return the next id
+/// from the allocator
+fn tx_with_id_allocator<F: Fn(&mut IdAllocator) -> Option<Id>>(
+    tx_db: &TransactionalTree,
+    f: F,
+) -> ConflictableTransactionResult<Option<Id>, DatabaseError> {
+    let mut id_alloc = match tx_db.get([0])? {
+        Some(ivec) => match bincode::deserialize::<IdAllocator>(&ivec) {
+            Ok(x) => x,
+            Err(x) => {
+                return sled::transaction::abort(DatabaseError::Connection {
+                    source: Box::from(x),
+                })
+            }
+        },
+        None => IdAllocator::new(),
+    };
+
+    let maybe_id = f(&mut id_alloc);
+
+    let id_alloc_bytes = match bincode::serialize(&id_alloc) {
+        Ok(x) => x,
+        Err(x) => {
+            return sled::transaction::abort(DatabaseError::Connection {
+                source: Box::from(x),
+            })
+        }
+    };
+
+    tx_db.insert(&[0], id_alloc_bytes)?;
+    Ok(maybe_id)
+}
+
+/// Provides a mutable reference to the id set stored at the given key within
+/// a transaction, such as the set associated with an ent type
+fn tx_with_id_set<K: AsRef<[u8]>, F: Fn(&mut EntIdSet)>(
+    tx_db: &TransactionalTree,
+    key: K,
+    f: F,
+) -> ConflictableTransactionResult<(), DatabaseError> {
+    let key = key.as_ref();
+    let mut set = match tx_db.get(key)? {
+        Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
+            Ok(x) => x,
+            Err(x) => {
+                return sled::transaction::abort(DatabaseError::Connection {
+                    source: Box::from(x),
+                })
+            }
+        },
+        None => HashSet::new(),
+    };
+
+    f(&mut set);
+
+    let set_bytes = match bincode::serialize(&set) {
+        Ok(x) => x,
+        Err(x) => {
+            return sled::transaction::abort(DatabaseError::Connection {
+                source: Box::from(x),
+            })
+        }
+    };
+
+    tx_db.insert(key, set_bytes)?;
+    Ok(())
+}
+
+/// Deserializes an ent from the primary tree, falling back to the layout of
+/// edges from before they held the name of their inverse edge so that ents
+/// stored before then can still be read
+fn deserialize_ent(bytes: &[u8]) -> bincode::Result<Box<dyn Ent>> {
+    bincode::deserialize(bytes).or_else(|x| {
+        crate::ent::with_edge_inverse_omitted(|| bincode::deserialize(bytes)).map_err(|_| x)
+    })
+}
+
+/// Retrieves an ent by id within a transaction
+fn tx_get(
+    trees: &TxTrees,
+    id: Id,
+) -> ConflictableTransactionResult<Option<Box<dyn Ent>>, DatabaseError> {
+    match trees.ents.get(id_to_ivec(id))? {
+        Some(ivec) => match deserialize_ent(ivec.as_ref()) {
+            Ok(ent) => Ok(Some(ent)),
+            Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+                id,
+                source: Box::from(x),
+            }),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Writes an ent to the primary tree within a transaction without any
+/// further processing of the ent
+fn tx_put(trees: &TxTrees, ent: &dyn Ent) -> ConflictableTransactionResult<(), DatabaseError> {
+    let id = ent.id();
+    match bincode::serialize(ent) {
+        Ok(bytes) => {
+            trees.ents.insert(id_to_ivec(id), bytes)?;
+            Ok(())
+        }
+        Err(x) => sled::transaction::abort(DatabaseError::CorruptedEnt {
+            id,
+            source: Box::from(x),
+        }),
+    }
+}
+
+/// Adds the id of the ent to the indexes of its indexed fields, its
+/// timestamps, the totals of its edges, the words of its searchable fields,
+/// and the ents its edges point at, and stores the values of its fields
+/// individually if the database opted into doing so, within a transaction
+fn tx_index(trees: &TxTrees, ent: &dyn Ent) -> ConflictableTransactionResult<(), DatabaseError> {
+    let id = ent.id();
+    let keys = EntFieldIndexKeys::from_ent(ent);
+
+    trees
+        .created_index
+        .insert(timestamp_index_key(ent.created(), id), &[])?;
+    trees
+        .last_updated_index
+        .insert(timestamp_index_key(ent.last_updated(), id), &[])?;
+
+    for (name, key) in keys.indexed {
+        tx_with_id_set(
+            trees.field_index,
+            field_index_key(&name, key.as_bytes()),
+            |set| {
+                set.insert(id);
+            },
+        )?;
+    }
+
+    for name in keys.unindexed {
+        if trees.unindexed_fields.get(&name)?.is_none() {
+            trees.unindexed_fields.insert(name.as_bytes(), &[])?;
+        }
+    }
+
+    for (name, count) in index::edge_counts(ent) {
+        trees
+            .edge_count_index
+            .insert(edge_count_index_key(&name, count, id), &[])?;
+    }
+
+    for (name, tokens) in keys.searchable {
+        for token in tokens {
+            tx_with_id_set(
+                trees.search_index,
+                field_index_key(&name, token.as_bytes()),
+                |set| {
+                    set.insert(id);
+                },
+            )?;
+        }
+    }
+
+    for name in keys.unsearchable {
+        if trees.unsearchable_fields.get(&name)?.is_none() {
+            trees.unsearchable_fields.insert(name.as_bytes(), &[])?;
+        }
+    }
+
+    for (name, targets) in index::edge_targets(ent) {
+        for target in targets {
+            tx_with_id_set(trees.referrer_index, id_to_ivec(target), |set| {
+                set.insert(id);
+            })?;
+            tx_with_id_set(
+                trees.referrer_index,
+                referrer_index_key(target, &name),
+                |set| {
+                    set.insert(id);
+                },
+            )?;
+        }
+    }
+
+    if trees.field_values.get(FIELD_VALUES_ENABLED)?.is_none() {
+        return Ok(());
+    }
+
+    for field in ent.fields() {
+        match bincode::serialize(field.value()) {
+            Ok(bytes) => {
+                trees
+                    .field_values
+                    .insert(field_value_key(id, field.name()), bytes)?;
             }
+            Err(x) => {
+                return sled::transaction::abort(DatabaseError::CorruptedEnt {
+                    id,
+                    source: Box::from(x),
+                })
+            }
+        }
+    }
+    trees.field_values.insert(id_to_ivec(id), &[])?;
+
+    Ok(())
+}
+
+/// Removes the id of the ent from the indexes of its indexed fields, its
+/// timestamps, the totals of its edges, the words of its searchable fields,
+/// and the ents its edges point at, and removes the values of its fields
+/// within a transaction
+fn tx_unindex(trees: &TxTrees, ent: &dyn Ent) -> ConflictableTransactionResult<(), DatabaseError> {
+    let id = ent.id();
+    let keys = EntFieldIndexKeys::from_ent(ent);
+
+    trees
+        .created_index
+        .remove(timestamp_index_key(ent.created(), id))?;
+    trees
+        .last_updated_index
+        .remove(timestamp_index_key(ent.last_updated(), id))?;
+
+    for (name, key) in keys.indexed {
+        let key = field_index_key(&name, key.as_bytes());
+        tx_with_id_set(trees.field_index, &key, |set| {
+            set.remove(&id);
+        })?;
+    }
+
+    for (name, count) in index::edge_counts(ent) {
+        trees
+            .edge_count_index
+            .remove(edge_count_index_key(&name, count, id))?;
+    }
 
-            // Remove the id from our type mapping if it is there
-            self.with_ent_type_set(ent.r#type(), |set| {
+    for (name, tokens) in keys.searchable {
+        for token in tokens {
+            let key = field_index_key(&name, token.as_bytes());
+            tx_with_id_set(trees.search_index, &key, |set| {
                 set.remove(&id);
             })?;
+        }
+    }
 
-            // Add the id to the freed ids available in the allocator
-            self.with_id_allocator(|alloc| {
-                alloc.extend(vec![id]);
-                None
+    for (name, targets) in index::edge_targets(ent) {
+        for target in targets {
+            tx_with_id_set(trees.referrer_index, id_to_ivec(target), |set| {
+                set.remove(&id);
             })?;
+            tx_with_id_set(
+                trees.referrer_index,
+                referrer_index_key(target, &name),
+                |set| {
+                    set.remove(&id);
+                },
+            )?;
+        }
+    }
 
-            Ok(true)
+    for field in ent.fields() {
+        trees
+            .field_values
+            .remove(field_value_key(id, field.name()))?;
+    }
+    trees.field_values.remove(id_to_ivec(id))?;
+
+    Ok(())
+}
+
+/// Inserts an ent within a transaction, allocating an id if the ent has
+/// an ephemeral id, syncing the edges declared as the inverse of its edges,
+/// and returning the id that was used
+fn tx_insert(
+    trees: &TxTrees,
+    mut ent: Box<dyn Ent>,
+) -> ConflictableTransactionResult<Id, DatabaseError> {
+    // Get the id of the ent, swapping out the ephemeral id
+    let id = ent.id();
+    let id = match tx_with_id_allocator(trees.id_allocator, move |alloc| {
+        if id == EPHEMERAL_ID {
+            alloc.next()
         } else {
-            Ok(false)
+            alloc.mark_external_id(id);
+            Some(id)
+        }
+    })? {
+        Some(id) => id,
+        None => return sled::transaction::abort(DatabaseError::EntCapacityReached),
+    };
+
+    // Update the ent's id to match what is actually to be used
+    ent.set_id(id);
+
+    // Update the ent's last_updated to be the current time
+    if let Err(x) = ent.mark_updated() {
+        return sled::transaction::abort(DatabaseError::Other {
+            source: Box::from(x),
+        });
+    }
+
+    // Replace the indexes of any ent we are overwriting with our own
+    let old_ent = tx_get(trees, id)?;
+    if let Some(old_ent) = old_ent.as_ref() {
+        tx_unindex(trees, old_ent.as_ref())?;
+    }
+    tx_index(trees, ent.as_ref())?;
+
+    // Add our ent's id to the set of ids associated with the ent's type
+    tx_with_id_set(trees.ents_of_type, ent.r#type(), |set| {
+        set.insert(id);
+    })?;
+
+    // Add our ent to the primary database
+    tx_put(trees, ent.as_ref())?;
+
+    // Point the inverse edges of the ents connected to ours back at it
+    super::sync_inverse_edges(trees, old_ent, ent)?;
+
+    Ok(id)
+}
+
+impl<'a> CascadeStore for TxTrees<'a> {
+    type Error = ConflictableTransactionError<DatabaseError>;
+
+    fn load(&self, id: Id) -> ConflictableTransactionResult<Option<Box<dyn Ent>>, DatabaseError> {
+        tx_get(self, id)
+    }
+
+    fn store(&self, ent: Box<dyn Ent>) -> ConflictableTransactionResult<(), DatabaseError> {
+        // Replace the indexes of the ent we are overwriting with our own
+        if let Some(old_ent) = tx_get(self, ent.id())? {
+            tx_unindex(self, old_ent.as_ref())?;
         }
+        tx_index(self, ent.as_ref())?;
+        tx_put(self, ent.as_ref())
     }
 
-    fn insert(&self, mut ent: Box<dyn Ent>) -> DatabaseResult<Id> {
-        // Get the id of the ent, swapping out the ephemeral id
+    fn delete(&self, ent: &dyn Ent) -> ConflictableTransactionResult<(), DatabaseError> {
         let id = ent.id();
-        let id = self
-            .with_id_allocator(move |alloc| {
-                if id == EPHEMERAL_ID {
-                    alloc.next()
-                } else {
-                    alloc.mark_external_id(id);
-                    Some(id)
-                }
-            })?
-            .ok_or(DatabaseError::EntCapacityReached)?;
+        self.ents.remove(id_to_ivec(id))?;
 
-        // Update the ent's id to match what is actually to be used
-        ent.set_id(id);
+        // Remove the id from the indexes of the ent's fields, timestamps, and edges
+        tx_unindex(self, ent)?;
 
-        // Update the ent's last_updated to be the current time
-        ent.mark_updated().map_err(|e| DatabaseError::Other {
-            source: Box::from(e),
+        // Remove the id from our type mapping if it is there
+        tx_with_id_set(self.ents_of_type, ent.r#type(), |set| {
+            set.remove(&id);
         })?;
 
-        // Add our ent's id to the set of ids associated with the ent's type
-        self.with_ent_type_set(ent.r#type(), |set| {
-            set.insert(id);
+        // Add the id to the freed ids available in the allocator
+        tx_with_id_allocator(self.id_allocator, |alloc| {
+            alloc.extend(vec![id]);
+            None
         })?;
 
-        // Add our ent to the primary database
-        let ent_bytes = bincode::serialize(&ent).map_err(|e| DatabaseError::CorruptedEnt {
-            id,
-            source: Box::from(e),
-        })?;
-        self.0
-            .insert(id_to_ivec(id), ent_bytes)
+        Ok(())
+    }
+
+    fn referrer_ids(&self, id: Id) -> ConflictableTransactionResult<EntIdSet, DatabaseError> {
+        match self.referrer_index.get(id_to_ivec(id))? {
+            Some(ivec) => match bincode::deserialize::<EntIdSet>(&ivec) {
+                Ok(x) => Ok(x),
+                Err(x) => sled::transaction::abort(DatabaseError::Connection {
+                    source: Box::from(x),
+                }),
+            },
+            None => Ok(EntIdSet::new()),
+        }
+    }
+
+    fn abort(error: DatabaseError) -> ConflictableTransactionError<DatabaseError> {
+        ConflictableTransactionError::Abort(error)
+    }
+}
+
+impl Database for SledDatabase {
+    fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        KeyValueDatabaseExecutor::from(self).get_all(ids)
+    }
+
+    fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        self.executor()?.find_all(query)
+    }
+
+    fn find_iter(&self, query: Query) -> DatabaseResult<FindIter<'_>> {
+        self.executor()?.find_iter(query)
+    }
+
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        self.executor()?.count(query)
+    }
+
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        self.executor()?.exists(query)
+    }
+
+    fn find_first(&self, query: Query) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.executor()?.find_first(query)
+    }
+
+    fn aggregate(&self, query: Query, aggregate: Aggregate) -> DatabaseResult<Value> {
+        self.executor()?.aggregate(query, aggregate)
+    }
+
+    fn aggregate_by(
+        &self,
+        query: Query,
+        aggregate: Aggregate,
+        group_by: GroupBy,
+    ) -> DatabaseResult<Vec<(Value, Value)>> {
+        self.executor()?.aggregate_by(query, aggregate, group_by)
+    }
+
+    fn explain(&self, query: Query) -> DatabaseResult<QueryPlan> {
+        self.executor()?.explain(query)
+    }
+
+    fn project(
+        &self,
+        query: Query,
+        fields: Vec<String>,
+    ) -> DatabaseResult<Vec<(Id, HashMap<String, Value>)>> {
+        self.executor()?.project(query, fields)
+    }
+
+    fn referrers(&self, id: Id, edge_name: &str) -> DatabaseResult<Vec<Id>> {
+        self.executor()?.referrers(id, edge_name)
+    }
+
+    fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        let maybe_ivec = self
+            .0
+            .get(id_to_ivec(id))
             .map_err(|e| DatabaseError::Connection {
                 source: Box::from(e),
             })?;
 
-        Ok(id)
+        maybe_ivec
+            .map(|ivec| deserialize_ent(ivec.as_ref()))
+            .transpose()
+            .map_err(|e| DatabaseError::CorruptedEnt {
+                id,
+                source: Box::from(e),
+            })
+    }
+
+    fn remove(&self, id: Id) -> DatabaseResult<bool> {
+        Ok(self.remove_cascading(id)?.is_some())
+    }
+
+    fn remove_cascading(&self, id: Id) -> DatabaseResult<Option<Removal>> {
+        // Removal relies on the referrer index to find the ents pointing at
+        // those removed, so it must hold every ent first
+        self.ensure_indexes()?;
+        self.with_transaction(|trees| super::remove_cascading(trees, id))
+    }
+
+    fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+        self.with_transaction(|trees| tx_insert(trees, ent.clone()))
+    }
+
+    fn reserve_id(&self) -> DatabaseResult<Id> {
+        self.with_id_allocator(Iterator::next)?
+            .ok_or(DatabaseError::EntCapacityReached)
+    }
+
+    fn commit_transaction(&self, tx: Transaction) -> DatabaseResult<Vec<Id>> {
+        self.ensure_indexes()?;
+
+        // NOTE: Sled may run our closure more than once if it encounters a
+        //       conflict, so we work from a copy of the operations each time
+        self.with_transaction(|trees| {
+            let mut ids = Vec::new();
+            for op in tx.clone() {
+                match op {
+                    TransactionOp::Insert(ent) => ids.push(tx_insert(trees, ent)?),
+                    TransactionOp::Remove(id) => {
+                        super::remove_cascading(trees, id)?;
+                    }
+                }
+            }
+            Ok(ids)
+        })
     }
 }
 
@@ -297,6 +932,53 @@ impl KeyValueDatabase for SledDatabase {
         self.0.contains_key(id_to_ivec(id)).ok().unwrap_or_default()
     }
 
+    /// Returns the values of the named fields of the ent with the provided
+    /// id, decoding only the requested values rather than the entire ent
+    /// when the database stores field values individually
+    /// (see [`SledDatabase::store_field_values`])
+    fn get_fields(
+        &self,
+        id: Id,
+        names: &[String],
+    ) -> DatabaseResult<Option<HashMap<String, Value>>> {
+        let tree = self.field_values_tree()?;
+        let has_values =
+            tree.contains_key(id_to_ivec(id))
+                .map_err(|e| DatabaseError::Connection {
+                    source: Box::from(e),
+                })?;
+
+        // Ents stored before their fields were stored individually need
+        // to be loaded in full
+        if !has_values {
+            return Ok(self.get(id)?.map(|ent| project_fields(ent.as_ref(), names)));
+        }
+
+        let get = |name: &str| -> DatabaseResult<Option<Value>> {
+            let maybe_ivec =
+                tree.get(field_value_key(id, name))
+                    .map_err(|e| DatabaseError::Connection {
+                        source: Box::from(e),
+                    })?;
+
+            maybe_ivec
+                .map(|ivec| bincode::deserialize(ivec.as_ref()))
+                .transpose()
+                .map_err(|e| DatabaseError::CorruptedEnt {
+                    id,
+                    source: Box::from(e),
+                })
+        };
+
+        let mut values = HashMap::new();
+        for name in names {
+            if let Some(value) = FieldPath::try_lookup_with(name, get)? {
+                values.insert(name.to_string(), value);
+            }
+        }
+        Ok(Some(values))
+    }
+
     /// Returns ids of all ents for the given type
     fn ids_for_type(&self, r#type: &str) -> EntIdSet {
         fn inner(this: &SledDatabase, r#type: &str) -> DatabaseResult<EntIdSet> {
@@ -322,12 +1004,182 @@ impl KeyValueDatabase for SledDatabase {
 
         inner(self, r#type).ok().unwrap_or_default()
     }
+
+    /// Returns ids of all ents whose indexed field has a value in the range
+    fn ids_for_field_range(&self, name: &str, range: IndexRange) -> Option<EntIdSet> {
+        if self.unindexed_fields_tree().ok()?.contains_key(name).ok()? {
+            return None;
+        }
+
+        let prefix = field_index_key(name, &[]);
+        let to_tree_bound = |bound: Bound<IndexKey>, unbounded| match bound {
+            Bound::Included(key) => Bound::Included(field_index_key(name, key.as_bytes())),
+            Bound::Excluded(key) => Bound::Excluded(field_index_key(name, key.as_bytes())),
+            Bound::Unbounded => unbounded,
+        };
+        let (start, end) = range;
+        let range = (
+            to_tree_bound(start, Bound::Included(prefix.clone())),
+            to_tree_bound(end, Bound::Unbounded),
+        );
+        if index::is_empty_range(&range) {
+            return Some(EntIdSet::new());
+        }
+
+        let mut ids = EntIdSet::new();
+        for result in self.field_index_tree().ok()?.range::<Vec<u8>, _>(range) {
+            let (key, ivec) = result.ok()?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            ids.extend(bincode::deserialize::<EntIdSet>(&ivec).ok()?);
+        }
+        Some(ids)
+    }
+
+    /// Returns ids of all ents whose searchable field contains the word
+    fn ids_for_search_token(&self, name: &str, token: &str) -> Option<EntIdSet> {
+        if self
+            .unsearchable_fields_tree()
+            .ok()?
+            .contains_key(name)
+            .ok()?
+        {
+            return None;
+        }
+
+        match self
+            .search_index_tree()
+            .ok()?
+            .get(field_index_key(name, token.as_bytes()))
+            .ok()?
+        {
+            Some(ivec) => bincode::deserialize(&ivec).ok(),
+            None => Some(EntIdSet::new()),
+        }
+    }
+
+    /// Returns ids of all ents whose edge of the given name points at the
+    /// ent with the given id
+    fn ids_for_referrers(&self, id: Id, name: &str) -> EntIdSet {
+        self.referrer_index_tree()
+            .ok()
+            .and_then(|tree| tree.get(referrer_index_key(id, name)).ok()?)
+            .and_then(|ivec| bincode::deserialize(&ivec).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns ids of all ents ordered by id, pulling them lazily from the
+    /// database
+    fn ids_in_order(
+        &self,
+        after: Option<Id>,
+        direction: OrderDirection,
+    ) -> Box<dyn Iterator<Item = Id> + '_> {
+        let start = match after {
+            Some(id) => Bound::Excluded(id_to_ivec(id)),
+            None => Bound::Unbounded,
+        };
+        let ids = move |iter: sled::Iter| iter.keys().filter_map(Result::ok).filter_map(ivec_to_id);
+
+        match direction {
+            OrderDirection::Ascending => Box::new(ids(self.0.range((start, Bound::Unbounded)))),
+            OrderDirection::Descending => {
+                Box::new(ids(self.0.range((Bound::Unbounded, start))).rev())
+            }
+        }
+    }
+
+    /// Returns ids of all ents whose creation timestamp is within the range
+    fn ids_for_created_range(&self, range: TimestampRange) -> EntIdSet {
+        self.created_index_tree()
+            .map(|tree| ids_in_timestamp_range(&tree, range))
+            .unwrap_or_default()
+    }
+
+    /// Returns ids of all ents whose last updated timestamp is within the range
+    fn ids_for_last_updated_range(&self, range: TimestampRange) -> EntIdSet {
+        self.last_updated_index_tree()
+            .map(|tree| ids_in_timestamp_range(&tree, range))
+            .unwrap_or_default()
+    }
+
+    /// Returns ids of all ents with the edge whose total ids is in the range
+    fn ids_for_edge_count_range(&self, name: &str, range: EdgeCountRange) -> EntIdSet {
+        // NOTE: Like timestamps, keys are the total followed by the id, so
+        //       the key of a total with the smallest id sorts before every
+        //       key with that total and the largest id sorts after them all
+        let first = |count: usize| edge_count_index_key(name, count, Id::MIN);
+        let last = |count: usize| edge_count_index_key(name, count, Id::MAX);
+        let (start, end) = range;
+        let range = (
+            match start {
+                Bound::Included(count) => Bound::Included(first(count)),
+                Bound::Excluded(count) => Bound::Excluded(last(count)),
+                Bound::Unbounded => Bound::Included(first(0)),
+            },
+            match end {
+                Bound::Included(count) => Bound::Included(last(count)),
+                Bound::Excluded(count) => Bound::Excluded(first(count)),
+                Bound::Unbounded => Bound::Included(last(usize::MAX)),
+            },
+        );
+        if index::is_empty_range(&range) {
+            return EntIdSet::new();
+        }
+
+        self.edge_count_index_tree()
+            .map(|tree| {
+                tree.range::<Vec<u8>, _>(range)
+                    .keys()
+                    .filter_map(|key| {
+                        key.ok()
+                            .and_then(|key| edge_count_index_key_to_id(name, &key))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Collects the ids of all ents within the range of a timestamp index tree
+fn ids_in_timestamp_range(tree: &sled::Tree, range: TimestampRange) -> EntIdSet {
+    // NOTE: Keys are the timestamp followed by the id, so the timestamp on
+    //       its own sorts before every key with that timestamp and the
+    //       timestamp followed by the largest id sorts after every such key
+    let first = |t: u64| t.to_be_bytes().to_vec();
+    let last = |t: u64| timestamp_index_key(t, Id::MAX);
+    let (start, end) = range;
+    let range = (
+        match start {
+            Bound::Included(t) => Bound::Included(first(t)),
+            Bound::Excluded(t) => Bound::Excluded(last(t)),
+            Bound::Unbounded => Bound::Unbounded,
+        },
+        match end {
+            Bound::Included(t) => Bound::Included(last(t)),
+            Bound::Excluded(t) => Bound::Excluded(first(t)),
+            Bound::Unbounded => Bound::Unbounded,
+        },
+    );
+    if index::is_empty_range(&range) {
+        return EntIdSet::new();
+    }
+
+    tree.range::<Vec<u8>, _>(range)
+        .keys()
+        .filter_map(|key| key.ok().and_then(|key| timestamp_index_key_to_id(&key)))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Field, UntypedEnt, Value};
+    use crate::{
+        Edge, EdgeDeletionPolicy, EdgeValue, Field, FieldAttribute, Predicate, TypedPredicate,
+        UntypedEnt, Value,
+    };
+    use std::collections::HashMap;
 
     fn new_db() -> SledDatabase {
         let config = sled::Config::new().temporary(true);
@@ -430,4 +1282,199 @@ mod tests {
             Some(999),
         );
     }
+
+    #[test]
+    fn get_fields_should_only_decode_stored_fields_or_fall_back_to_ent() {
+        let db = new_db();
+        let tree = db.field_values_tree().unwrap();
+
+        let ent = UntypedEnt::from_collections(
+            999,
+            vec![Field::new("a", 1), Field::new("b", vec![2, 3])],
+            vec![],
+        );
+        let names = vec![String::from("a"), String::from("b[1]"), String::from("c")];
+        let mut expected = HashMap::new();
+        expected.insert(String::from("a"), Value::from(1));
+        expected.insert(String::from("b[1]"), Value::from(3));
+
+        // Field values are not stored individually unless opted into, with
+        // ents being loaded in full instead
+        let _ = db.insert(Box::from(ent.clone())).unwrap();
+        assert_eq!(tree.len(), 0);
+        assert_eq!(db.get_fields(999, &names).unwrap(), Some(expected.clone()));
+
+        db.store_field_values().unwrap();
+        let _ = db.insert(Box::from(ent)).unwrap();
+        assert!(tree.contains_key(id_to_ivec(999)).unwrap());
+        assert_eq!(db.get_fields(999, &names).unwrap(), Some(expected.clone()));
+        assert_eq!(db.get_fields(998, &names).unwrap(), None);
+
+        // Ents stored without their fields stored individually are loaded
+        // in full instead
+        tree.remove(id_to_ivec(999)).unwrap();
+        tree.remove(field_value_key(999, "a")).unwrap();
+        assert_eq!(db.get_fields(999, &names).unwrap(), Some(expected));
+
+        // Removing the ent removes its fields as well, leaving only the
+        // choice to store them
+        let _ = db
+            .insert(Box::from(UntypedEnt::empty_with_id(999)))
+            .unwrap();
+        let _ = db.remove(999).unwrap();
+        assert_eq!(tree.len(), 1);
+        assert!(tree.contains_key(FIELD_VALUES_ENABLED).unwrap());
+    }
+
+    #[test]
+    fn queries_should_index_ents_stored_before_indexes_existed() {
+        let db = new_db();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                999,
+                vec![
+                    Field::new_with_attributes("x", 3, vec![FieldAttribute::Indexed]),
+                    Field::new_with_attributes("s", "quick fox", vec![FieldAttribute::Searchable]),
+                ],
+                vec![Edge::new_with_deletion_policy(
+                    "e",
+                    1000,
+                    EdgeDeletionPolicy::Restrict,
+                )],
+            )))
+            .unwrap();
+
+        let ids = |query: Query| {
+            db.find_all(query)
+                .unwrap()
+                .iter()
+                .map(|ent| ent.id())
+                .collect::<Vec<Id>>()
+        };
+
+        // Clears an index and its status as if the ent was stored before the
+        // index existed, once the ent has been added to every index
+        let status = db.index_status_tree().unwrap();
+        let forget = |name: &str| {
+            let _ = ids(Query::default());
+            db.0.open_tree(name).unwrap().clear().unwrap();
+            status.remove(name).unwrap();
+        };
+
+        forget(FIELD_INDEX);
+        assert_eq!(
+            ids(Query::default().where_field("x", Predicate::equals(3))),
+            vec![999]
+        );
+
+        forget(CREATED_INDEX);
+        assert_eq!(
+            ids(Query::default().where_created(TypedPredicate::greater_than(0))),
+            vec![999]
+        );
+
+        forget(LAST_UPDATED_INDEX);
+        assert_eq!(
+            ids(Query::default().where_last_updated(TypedPredicate::greater_than(0))),
+            vec![999]
+        );
+
+        forget(EDGE_COUNT_INDEX);
+        assert_eq!(
+            ids(Query::default().where_edge_count("e", TypedPredicate::equals(1))),
+            vec![999]
+        );
+
+        forget(SEARCH_INDEX);
+        assert_eq!(ids(Query::default().where_search("s", "fox")), vec![999]);
+
+        forget(REFERRER_INDEX);
+        assert_eq!(db.referrers(1000, "e").unwrap(), vec![999]);
+
+        // Removal must see the ents pointing at the removed ent
+        let _ = db
+            .insert(Box::from(UntypedEnt::empty_with_id(1000)))
+            .unwrap();
+        forget(REFERRER_INDEX);
+        match db.remove(1000) {
+            Err(DatabaseError::RestrictedRemoval { .. }) => {}
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        for name in INDEX_TREES {
+            assert!(status.contains_key(name).unwrap(), "{} not marked", name);
+        }
+    }
+
+    #[test]
+    fn get_should_read_ents_stored_before_edges_held_their_inverse() {
+        /// Layout of an edge from before it held the name of its inverse edge
+        #[derive(serde::Serialize)]
+        struct LegacyEdge {
+            name: &'static str,
+            value: EdgeValue,
+            deletion_policy: EdgeDeletionPolicy,
+        }
+
+        fn to_bytes<T: serde::Serialize + ?Sized>(value: &T) -> Vec<u8> {
+            bincode::serialize(value).unwrap()
+        }
+
+        // Ents are stored as a map of the name of each field of the ent to
+        // its value, following the name of the type of ent
+        let mut edges = HashMap::new();
+        edges.insert(
+            "e",
+            LegacyEdge {
+                name: "e",
+                value: EdgeValue::One(1000),
+                deletion_policy: EdgeDeletionPolicy::Nothing,
+            },
+        );
+        let entries = vec![
+            ("type", to_bytes("UntypedEnt")),
+            ("id", to_bytes(&999u64)),
+            ("fields", to_bytes(&HashMap::<String, Field>::new())),
+            ("edges", to_bytes(&edges)),
+            ("created", to_bytes(&0u64)),
+            ("last_updated", to_bytes(&0u64)),
+        ];
+        let mut bytes = to_bytes(&(entries.len() as u64));
+        for (key, value) in entries {
+            bytes.extend(to_bytes(key));
+            bytes.extend(value);
+        }
+
+        // Replaces the stored ent with its old layout and marks the indexes
+        // as needing to be rebuilt from the stored ents
+        let db = new_db();
+        let _ = db
+            .insert(Box::from(UntypedEnt::from_collections(
+                999,
+                vec![],
+                vec![Edge::new("e", 1000)],
+            )))
+            .unwrap();
+        db.0.insert(id_to_ivec(999), bytes).unwrap();
+        db.index_status_tree().unwrap().clear().unwrap();
+
+        let ent = db.get(999).unwrap().expect("Ent missing");
+        assert_eq!(ent.edge("e"), Some(EdgeValue::One(1000)));
+        assert_eq!(ent.edges()[0].inverse(), None);
+
+        let ids: Vec<Id> = db
+            .find_all(Query::default().where_edge_count("e", TypedPredicate::equals(1)))
+            .unwrap()
+            .iter()
+            .map(|ent| ent.id())
+            .collect();
+        assert_eq!(ids, vec![999]);
+
+        // Storing the ent again writes it in the current layout
+        let _ = db.insert(ent).unwrap();
+        assert_eq!(
+            db.get(999).unwrap().expect("Ent missing").edge("e"),
+            Some(EdgeValue::One(1000))
+        );
+    }
 }
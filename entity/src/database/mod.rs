@@ -1,12 +1,38 @@
+#[cfg(feature = "async")]
+mod async_db;
+#[cfg(feature = "async")]
+pub use async_db::*;
+
+mod iter;
+pub use iter::*;
+
 mod kv;
 pub use kv::*;
 
+mod plan;
+pub use plan::*;
+
+mod removal;
+pub use removal::*;
+
+mod transaction;
+pub use transaction::*;
+
+mod traversal;
+pub(crate) use traversal::{edge_ids, Traversal};
+
 use crate::{
-    ent::{Ent, Query, ValueType},
-    Id,
+    ent::{
+        Aggregate, Aggregator, Ent, Filter, GroupBy, GroupedAggregator, Query, TypedPredicate,
+        Value, ValueType,
+    },
+    FieldPath, Id,
 };
 use derive_more::Display;
-use std::sync::{Arc, Weak};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
 
 /// Represents a thread-safe reference to a boxed database trait object
 pub type DatabaseRc = Arc<Box<dyn Database>>;
@@ -21,7 +47,9 @@ pub type DatabaseResult<T> = Result<T, DatabaseError>;
 #[derive(Debug, Display)]
 pub enum DatabaseError {
     #[display(fmt = "Connection Issue: {}", source)]
-    Connection { source: Box<dyn std::error::Error> },
+    Connection {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 
     #[display(fmt = "Disconnected")]
     Disconnected,
@@ -44,7 +72,7 @@ pub enum DatabaseError {
     #[display(fmt = "Corrupted Ent {}: {}", id, source)]
     CorruptedEnt {
         id: Id,
-        source: Box<dyn std::error::Error>,
+        source: Box<dyn std::error::Error + Send + Sync>,
     },
 
     #[display(fmt = "Broken Edge {}", name)]
@@ -53,8 +81,19 @@ pub enum DatabaseError {
     #[display(fmt = "Ent Capacity Reached")]
     EntCapacityReached,
 
+    #[display(fmt = "Cursor does not match ordering of query")]
+    InvalidCursor,
+
+    #[display(fmt = "Ent {} is still referred to by {:?}", id, referrers)]
+    RestrictedRemoval { id: Id, referrers: Vec<Id> },
+
+    #[display(fmt = "Unsupported Operation: {}", name)]
+    UnsupportedOperation { name: String },
+
     #[display(fmt = "{}", source)]
-    Other { source: Box<dyn std::error::Error> },
+    Other {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
 }
 
 impl std::error::Error for DatabaseError {}
@@ -75,6 +114,21 @@ pub trait Database: Send + Sync {
     /// if an ent was removed.
     fn remove(&self, id: Id) -> DatabaseResult<bool>;
 
+    /// Removes the ent with the corresponding id the same way as
+    /// [`Database::remove`], describing every ent removed or updated as a
+    /// result, or returning none if there was no ent to remove
+    ///
+    /// By default, this removes the ent using [`Database::remove`] and only
+    /// describes the removal of the ent itself; databases should override
+    /// this to describe the ents changed by the deletion policies of edges.
+    fn remove_cascading(&self, id: Id) -> DatabaseResult<Option<Removal>> {
+        Ok(if self.remove(id)? {
+            Some(Removal::new(vec![id], Vec::new()))
+        } else {
+            None
+        })
+    }
+
     /// Inserts a new ent using its id as the primary index, overwriting
     /// any ent with a matching id. If the ent's id is set to the ephemeral
     /// id (of 0), a unique id will be assigned to the ent prior to being
@@ -88,6 +142,199 @@ pub trait Database: Send + Sync {
 
     /// Finds all generic ents that match the query
     fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Finds all generic ents that match the query, returning an iterator
+    /// that produces them one at a time and tracks the position of the last
+    /// ent produced so the query can be resumed later
+    ///
+    /// By default, this finds all ents at once using [`Database::find_all`];
+    /// databases should override this to avoid loading every ent up front.
+    fn find_iter(&self, query: Query) -> DatabaseResult<FindIter<'_>> {
+        let ents = self.find_all(query.clone())?;
+        Ok(FindIter::new(&query, ents.into_iter().map(Ok)))
+    }
+
+    /// Counts the generic ents that match the query
+    ///
+    /// By default, this walks the ents produced by [`Database::find_iter`];
+    /// databases should override this to avoid loading ents whenever the
+    /// count can be determined some other way.
+    fn count(&self, query: Query) -> DatabaseResult<usize> {
+        let mut total = 0;
+        for ent in self.find_iter(query)? {
+            ent?;
+            total += 1;
+        }
+        Ok(total)
+    }
+
+    /// Returns true if at least one generic ent matches the query
+    ///
+    /// By default, this looks for the first ent using [`Database::find_first`].
+    fn exists(&self, query: Query) -> DatabaseResult<bool> {
+        self.find_first(query).map(|x| x.is_some())
+    }
+
+    /// Finds the first generic ent that matches the query, returning none
+    /// if no ent matches
+    ///
+    /// By default, this takes the first ent produced by
+    /// [`Database::find_iter`] after limiting the query to a single ent.
+    fn find_first(&self, query: Query) -> DatabaseResult<Option<Box<dyn Ent>>> {
+        self.find_iter(query.first_only())?.next().transpose()
+    }
+
+    /// Computes the aggregate over the generic ents that match the query
+    ///
+    /// By default, this counts using [`Database::count`] and otherwise
+    /// pushes each ent produced by [`Database::find_iter`] into an
+    /// [`Aggregator`].
+    fn aggregate(&self, query: Query, aggregate: Aggregate) -> DatabaseResult<Value> {
+        if aggregate == Aggregate::Count {
+            return self.count(query).map(Value::from);
+        }
+
+        let mut aggregator = Aggregator::new(aggregate);
+        for ent in self.find_iter(query)? {
+            aggregator.push(ent?.as_ref());
+        }
+        Ok(aggregator.finish())
+    }
+
+    /// Computes the aggregate separately for each group of generic ents that
+    /// match the query, returning the value identifying each group alongside
+    /// the value of the group's aggregate
+    ///
+    /// By default, this pushes each ent produced by [`Database::find_iter`]
+    /// into a [`GroupedAggregator`].
+    fn aggregate_by(
+        &self,
+        query: Query,
+        aggregate: Aggregate,
+        group_by: GroupBy,
+    ) -> DatabaseResult<Vec<(Value, Value)>> {
+        let mut aggregator = GroupedAggregator::new(aggregate, group_by);
+        for ent in self.find_iter(query)? {
+            aggregator.push(ent?.as_ref());
+        }
+        Ok(aggregator.finish())
+    }
+
+    /// Finds the values of the named fields of each generic ent that matches
+    /// the query, returning the id of each ent alongside its values by name
+    ///
+    /// Names can also be paths into fields as described by [`FieldPath`],
+    /// and any name that an ent does not have a value for is left out of the
+    /// ent's values.
+    ///
+    /// By default, this takes the values from each ent produced by
+    /// [`Database::find_iter`]; databases should override this to avoid
+    /// loading entire ents.
+    fn project(
+        &self,
+        query: Query,
+        fields: Vec<String>,
+    ) -> DatabaseResult<Vec<(Id, HashMap<String, Value>)>> {
+        self.find_iter(query)?
+            .map(|ent| ent.map(|ent| (ent.id(), project_fields(ent.as_ref(), &fields))))
+            .collect()
+    }
+
+    /// Describes the steps taken to find the ents that match the query
+    /// without finding them, including any indexes used and the estimated
+    /// total candidates remaining after each step
+    ///
+    /// By default, this describes checking each filter in order against
+    /// every ent using [`QueryPlan::unoptimized`]; databases that plan
+    /// queries should override this to describe their actual plan.
+    fn explain(&self, query: Query) -> DatabaseResult<QueryPlan> {
+        Ok(QueryPlan::unoptimized(&query))
+    }
+
+    /// Finds the ids of the ents reachable from the ent with the given id by
+    /// following the edge of the same name from ent to ent, up to the
+    /// maximum number of hops or transitively if there is no maximum,
+    /// alongside the fewest hops taken to reach each ent
+    ///
+    /// Ents are returned in the order they are reached, closest first, and
+    /// each ent is returned at most once even if the edge forms a cycle.
+    /// The starting ent is only returned if the edge leads back to it.
+    fn traverse(
+        &self,
+        id: Id,
+        edge_name: &str,
+        max_depth: Option<usize>,
+    ) -> DatabaseResult<Vec<(Id, usize)>> {
+        let mut traversal = Traversal::new(vec![id], max_depth);
+        let mut reached = Vec::new();
+        while let Some(id) = traversal.pending() {
+            let ent = self.get(id)?;
+            reached.extend(traversal.visit(edge_ids(ent.as_deref(), edge_name)));
+        }
+        Ok(reached)
+    }
+
+    /// Finds the ids along a shortest path from one ent to another by
+    /// following the edge of the given name, including both ents, or none
+    /// if the second ent cannot be reached from the first
+    fn shortest_path(&self, from: Id, to: Id, edge_name: &str) -> DatabaseResult<Option<Vec<Id>>> {
+        if from == to {
+            return Ok(self.get(from)?.map(|_| vec![from]));
+        }
+
+        let mut traversal = Traversal::new(vec![from], None);
+        while let Some(id) = traversal.pending() {
+            let ent = self.get(id)?;
+            if let Some((id, _)) = traversal.visit(edge_ids(ent.as_deref(), edge_name)) {
+                if id == to {
+                    return Ok(Some(traversal.path_to(to)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the ids of all ents whose edge of the given name points at the
+    /// ent with the given id, in ascending order
+    ///
+    /// By default, this finds every ent whose edge passes an id filter using
+    /// [`Database::find_all`]; databases should override this to look up the
+    /// ents using an index of incoming edges.
+    fn referrers(&self, id: Id, edge_name: &str) -> DatabaseResult<Vec<Id>> {
+        let query = Query::default().where_edge(edge_name, Filter::Id(TypedPredicate::equals(id)));
+        Ok(self
+            .find_all(query)?
+            .into_iter()
+            .map(|ent| ent.id())
+            .collect())
+    }
+
+    /// Reserves a new, unique id without inserting an ent, which is useful
+    /// when staging several ents within a [`Transaction`] that need to
+    /// reference one another through their edges. A reserved id that is
+    /// never used to insert an ent is not returned to the database.
+    ///
+    /// By default, this fails with [`DatabaseError::UnsupportedOperation`]
+    /// as only the database knows how to allocate its ids.
+    fn reserve_id(&self) -> DatabaseResult<Id> {
+        Err(DatabaseError::UnsupportedOperation {
+            name: String::from("reserve_id"),
+        })
+    }
+
+    /// Applies all operations staged within the transaction in order such
+    /// that either every operation succeeds or none of them are applied.
+    ///
+    /// The ids of the inserted ents are returned in the order that the
+    /// inserts were staged.
+    ///
+    /// By default, this fails with [`DatabaseError::UnsupportedOperation`]
+    /// as only the database knows how to apply operations atomically.
+    fn commit_transaction(&self, _tx: Transaction) -> DatabaseResult<Vec<Id>> {
+        Err(DatabaseError::UnsupportedOperation {
+            name: String::from("commit_transaction"),
+        })
+    }
 }
 
 pub trait DatabaseExt: Database {
@@ -102,6 +349,18 @@ pub trait DatabaseExt: Database {
 
     /// Finds ents that match the specified query and are of the specified type
     fn find_all_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Vec<E>>;
+
+    /// Finds the first ent that matches the specified query and is of the
+    /// specified type
+    fn find_first_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Option<E>>;
+
+    /// Stages operations within a new transaction using the given function,
+    /// committing the transaction if the function succeeds and discarding
+    /// it if the function fails
+    fn transaction<F: FnOnce(&mut Transaction) -> DatabaseResult<()>>(
+        &self,
+        f: F,
+    ) -> DatabaseResult<Vec<Id>>;
 }
 
 impl<T: Database> DatabaseExt for T {
@@ -122,4 +381,82 @@ impl<T: Database> DatabaseExt for T {
         self.find_all(query)
             .map(|x| x.into_iter().filter_map(|ent| ent.to_ent::<E>()).collect())
     }
+
+    fn find_first_typed<E: Ent>(&self, query: Query) -> DatabaseResult<Option<E>> {
+        self.find_first(query)
+            .map(|x| x.and_then(|ent| ent.to_ent::<E>()))
+    }
+
+    fn transaction<F: FnOnce(&mut Transaction) -> DatabaseResult<()>>(
+        &self,
+        f: F,
+    ) -> DatabaseResult<Vec<Id>> {
+        let mut tx = Transaction::new();
+        f(&mut tx)?;
+        self.commit_transaction(tx)
+    }
+}
+
+/// Collects the values of the named fields of the ent, where names can also
+/// be paths into fields, leaving out any name without a value
+fn project_fields(ent: &dyn Ent, names: &[String]) -> HashMap<String, Value> {
+    names
+        .iter()
+        .filter_map(|name| FieldPath::lookup(ent, name).map(|value| (name.to_string(), value)))
+        .collect()
+}
+
+#[cfg(all(test, feature = "inmemory_db"))]
+mod tests {
+    use super::*;
+    use crate::UntypedEnt;
+
+    /// Database that only implements the required methods, relying on the
+    /// defaults of the trait for everything else
+    struct MinimalDatabase(InmemoryDatabase);
+
+    impl Database for MinimalDatabase {
+        fn get(&self, id: Id) -> DatabaseResult<Option<Box<dyn Ent>>> {
+            self.0.get(id)
+        }
+
+        fn remove(&self, id: Id) -> DatabaseResult<bool> {
+            self.0.remove(id)
+        }
+
+        fn insert(&self, ent: Box<dyn Ent>) -> DatabaseResult<Id> {
+            self.0.insert(ent)
+        }
+
+        fn get_all(&self, ids: Vec<Id>) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+            self.0.get_all(ids)
+        }
+
+        fn find_all(&self, query: Query) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+            self.0.find_all(query)
+        }
+    }
+
+    #[test]
+    fn reserve_id_should_fail_by_default() {
+        let db = MinimalDatabase(InmemoryDatabase::default());
+        assert!(matches!(
+            db.reserve_id(),
+            Err(DatabaseError::UnsupportedOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn commit_transaction_should_fail_by_default() {
+        let db = MinimalDatabase(InmemoryDatabase::default());
+        let result = db.transaction(|tx| {
+            tx.insert(Box::from(UntypedEnt::empty_with_id(1)));
+            Ok(())
+        });
+        assert!(matches!(
+            result,
+            Err(DatabaseError::UnsupportedOperation { .. })
+        ));
+        assert!(db.get(1).unwrap().is_none());
+    }
 }
@@ -0,0 +1,233 @@
+use crate::ent::{Filter, Query};
+use std::fmt;
+
+/// Represents the steps a database takes to find the ents that match a
+/// query, produced by [`Database::explain`](super::Database::explain)
+///
+/// Each step works with a set of candidate ids, starting from either the
+/// ids of every ent or the ids found using an index, and narrows or replaces
+/// the candidates until only the ids of matching ents remain.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Filter, PlanStep, Predicate, Query, QueryPlan, TypedPredicate};
+///
+/// let query = Query::default()
+///     .where_field("name", Predicate::equals("alice"))
+///     .where_id(TypedPredicate::greater_than(3));
+///
+/// // Without knowledge of indexes, filters are checked in order against
+/// // every ent
+/// let plan = QueryPlan::unoptimized(&query);
+/// assert_eq!(plan.steps().len(), 3);
+/// assert_eq!(plan.to_string(), concat!(
+///     "1. scan all ents (unknown candidates)\n",
+///     "2. check `field(name) = \"alice\"`, loading each ent (unknown candidates)\n",
+///     "3. check `id > 3`, using each id (unknown candidates)\n",
+/// ));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryPlan {
+    steps: Vec<PlanStep>,
+}
+
+impl QueryPlan {
+    /// Creates a new plan comprised of the given steps in order
+    pub fn new(steps: Vec<PlanStep>) -> Self {
+        Self { steps }
+    }
+
+    /// Creates a plan that scans every ent and applies the filters of the
+    /// query in the order they are given, used by databases that do not
+    /// plan queries themselves
+    pub fn unoptimized(query: &Query) -> Self {
+        let mut steps = vec![PlanStep::Scan { estimate: None }];
+        steps.extend(query.filters().iter().map(|filter| match filter {
+            Filter::IntoEdge(name) => PlanStep::IntoEdge {
+                name: name.to_string(),
+            },
+            Filter::IntoEdgeRecursive(name, max_depth) => PlanStep::IntoEdgeRecursive {
+                name: name.to_string(),
+                max_depth: *max_depth,
+            },
+            filter => PlanStep::Check {
+                filter: filter.clone(),
+                cost: CheckCost::of(filter),
+                estimate: None,
+            },
+        }));
+        Self { steps }
+    }
+
+    /// Returns the steps of the plan in the order they are performed
+    pub fn steps(&self) -> &[PlanStep] {
+        &self.steps
+    }
+
+    /// Returns the estimated total candidates remaining after the final
+    /// step, or none if unknown
+    pub fn estimate(&self) -> Option<usize> {
+        self.steps.last().and_then(PlanStep::estimate)
+    }
+}
+
+/// Displays each step of the plan on its own numbered line
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}. {}", i + 1, step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents a single step of a [`QueryPlan`], where the estimate of each
+/// step is the most candidates that can remain after the step is performed
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlanStep {
+    /// Uses the ids of every ent in the database as candidates
+    Scan { estimate: Option<usize> },
+
+    /// Narrows the candidates to the ids found for the filter using one or
+    /// more indexes, which also become the candidates if this is the first
+    /// step. If the index does not answer the filter exactly, the filter is
+    /// then checked against each remaining candidate.
+    Lookup {
+        filter: Filter,
+        indexes: Vec<String>,
+        is_exact: bool,
+        estimate: Option<usize>,
+    },
+
+    /// Checks the filter against each candidate, keeping those that match
+    Check {
+        filter: Filter,
+        cost: CheckCost,
+        estimate: Option<usize>,
+    },
+
+    /// Replaces each candidate with the ids of the ents of its edge
+    IntoEdge { name: String },
+
+    /// Replaces the candidates with the ids of the ents reached by following
+    /// the edge from ent to ent, up to the maximum number of hops or
+    /// transitively if there is no maximum
+    IntoEdgeRecursive {
+        name: String,
+        max_depth: Option<usize>,
+    },
+}
+
+impl PlanStep {
+    /// Returns the estimated total candidates remaining after the step, or
+    /// none if unknown
+    pub fn estimate(&self) -> Option<usize> {
+        match self {
+            Self::Scan { estimate } => *estimate,
+            Self::Lookup { estimate, .. } => *estimate,
+            Self::Check { estimate, .. } => *estimate,
+            Self::IntoEdge { .. } | Self::IntoEdgeRecursive { .. } => None,
+        }
+    }
+
+    /// Returns the filter applied by the step, if any
+    pub fn filter(&self) -> Option<&Filter> {
+        match self {
+            Self::Lookup { filter, .. } | Self::Check { filter, .. } => Some(filter),
+            Self::Scan { .. } | Self::IntoEdge { .. } | Self::IntoEdgeRecursive { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for PlanStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Scan { .. } => write!(f, "scan all ents")?,
+            Self::Lookup {
+                filter,
+                indexes,
+                is_exact,
+                ..
+            } => {
+                write!(
+                    f,
+                    "look up `{}` using index on {}",
+                    filter,
+                    indexes.join(", ")
+                )?;
+                if *is_exact {
+                    write!(f, " (exact)")?;
+                } else {
+                    write!(f, ", then check each candidate")?;
+                }
+            }
+            Self::Check { filter, cost, .. } => {
+                let how = match cost {
+                    CheckCost::Id => "using each id",
+                    CheckCost::Ent => "loading each ent",
+                    CheckCost::Edges => "loading each ent and the ents of its edges",
+                };
+                write!(f, "check `{}`, {}", filter, how)?;
+            }
+            Self::IntoEdge { name } => {
+                write!(f, "replace each candidate with the ents of edge {}", name)?
+            }
+            Self::IntoEdgeRecursive { name, max_depth } => {
+                write!(
+                    f,
+                    "replace the candidates with the ents reached through edge {}",
+                    name
+                )?;
+                match max_depth {
+                    Some(1) => write!(f, " within 1 hop")?,
+                    Some(max_depth) => write!(f, " within {} hops", max_depth)?,
+                    None => write!(f, " transitively")?,
+                }
+            }
+        }
+
+        match self.estimate() {
+            Some(1) => write!(f, " (~1 candidate)"),
+            Some(estimate) => write!(f, " (~{} candidates)", estimate),
+            None => write!(f, " (unknown candidates)"),
+        }
+    }
+}
+
+/// Represents how expensive it is to check a filter against a candidate,
+/// ordered from least to most expensive
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CheckCost {
+    /// Checked using only the candidate's id
+    Id,
+
+    /// Checked by loading the candidate's ent
+    Ent,
+
+    /// Checked by loading the candidate's ent and the ents of its edges
+    Edges,
+}
+
+impl CheckCost {
+    /// Determines the cost of checking the filter, which is the cost of its
+    /// most expensive part
+    pub fn of(filter: &Filter) -> Self {
+        match filter {
+            Filter::Id(_) => Self::Id,
+            Filter::Edge(_, _) | Filter::EdgeRecursive(_, _, _) => Self::Edges,
+            Filter::And(list) | Filter::Or(list) => {
+                list.iter().map(Self::of).max().unwrap_or(Self::Id)
+            }
+            Filter::Not(filter) => Self::of(filter),
+            Filter::Type(_)
+            | Filter::Created(_)
+            | Filter::LastUpdated(_)
+            | Filter::Field(_, _)
+            | Filter::EdgeCount(_, _)
+            | Filter::Search(_, _)
+            | Filter::IntoEdge(_)
+            | Filter::IntoEdgeRecursive(_, _) => Self::Ent,
+        }
+    }
+}
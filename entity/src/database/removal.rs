@@ -0,0 +1,60 @@
+use crate::Id;
+
+/// Describes the ents changed by removing an ent, produced by
+/// [`Database::remove_cascading`](super::Database::remove_cascading)
+///
+/// Removing an ent applies the deletion policy of each of its edges, which
+/// can remove other ents or update them to no longer refer to removed ents.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::Removal;
+///
+/// let removal = Removal::new(vec![1, 2, 3], vec![4]);
+/// assert_eq!(removal.id(), 1);
+/// assert_eq!(removal.removed(), &[1, 2, 3]);
+/// assert_eq!(removal.cascaded(), &[2, 3]);
+/// assert_eq!(removal.updated(), &[4]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Removal {
+    removed: Vec<Id>,
+    updated: Vec<Id>,
+}
+
+impl Removal {
+    /// Creates a new description from the ids of removed ents, starting with
+    /// the ent that was asked to be removed, and the ids of updated ents
+    ///
+    /// ### Panics
+    ///
+    /// Panics if there are no removed ents.
+    pub fn new(removed: Vec<Id>, updated: Vec<Id>) -> Self {
+        assert!(!removed.is_empty(), "Removal must include the removed ent");
+        Self { removed, updated }
+    }
+
+    /// Returns the id of the ent that was asked to be removed
+    pub fn id(&self) -> Id {
+        self.removed[0]
+    }
+
+    /// Returns the ids of all removed ents in the order they were reached,
+    /// starting with the ent that was asked to be removed
+    pub fn removed(&self) -> &[Id] {
+        &self.removed
+    }
+
+    /// Returns the ids of ents removed because of the deletion policies of
+    /// edges rather than being asked to be removed
+    pub fn cascaded(&self) -> &[Id] {
+        &self.removed[1..]
+    }
+
+    /// Returns the ids of ents that remain but had the ids of removed ents
+    /// taken out of their edges
+    pub fn updated(&self) -> &[Id] {
+        &self.updated
+    }
+}
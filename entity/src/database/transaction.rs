@@ -0,0 +1,119 @@
+use crate::{Ent, Id};
+use derive_more::IntoIterator;
+
+/// Represents a single operation staged within a [`Transaction`]
+#[derive(Clone)]
+pub enum TransactionOp {
+    /// Inserts the ent, overwriting any ent with a matching id
+    Insert(Box<dyn Ent>),
+
+    /// Removes the ent with the corresponding id, triggering edge processing
+    /// for all disconnected ents
+    Remove(Id),
+}
+
+impl TransactionOp {
+    /// Returns true if the operation is an insertion
+    #[inline]
+    pub fn is_insert(&self) -> bool {
+        matches!(self, Self::Insert(_))
+    }
+
+    /// Returns true if the operation is a removal
+    #[inline]
+    pub fn is_remove(&self) -> bool {
+        matches!(self, Self::Remove(_))
+    }
+}
+
+/// Represents a series of inserts and removals that are staged locally and
+/// later committed to a database all at once. Either every operation in the
+/// transaction is applied or none of them are.
+///
+/// Nothing is sent to the database until the transaction is committed via
+/// [`Database::commit_transaction`](crate::Database::commit_transaction), so
+/// rolling back a transaction is a matter of dropping it. Databases that
+/// cannot apply the operations atomically refuse to commit them at all.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Database, DatabaseResult, Edge, Id, Transaction, UntypedEnt};
+///
+/// fn add_user_with_address<D: Database>(db: &D) -> DatabaseResult<Vec<Id>> {
+///     // Reserve ids up front so the ents can reference one another
+///     let user_id = db.reserve_id()?;
+///     let address_id = db.reserve_id()?;
+///
+///     let mut tx = Transaction::new();
+///     tx.insert(Box::from(UntypedEnt::from_collections(
+///         user_id,
+///         vec![],
+///         vec![Edge::new("address", address_id)],
+///     )))
+///     .insert(Box::from(UntypedEnt::empty_with_id(address_id)));
+///
+///     db.commit_transaction(tx)
+/// }
+///
+/// let mut tx = Transaction::new();
+/// tx.insert(Box::from(UntypedEnt::empty_with_id(1))).remove(2);
+/// assert_eq!(tx.len(), 2);
+///
+/// tx.rollback();
+/// assert!(tx.is_empty());
+/// ```
+#[derive(Clone, Default, IntoIterator)]
+pub struct Transaction(Vec<TransactionOp>);
+
+impl Transaction {
+    /// Begins a new, empty transaction
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stages the insertion of an ent, overwriting any ent with a matching
+    /// id when committed. If the ent's id is set to the ephemeral id, a
+    /// unique id will be assigned to the ent when committed.
+    pub fn insert(&mut self, ent: Box<dyn Ent>) -> &mut Self {
+        self.0.push(TransactionOp::Insert(ent));
+        self
+    }
+
+    /// Stages the insertion of an ent of a specific type
+    pub fn insert_typed<E: Ent>(&mut self, ent: E) -> &mut Self {
+        self.insert(Box::from(ent))
+    }
+
+    /// Stages the removal of the ent with the corresponding id
+    pub fn remove(&mut self, id: Id) -> &mut Self {
+        self.0.push(TransactionOp::Remove(id));
+        self
+    }
+
+    /// Discards all operations staged so far, leaving an empty transaction
+    #[inline]
+    pub fn rollback(&mut self) {
+        self.0.clear();
+    }
+
+    /// Returns the operations staged within the transaction in the order
+    /// that they will be applied
+    #[inline]
+    pub fn ops(&self) -> &[TransactionOp] {
+        &self.0
+    }
+
+    /// Returns the total operations staged within the transaction
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no operations have been staged
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
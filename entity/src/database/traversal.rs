@@ -0,0 +1,187 @@
+use crate::{Ent, Id};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Walks the ents reachable from a set of starting ents by repeatedly
+/// following an edge, breadth first so that each ent is reached using the
+/// fewest hops possible
+///
+/// The traversal does not look up ents itself. Instead, the id of the next
+/// ent to visit is available from [`Traversal::pending`] and the ids on the
+/// edge of that ent are given to [`Traversal::visit`], which allows the
+/// same traversal to be driven by blocking and async databases alike.
+///
+/// Each ent is visited at most once after the starting ents, so cycles
+/// formed by the edge do not cause the traversal to repeat itself. Starting
+/// ents are only reached if there is a path of at least one hop back to them.
+pub(crate) struct Traversal {
+    max_depth: Option<usize>,
+    queue: VecDeque<Step>,
+    queued: HashSet<Id>,
+    starts: HashSet<Id>,
+    parents: HashMap<Id, Id>,
+}
+
+/// Represents an ent waiting to be visited, alongside the hops taken to
+/// reach it and the ent it was reached from
+struct Step {
+    id: Id,
+    depth: usize,
+    parent: Option<Id>,
+}
+
+impl Traversal {
+    /// Creates a traversal from the given ents that follows up to the
+    /// maximum number of hops, or until no new ents are reached if there is
+    /// no maximum
+    pub fn new<I: IntoIterator<Item = Id>>(starts: I, max_depth: Option<usize>) -> Self {
+        let starts: HashSet<Id> = starts.into_iter().collect();
+        let queue = starts
+            .iter()
+            .map(|id| Step {
+                id: *id,
+                depth: 0,
+                parent: None,
+            })
+            .collect();
+
+        Self {
+            max_depth,
+            queue,
+            queued: HashSet::new(),
+            starts,
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Returns the id of the next ent to visit, or none if the traversal
+    /// is finished
+    pub fn pending(&self) -> Option<Id> {
+        self.queue.front().map(|step| step.id)
+    }
+
+    /// Visits the pending ent using the ids on its edge, or none if the ent
+    /// does not exist, returning the ent's id and the hops taken to reach it
+    /// if the ent was reached from another ent
+    pub fn visit(&mut self, edge_ids: Option<Vec<Id>>) -> Option<(Id, usize)> {
+        let step = self.queue.pop_front()?;
+        let edge_ids = edge_ids?;
+
+        // Ents at the maximum depth are only visited to confirm they exist,
+        // so the ents on their edge are not queued
+        let is_last = matches!(self.max_depth, Some(max) if step.depth >= max);
+        if !is_last {
+            for id in edge_ids {
+                if self.queued.insert(id) {
+                    self.queue.push_back(Step {
+                        id,
+                        depth: step.depth + 1,
+                        parent: Some(step.id),
+                    });
+                }
+            }
+        }
+
+        let parent = step.parent?;
+        self.parents.insert(step.id, parent);
+        Some((step.id, step.depth))
+    }
+
+    /// Returns the ids along the path taken to reach the ent, starting with
+    /// one of the starting ents and ending with the ent itself
+    ///
+    /// Because ents are visited breadth first, this is a shortest path to
+    /// the ent. If the ent has not been reached, the path only contains the
+    /// ent itself.
+    pub fn path_to(&self, id: Id) -> Vec<Id> {
+        let mut path = vec![id];
+        let mut current = id;
+        while !self.starts.contains(&current) {
+            match self.parents.get(&current) {
+                Some(parent) => {
+                    path.push(*parent);
+                    current = *parent;
+                }
+                None => break,
+            }
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Returns the ids on the edge of the ent, which is empty if the ent does not
+/// have the edge, or none if there is no ent
+pub(crate) fn edge_ids(ent: Option<&dyn Ent>, edge_name: &str) -> Option<Vec<Id>> {
+    ent.map(|ent| {
+        ent.edge(edge_name)
+            .map(|edge| edge.to_ids())
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Visits every pending ent using a list of connections for the edge,
+    /// where ents with ids above 100 do not exist
+    fn run(traversal: &mut Traversal, connections: &[(Id, Id)]) -> Vec<(Id, usize)> {
+        let mut reached = Vec::new();
+        while let Some(id) = traversal.pending() {
+            let edge_ids = if id > 100 {
+                None
+            } else {
+                Some(
+                    connections
+                        .iter()
+                        .filter(|(from, _)| *from == id)
+                        .map(|(_, to)| *to)
+                        .collect(),
+                )
+            };
+            reached.extend(traversal.visit(edge_ids));
+        }
+        reached
+    }
+
+    #[test]
+    fn traversal_should_reach_ents_breadth_first_with_fewest_hops() {
+        let connections = [(1, 2), (1, 3), (2, 4), (3, 4), (4, 5), (1, 5)];
+        let mut traversal = Traversal::new(vec![1], None);
+        assert_eq!(
+            run(&mut traversal, &connections),
+            vec![(2, 1), (3, 1), (5, 1), (4, 2)]
+        );
+        assert_eq!(traversal.path_to(4), vec![1, 2, 4]);
+        assert_eq!(traversal.path_to(5), vec![1, 5]);
+    }
+
+    #[test]
+    fn traversal_should_stop_at_max_depth() {
+        let connections = [(1, 2), (2, 3), (3, 4)];
+        let mut traversal = Traversal::new(vec![1], Some(2));
+        assert_eq!(run(&mut traversal, &connections), vec![(2, 1), (3, 2)]);
+
+        let mut traversal = Traversal::new(vec![1], Some(0));
+        assert_eq!(run(&mut traversal, &connections), vec![]);
+    }
+
+    #[test]
+    fn traversal_should_visit_each_ent_once_within_cycles() {
+        let connections = [(1, 2), (2, 3), (3, 1), (3, 2)];
+        let mut traversal = Traversal::new(vec![1], None);
+        assert_eq!(
+            run(&mut traversal, &connections),
+            vec![(2, 1), (3, 2), (1, 3)]
+        );
+        assert_eq!(traversal.path_to(3), vec![1, 2, 3]);
+        assert_eq!(traversal.path_to(1), vec![1]);
+    }
+
+    #[test]
+    fn traversal_should_skip_ents_that_do_not_exist() {
+        let connections = [(1, 101), (1, 2), (2, 102)];
+        let mut traversal = Traversal::new(vec![1, 103], None);
+        assert_eq!(run(&mut traversal, &connections), vec![(2, 1)]);
+    }
+}
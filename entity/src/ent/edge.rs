@@ -4,13 +4,16 @@ use std::collections::HashSet;
 use strum::{Display, EnumDiscriminants, EnumString};
 
 /// Represents a definition of an edge, which is comprised of its name, type
-/// of edge value, and the edge's deletion policy
+/// of edge value, the edge's deletion policy, and the name of the edge kept
+/// in sync as its inverse, if any
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub struct EdgeDefinition {
     pub(super) name: String,
     r#type: EdgeValueType,
     deletion_policy: EdgeDeletionPolicy,
+    #[cfg_attr(feature = "serde-1", serde(default))]
+    inverse: Option<String>,
 }
 
 impl EdgeDefinition {
@@ -31,9 +34,18 @@ impl EdgeDefinition {
             name: name.into(),
             r#type: r#type.into(),
             deletion_policy,
+            inverse: None,
         }
     }
 
+    /// Updates the definition to keep the edge with the given name in sync
+    /// as the inverse of this edge, meaning that ents connected by this edge
+    /// have an edge with that name pointing back at the ent with this edge
+    pub fn with_inverse<N: Into<String>>(mut self, inverse: N) -> Self {
+        self.inverse = Some(inverse.into());
+        self
+    }
+
     /// The name of the edge tied to the definition
     #[inline]
     pub fn name(&self) -> &str {
@@ -52,6 +64,13 @@ impl EdgeDefinition {
         self.deletion_policy
     }
 
+    /// Returns the name of the edge kept in sync as the inverse of the edge
+    /// tied to the definition, if any
+    #[inline]
+    pub fn inverse(&self) -> Option<&str> {
+        self.inverse.as_deref()
+    }
+
     /// Returns true if the deletion policy is nothing
     #[inline]
     pub fn has_no_deletion_policy(&self) -> bool {
@@ -69,17 +88,35 @@ impl EdgeDefinition {
     pub fn has_deep_deletion_policy(&self) -> bool {
         matches!(self.deletion_policy(), EdgeDeletionPolicy::DeepDelete)
     }
+
+    /// Returns true if the deletion policy is restrict
+    #[inline]
+    pub fn has_restrict_deletion_policy(&self) -> bool {
+        matches!(self.deletion_policy(), EdgeDeletionPolicy::Restrict)
+    }
+
+    /// Returns true if the deletion policy is nullify
+    #[inline]
+    pub fn has_nullify_deletion_policy(&self) -> bool {
+        matches!(self.deletion_policy(), EdgeDeletionPolicy::Nullify)
+    }
 }
 
 impl From<Edge> for EdgeDefinition {
     fn from(edge: Edge) -> Self {
-        Self::new_with_deletion_policy(edge.name, edge.value, edge.deletion_policy)
+        Self {
+            inverse: edge.inverse,
+            ..Self::new_with_deletion_policy(edge.name, edge.value, edge.deletion_policy)
+        }
     }
 }
 
 impl<'a> From<&'a Edge> for EdgeDefinition {
     fn from(edge: &'a Edge) -> Self {
-        Self::new_with_deletion_policy(edge.name(), edge.value(), edge.deletion_policy())
+        Self {
+            inverse: edge.inverse.clone(),
+            ..Self::new_with_deletion_policy(edge.name(), edge.value(), edge.deletion_policy())
+        }
     }
 }
 
@@ -90,6 +127,43 @@ pub struct Edge {
     name: String,
     value: EdgeValue,
     deletion_policy: EdgeDeletionPolicy,
+    #[cfg_attr(
+        feature = "serde-1",
+        serde(default, deserialize_with = "deserialize_edge_inverse")
+    )]
+    inverse: Option<String>,
+}
+
+#[cfg(feature = "serde-1")]
+thread_local! {
+    /// Whether edges are being deserialized from the layout they had before
+    /// they held the name of their inverse edge
+    static IS_EDGE_INVERSE_OMITTED: std::cell::Cell<bool> =
+        const { std::cell::Cell::new(false) };
+}
+
+/// Deserializes the name of the inverse edge of an edge, reading nothing if
+/// the edge is in the layout from before it held the name
+#[cfg(feature = "serde-1")]
+fn deserialize_edge_inverse<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    if IS_EDGE_INVERSE_OMITTED.with(|x| x.get()) {
+        Ok(None)
+    } else {
+        serde::Deserialize::deserialize(deserializer)
+    }
+}
+
+/// Runs the given function with every edge deserialized from the layout it
+/// had before it held the name of its inverse edge, which is needed to read
+/// ents from formats like bincode that have no notion of a missing field
+#[cfg(feature = "sled_db")]
+pub(crate) fn with_edge_inverse_omitted<T, F: FnOnce() -> T>(f: F) -> T {
+    let was_omitted = IS_EDGE_INVERSE_OMITTED.with(|x| x.replace(true));
+    let result = f();
+    IS_EDGE_INVERSE_OMITTED.with(|x| x.set(was_omitted));
+    result
 }
 
 impl Edge {
@@ -135,9 +209,28 @@ impl Edge {
             name: name.into(),
             value: value.into(),
             deletion_policy,
+            inverse: None,
         }
     }
 
+    /// Updates the edge to keep the edge with the given name in sync as its
+    /// inverse, meaning that ents connected by this edge have an edge with
+    /// that name pointing back at the ent with this edge
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use entity::Edge;
+    ///
+    /// let edge = Edge::new("groups", vec![1, 2]).with_inverse("members");
+    /// assert_eq!(edge.inverse(), Some("members"));
+    /// assert_eq!(Edge::new("groups", vec![1, 2]).inverse(), None);
+    /// ```
+    pub fn with_inverse<N: Into<String>>(mut self, inverse: N) -> Self {
+        self.inverse = Some(inverse.into());
+        self
+    }
+
     /// The name of the edge
     #[inline]
     pub fn name(&self) -> &str {
@@ -178,6 +271,13 @@ impl Edge {
         self.deletion_policy
     }
 
+    /// Returns the name of the edge kept in sync as the inverse of this edge,
+    /// if any
+    #[inline]
+    pub fn inverse(&self) -> Option<&str> {
+        self.inverse.as_deref()
+    }
+
     /// Returns true if the deletion policy is nothing
     #[inline]
     pub fn has_no_deletion_policy(&self) -> bool {
@@ -195,6 +295,18 @@ impl Edge {
     pub fn has_deep_deletion_policy(&self) -> bool {
         matches!(self.deletion_policy(), EdgeDeletionPolicy::DeepDelete)
     }
+
+    /// Returns true if the deletion policy is restrict
+    #[inline]
+    pub fn has_restrict_deletion_policy(&self) -> bool {
+        matches!(self.deletion_policy(), EdgeDeletionPolicy::Restrict)
+    }
+
+    /// Returns true if the deletion policy is nullify
+    #[inline]
+    pub fn has_nullify_deletion_policy(&self) -> bool {
+        matches!(self.deletion_policy(), EdgeDeletionPolicy::Nullify)
+    }
 }
 
 /// Represents the policy to apply to an edge when its ent is deleted
@@ -211,6 +323,14 @@ pub enum EdgeDeletionPolicy {
     /// When this ent instance is deleted, fully delete all ents connected
     /// by this edge
     DeepDelete,
+
+    /// When an ent connected by this edge is deleted, refuse to delete it
+    /// while this ent instance still points at it
+    Restrict,
+
+    /// When an ent connected by this edge is deleted, take its id out of
+    /// this edge, leaving an optional edge empty
+    Nullify,
 }
 
 impl Default for EdgeDeletionPolicy {
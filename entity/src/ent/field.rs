@@ -70,6 +70,13 @@ impl FieldDefinition {
     pub fn is_immutable(&self) -> bool {
         self.attributes().contains(&FieldAttribute::Immutable)
     }
+
+    /// Returns true if this field is marked as searchable (used in
+    /// databases) in its definition
+    #[inline]
+    pub fn is_searchable(&self) -> bool {
+        self.attributes().contains(&FieldAttribute::Searchable)
+    }
 }
 
 impl From<Field> for FieldDefinition {
@@ -167,6 +174,12 @@ impl Field {
     pub fn is_immutable(&self) -> bool {
         self.attributes().contains(&FieldAttribute::Immutable)
     }
+
+    /// Returns true if this field is marked as searchable (used in databases)
+    #[inline]
+    pub fn is_searchable(&self) -> bool {
+        self.attributes().contains(&FieldAttribute::Searchable)
+    }
 }
 
 /// Represents an attribute associated with a field for an ent
@@ -179,4 +192,8 @@ pub enum FieldAttribute {
     /// Indicates that this field is immutable, meaning that it cannot be
     /// changed after being initialized
     Immutable,
+
+    /// Indicates that the words of the text within this field are indexed
+    /// for full-text search
+    Searchable,
 }
@@ -158,12 +158,17 @@ pub trait Ent: AsAny + DynClone + Send + Sync {
     /// Returns a copy of the value of the edge with the specified name
     fn edge(&self, name: &str) -> Option<EdgeValue>;
 
-    /// Returns a copy of all edges contained by the ent and their associated values
+    /// Returns a copy of all edges contained by the ent and their associated
+    /// values, each with the deletion policy and inverse from its definition
     fn edges(&self) -> Vec<Edge> {
         let mut edges = Vec::new();
-        for name in self.edge_names() {
-            if let Some(value) = self.edge(&name) {
-                edges.push(Edge::new(name, value));
+        for def in self.edge_definitions() {
+            if let Some(value) = self.edge(def.name()) {
+                let edge = Edge::new_with_deletion_policy(def.name(), value, def.deletion_policy());
+                edges.push(match def.inverse() {
+                    Some(inverse) => edge.with_inverse(inverse),
+                    None => edge,
+                });
             }
         }
         edges
@@ -258,6 +263,84 @@ impl<T: Ent> EntExt for T {
     }
 }
 
+/// Represents async counterparts to the database-related operations of an
+/// [`Ent`]. Each operation is performed against a copy of the ent on a
+/// dedicated thread pool so the async executor is never blocked.
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+pub trait AsyncEntExt: Ent {
+    /// Loads the ents connected by the edge with the given name
+    ///
+    /// Requires ent to be connected to a database
+    async fn load_edge_async(&self, name: &str) -> DatabaseResult<Vec<Box<dyn Ent>>>;
+
+    /// Loads ents of a specified type from a named edge
+    ///
+    /// Requires ent to be connected to a database
+    async fn load_edge_typed_async<E: Ent>(&self, name: &str) -> DatabaseResult<Vec<E>>;
+
+    /// Refreshes ent by checking database for latest version and returning it
+    ///
+    /// Requires ent to be connected to a database
+    async fn refresh_async(&mut self) -> DatabaseResult<()>;
+
+    /// Saves the ent to the database, updating this local instance's id
+    /// if the database has reported a new id
+    ///
+    /// Requires ent to be connected to a database
+    async fn commit_async(&mut self) -> DatabaseResult<()>;
+
+    /// Removes self from database, returning true if successful
+    ///
+    /// Requires ent to be connected to a database
+    async fn remove_async(&self) -> DatabaseResult<bool>;
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<T: Ent> AsyncEntExt for T {
+    async fn load_edge_async(&self, name: &str) -> DatabaseResult<Vec<Box<dyn Ent>>> {
+        let ent = dyn_clone::clone(self);
+        let name = name.to_string();
+        blocking::unblock(move || ent.load_edge(&name)).await
+    }
+
+    async fn load_edge_typed_async<E: Ent>(&self, name: &str) -> DatabaseResult<Vec<E>> {
+        self.load_edge_async(name).await.map(|ents| {
+            ents.into_iter()
+                .filter_map(|ent| ent.to_ent::<E>())
+                .collect()
+        })
+    }
+
+    async fn refresh_async(&mut self) -> DatabaseResult<()> {
+        let mut ent = dyn_clone::clone(self);
+        let (ent, result) = blocking::unblock(move || {
+            let result = ent.refresh();
+            (ent, result)
+        })
+        .await;
+        *self = ent;
+        result
+    }
+
+    async fn commit_async(&mut self) -> DatabaseResult<()> {
+        let mut ent = dyn_clone::clone(self);
+        let (ent, result) = blocking::unblock(move || {
+            let result = ent.commit();
+            (ent, result)
+        })
+        .await;
+        *self = ent;
+        result
+    }
+
+    async fn remove_async(&self) -> DatabaseResult<bool> {
+        let ent = dyn_clone::clone(self);
+        blocking::unblock(move || ent.remove()).await
+    }
+}
+
 /// Represents a general-purpose ent that is shapeless (no hard type) and
 /// maintains fields and edges using internal maps. This ent can optionally
 /// be connected to a database and supports additional functionality like
@@ -700,10 +783,7 @@ impl Ent for UntypedEnt {
         self.mark_updated()?;
 
         match self.edges.entry(name.to_string()) {
-            Entry::Occupied(mut x) => {
-                let edge = Edge::new(name.to_string(), value);
-                Ok(x.insert(edge).into_value())
-            }
+            Entry::Occupied(mut x) => Ok(std::mem::replace(x.get_mut().value_mut(), value)),
             Entry::Vacant(_) => Err(EntMutationError::NoEdge {
                 name: name.to_string(),
             }),
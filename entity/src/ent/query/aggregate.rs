@@ -0,0 +1,548 @@
+use crate::{Ent, FieldPath, IndexKey, Number, Value};
+use std::{cmp::Ordering, collections::BTreeMap, convert::TryFrom};
+
+/// Represents a computation performed over the ents found by a query that
+/// produces a single value
+///
+/// Aside from counting, each aggregate is computed over the numeric values
+/// of a named field, skipping ents that are missing the field or whose value
+/// is not a number.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Aggregate, Aggregator, Field, UntypedEnt, Value};
+///
+/// let mut aggregator = Aggregator::new(Aggregate::sum("age"));
+/// aggregator.push(&UntypedEnt::from_collections(1, vec![Field::new("age", 30)], vec![]));
+/// aggregator.push(&UntypedEnt::from_collections(2, vec![Field::new("age", 12)], vec![]));
+/// aggregator.push(&UntypedEnt::from_collections(3, vec![], vec![]));
+///
+/// assert_eq!(aggregator.finish(), Value::from(42));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregate {
+    /// Counts the ents
+    Count,
+
+    /// Sums the numeric values of the field
+    Sum(String),
+
+    /// Finds the smallest numeric value of the field
+    Min(String),
+
+    /// Finds the largest numeric value of the field
+    Max(String),
+
+    /// Averages the numeric values of the field
+    Average(String),
+}
+
+impl Aggregate {
+    /// Creates a new aggregate that counts ents
+    pub fn count() -> Self {
+        Self::Count
+    }
+
+    /// Creates a new aggregate that sums the values of the field
+    pub fn sum<S: Into<String>>(name: S) -> Self {
+        Self::Sum(name.into())
+    }
+
+    /// Creates a new aggregate that finds the smallest value of the field
+    pub fn min<S: Into<String>>(name: S) -> Self {
+        Self::Min(name.into())
+    }
+
+    /// Creates a new aggregate that finds the largest value of the field
+    pub fn max<S: Into<String>>(name: S) -> Self {
+        Self::Max(name.into())
+    }
+
+    /// Creates a new aggregate that averages the values of the field
+    pub fn average<S: Into<String>>(name: S) -> Self {
+        Self::Average(name.into())
+    }
+
+    /// Returns the name of the field being aggregated, if any
+    pub fn field_name(&self) -> Option<&str> {
+        match self {
+            Self::Count => None,
+            Self::Sum(name) | Self::Min(name) | Self::Max(name) | Self::Average(name) => Some(name),
+        }
+    }
+}
+
+/// Represents the property of an ent used to divide ents into groups that
+/// are each aggregated separately
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupBy {
+    /// Groups by the ent's type
+    Type,
+
+    /// Groups by the value of an ent's field, where ents missing the field
+    /// are grouped together under [`Value::Optional`] of none
+    Field(String),
+}
+
+impl GroupBy {
+    /// Produces the value identifying the group of the ent
+    pub fn key(&self, ent: &dyn Ent) -> Value {
+        match self {
+            Self::Type => Value::from(ent.r#type()),
+            Self::Field(name) => FieldPath::lookup(ent, name).unwrap_or(Value::Optional(None)),
+        }
+    }
+}
+
+/// Represents the running state of an [`Aggregate`] as ents are pushed into
+/// it one at a time
+///
+/// Sums are computed using unsigned integers while every value is a positive
+/// integer, signed integers once a negative integer is found, and floating
+/// point numbers once a float is found or an integer sum would overflow.
+/// Averages are always floating point numbers. Sums, minimums, maximums, and
+/// averages of no values are [`Value::Optional`] of none, whereas counts
+/// are always numbers.
+#[derive(Clone, Debug)]
+pub struct Aggregator {
+    aggregate: Aggregate,
+    count: usize,
+    sum: Option<Sum>,
+    extreme: Option<Number>,
+}
+
+impl Aggregator {
+    /// Creates a new aggregator that has not yet seen any ents
+    pub fn new(aggregate: Aggregate) -> Self {
+        Self {
+            aggregate,
+            count: 0,
+            sum: None,
+            extreme: None,
+        }
+    }
+
+    /// Returns the aggregate being computed
+    pub fn aggregate(&self) -> &Aggregate {
+        &self.aggregate
+    }
+
+    /// Includes the ent in the aggregate
+    pub fn push(&mut self, ent: &dyn Ent) {
+        let number = match self.aggregate.field_name() {
+            Some(name) => match FieldPath::lookup(ent, name).as_ref().and_then(to_number) {
+                Some(number) => number,
+                None => return,
+            },
+            None => {
+                self.count += 1;
+                return;
+            }
+        };
+
+        match &self.aggregate {
+            Aggregate::Count => {}
+            Aggregate::Sum(_) | Aggregate::Average(_) => {
+                self.count += 1;
+                self.sum = Some(match self.sum {
+                    Some(sum) => sum.add(number),
+                    None => Sum::from(number),
+                });
+            }
+            Aggregate::Min(_) | Aggregate::Max(_) => {
+                // Numbers that cannot be ordered, such as NaN, are skipped
+                if compare_numbers(number, number).is_none() {
+                    return;
+                }
+
+                let ordering = if matches!(self.aggregate, Aggregate::Min(_)) {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+                let replace = match self.extreme {
+                    Some(extreme) => compare_numbers(number, extreme) == Some(ordering),
+                    None => true,
+                };
+                if replace {
+                    self.extreme = Some(number);
+                }
+            }
+        }
+    }
+
+    /// Consumes the aggregator, producing the value of the aggregate
+    pub fn finish(self) -> Value {
+        let number = match self.aggregate {
+            Aggregate::Count => return Value::from(self.count),
+            Aggregate::Sum(_) => self.sum.map(Number::from),
+            Aggregate::Min(_) | Aggregate::Max(_) => self.extreme,
+            Aggregate::Average(_) => {
+                let count = self.count;
+                self.sum
+                    .map(|sum| Number::from(sum.to_f64() / count as f64))
+            }
+        };
+
+        match number {
+            Some(number) => Value::from(number),
+            None => Value::Optional(None),
+        }
+    }
+}
+
+/// Represents the running state of an [`Aggregate`] computed separately for
+/// each group of ents
+///
+/// Groups are produced ordered by their values, followed by any groups whose
+/// values cannot be ordered (such as lists and maps) in the order they were
+/// first found.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Aggregate, Field, GroupBy, GroupedAggregator, UntypedEnt, Value};
+///
+/// let mut aggregator = GroupedAggregator::new(
+///     Aggregate::count(),
+///     GroupBy::Field(String::from("team")),
+/// );
+/// aggregator.push(&UntypedEnt::from_collections(1, vec![Field::new("team", "red")], vec![]));
+/// aggregator.push(&UntypedEnt::from_collections(2, vec![Field::new("team", "blue")], vec![]));
+/// aggregator.push(&UntypedEnt::from_collections(3, vec![Field::new("team", "red")], vec![]));
+///
+/// assert_eq!(
+///     aggregator.finish(),
+///     vec![
+///         (Value::from("blue"), Value::from(1usize)),
+///         (Value::from("red"), Value::from(2usize)),
+///     ],
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct GroupedAggregator {
+    aggregate: Aggregate,
+    group_by: GroupBy,
+    groups: BTreeMap<IndexKey, (Value, Aggregator)>,
+    unordered_groups: Vec<(Value, Aggregator)>,
+}
+
+impl GroupedAggregator {
+    /// Creates a new aggregator that has not yet seen any ents
+    pub fn new(aggregate: Aggregate, group_by: GroupBy) -> Self {
+        Self {
+            aggregate,
+            group_by,
+            groups: BTreeMap::new(),
+            unordered_groups: Vec::new(),
+        }
+    }
+
+    /// Includes the ent in the aggregate of its group
+    pub fn push(&mut self, ent: &dyn Ent) {
+        let value = self.group_by.key(ent);
+        let aggregate = &self.aggregate;
+        let group = match IndexKey::from_value(&value) {
+            Some(key) => self
+                .groups
+                .entry(key)
+                .or_insert_with(|| (value, Aggregator::new(aggregate.clone()))),
+            None => match self.unordered_groups.iter().position(|(x, _)| *x == value) {
+                Some(i) => &mut self.unordered_groups[i],
+                None => {
+                    self.unordered_groups
+                        .push((value, Aggregator::new(aggregate.clone())));
+                    self.unordered_groups.last_mut().unwrap()
+                }
+            },
+        };
+        group.1.push(ent);
+    }
+
+    /// Consumes the aggregator, producing the value identifying each group
+    /// alongside the value of the group's aggregate
+    pub fn finish(self) -> Vec<(Value, Value)> {
+        let (_, groups): (Vec<IndexKey>, Vec<(Value, Aggregator)>) =
+            self.groups.into_iter().unzip();
+        groups
+            .into_iter()
+            .chain(self.unordered_groups)
+            .map(|(value, aggregator)| (value, aggregator.finish()))
+            .collect()
+    }
+}
+
+/// Converts a value into a number if it is a number, or an optional value
+/// containing a number
+fn to_number(value: &Value) -> Option<Number> {
+    match value {
+        Value::Optional(Some(x)) => to_number(x),
+        x => x.to_number(),
+    }
+}
+
+/// Represents a sum that widens as needed to hold the values added to it
+#[derive(Copy, Clone, Debug)]
+enum Sum {
+    Unsigned(u128),
+    Signed(i128),
+    Float(f64),
+}
+
+impl Sum {
+    fn add(self, number: Number) -> Self {
+        let sum = match (self, Self::from(number)) {
+            (Self::Float(a), b) | (b, Self::Float(a)) => Some(Self::Float(a + b.to_f64())),
+            (Self::Unsigned(a), Self::Unsigned(b)) => a.checked_add(b).map(Self::Unsigned),
+            (a, b) => match (a.to_i128(), b.to_i128()) {
+                (Some(a), Some(b)) => a.checked_add(b).map(Self::Signed),
+                _ => None,
+            },
+        };
+
+        // If the integer sum would overflow, fall back to a float
+        sum.unwrap_or_else(|| Self::Float(self.to_f64() + number.to_f64()))
+    }
+
+    fn to_i128(self) -> Option<i128> {
+        match self {
+            Self::Unsigned(x) => i128::try_from(x).ok(),
+            Self::Signed(x) => Some(x),
+            Self::Float(_) => None,
+        }
+    }
+
+    fn to_f64(self) -> f64 {
+        match self {
+            Self::Unsigned(x) => x as f64,
+            Self::Signed(x) => x as f64,
+            Self::Float(x) => x,
+        }
+    }
+}
+
+/// Compares two numbers as unsigned or signed integers when both are
+/// integers and as floating point numbers otherwise, returning none if
+/// either number is NaN
+fn compare_numbers(a: Number, b: Number) -> Option<Ordering> {
+    match (Sum::from(a), Sum::from(b)) {
+        (Sum::Unsigned(a), Sum::Unsigned(b)) => Some(a.cmp(&b)),
+        (Sum::Signed(a), Sum::Signed(b)) => Some(a.cmp(&b)),
+        (Sum::Signed(_), Sum::Unsigned(_)) => Some(Ordering::Less),
+        (Sum::Unsigned(_), Sum::Signed(_)) => Some(Ordering::Greater),
+        (a, b) => a.to_f64().partial_cmp(&b.to_f64()),
+    }
+}
+
+impl From<Number> for Sum {
+    fn from(number: Number) -> Self {
+        if number.is_float() {
+            Self::Float(number.to_f64())
+        } else if number.is_negative() {
+            Self::Signed(number.to_i128())
+        } else {
+            Self::Unsigned(number.to_u128())
+        }
+    }
+}
+
+impl From<Sum> for Number {
+    fn from(sum: Sum) -> Self {
+        match sum {
+            Sum::Unsigned(x) => Number::from(x),
+            Sum::Signed(x) => Number::from(x),
+            Sum::Float(x) => Number::from(x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Field, UntypedEnt};
+
+    fn ent_with_value<V: Into<Value>>(id: usize, value: V) -> UntypedEnt {
+        UntypedEnt::from_collections(id, vec![Field::new("x", value)], vec![])
+    }
+
+    fn aggregate(aggregate: Aggregate, ents: &[UntypedEnt]) -> Value {
+        let mut aggregator = Aggregator::new(aggregate);
+        for ent in ents {
+            aggregator.push(ent);
+        }
+        aggregator.finish()
+    }
+
+    #[test]
+    fn aggregator_should_count_all_ents() {
+        let ents = vec![ent_with_value(1, 1), UntypedEnt::empty_with_id(2)];
+        assert_eq!(aggregate(Aggregate::count(), &ents), Value::from(2usize));
+        assert_eq!(aggregate(Aggregate::count(), &[]), Value::from(0usize));
+    }
+
+    #[test]
+    fn aggregator_should_skip_ents_missing_field_or_with_non_numeric_value() {
+        let ents = vec![
+            ent_with_value(1, 5),
+            ent_with_value(2, "text"),
+            ent_with_value(3, Some(7)),
+            ent_with_value(4, None::<u8>),
+            UntypedEnt::empty_with_id(5),
+        ];
+        assert_eq!(aggregate(Aggregate::sum("x"), &ents), Value::from(12u128));
+        assert_eq!(aggregate(Aggregate::min("x"), &ents), Value::from(5));
+        assert_eq!(aggregate(Aggregate::max("x"), &ents), Value::from(7));
+        assert_eq!(aggregate(Aggregate::average("x"), &ents), Value::from(6.0));
+    }
+
+    #[test]
+    fn aggregator_should_produce_none_if_no_numeric_values() {
+        let ents = vec![ent_with_value(1, "text"), UntypedEnt::empty_with_id(2)];
+        for a in &[
+            Aggregate::sum("x"),
+            Aggregate::min("x"),
+            Aggregate::max("x"),
+            Aggregate::average("x"),
+        ] {
+            assert_eq!(aggregate(a.clone(), &ents), Value::Optional(None));
+        }
+    }
+
+    fn aggregate_number(a: Aggregate, ents: &[UntypedEnt]) -> Number {
+        let value = aggregate(a, ents);
+        match value.to_number() {
+            Some(x) => x,
+            None => panic!("Unexpected value: {:?}", value),
+        }
+    }
+
+    #[test]
+    fn aggregator_should_widen_sum_as_needed() {
+        let ents = vec![ent_with_value(1, 5u8), ent_with_value(2, 7u32)];
+        match aggregate_number(Aggregate::sum("x"), &ents) {
+            Number::U128(x) => assert_eq!(x, 12),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+
+        let ents = vec![ent_with_value(1, 5u8), ent_with_value(2, -7i32)];
+        match aggregate_number(Aggregate::sum("x"), &ents) {
+            Number::I128(x) => assert_eq!(x, -2),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+
+        let ents = vec![ent_with_value(1, 5u8), ent_with_value(2, 0.5f32)];
+        match aggregate_number(Aggregate::sum("x"), &ents) {
+            Number::F64(x) => assert!((x - 5.5).abs() < f64::EPSILON),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+
+        let ents = vec![ent_with_value(1, u128::MAX), ent_with_value(2, 1u8)];
+        match aggregate_number(Aggregate::sum("x"), &ents) {
+            Number::F64(x) => assert!(x >= u128::MAX as f64),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn aggregator_should_average_as_float() {
+        let ents = vec![ent_with_value(1, 1), ent_with_value(2, 2)];
+        match aggregate_number(Aggregate::average("x"), &ents) {
+            Number::F64(x) => assert!((x - 1.5).abs() < f64::EPSILON),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn aggregator_should_compare_numbers_of_different_types() {
+        let ents = vec![
+            ent_with_value(1, 2.5f64),
+            ent_with_value(2, -3i64),
+            ent_with_value(3, 2u8),
+            ent_with_value(4, 2.25f32),
+        ];
+        match aggregate_number(Aggregate::min("x"), &ents) {
+            Number::I64(x) => assert_eq!(x, -3),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+        match aggregate_number(Aggregate::max("x"), &ents) {
+            Number::F64(x) => assert!((x - 2.5).abs() < f64::EPSILON),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn aggregator_should_compare_fractions_of_floats() {
+        let float = |a: Aggregate, values: &[f64]| {
+            let ents: Vec<UntypedEnt> = values
+                .iter()
+                .enumerate()
+                .map(|(id, x)| ent_with_value(id, *x))
+                .collect();
+            match aggregate_number(a, &ents) {
+                Number::F64(x) => x,
+                x => panic!("Unexpected number: {:?}", x),
+            }
+        };
+
+        assert!((float(Aggregate::min("x"), &[1.5, 1.2]) - 1.2).abs() < f64::EPSILON);
+        assert!((float(Aggregate::max("x"), &[1.2, 1.5]) - 1.5).abs() < f64::EPSILON);
+        assert!((float(Aggregate::min("x"), &[0.9, 0.2]) - 0.2).abs() < f64::EPSILON);
+        assert!((float(Aggregate::max("x"), &[0.2, 0.9]) - 0.9).abs() < f64::EPSILON);
+        assert!((float(Aggregate::min("x"), &[-0.25, -0.5]) + 0.5).abs() < f64::EPSILON);
+        assert!((float(Aggregate::max("x"), &[-0.5, -0.25]) + 0.25).abs() < f64::EPSILON);
+
+        // NaN cannot be ordered, so it is skipped
+        assert!((float(Aggregate::min("x"), &[f64::NAN, 0.5]) - 0.5).abs() < f64::EPSILON);
+        assert!((float(Aggregate::max("x"), &[0.5, f64::NAN]) - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn aggregator_should_compare_integers_exactly() {
+        let ents = vec![
+            ent_with_value(1, u128::MAX - 1),
+            ent_with_value(2, u128::MAX),
+            ent_with_value(3, i128::MIN + 1),
+            ent_with_value(4, i128::MIN),
+        ];
+        match aggregate_number(Aggregate::max("x"), &ents) {
+            Number::U128(x) => assert_eq!(x, u128::MAX),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+        match aggregate_number(Aggregate::min("x"), &ents) {
+            Number::I128(x) => assert_eq!(x, i128::MIN),
+            x => panic!("Unexpected number: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn grouped_aggregator_should_aggregate_each_group_separately() {
+        let mut aggregator =
+            GroupedAggregator::new(Aggregate::sum("x"), GroupBy::Field(String::from("g")));
+        for &(id, g, x) in &[(1, 2, 10), (2, 1, 20), (3, 2, 30)] {
+            aggregator.push(&UntypedEnt::from_collections(
+                id,
+                vec![Field::new("g", g), Field::new("x", x)],
+                vec![],
+            ));
+        }
+        aggregator.push(&ent_with_value(4, 40));
+        aggregator.push(&UntypedEnt::from_collections(
+            5,
+            vec![Field::new("g", vec![1, 2]), Field::new("x", 50)],
+            vec![],
+        ));
+
+        assert_eq!(
+            aggregator.finish(),
+            vec![
+                (Value::Optional(None), Value::from(40u128)),
+                (Value::from(1), Value::from(20u128)),
+                (Value::from(2), Value::from(40u128)),
+                (Value::from(vec![1, 2]), Value::from(50u128)),
+            ]
+        );
+    }
+}
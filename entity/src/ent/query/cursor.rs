@@ -0,0 +1,113 @@
+use crate::{Ent, Id, IndexKey, Order};
+use std::{cmp, fmt, str::FromStr};
+
+/// Represents an opaque position within the ordered results of a query,
+/// used to resume finding ents after the last ent previously returned
+///
+/// A cursor is only meaningful for queries using the same ordering clauses
+/// as the query that produced it. It can be converted to and from a string
+/// so it can be persisted and used to continue at a later time.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Cursor, OrderDirection, Query, UntypedEnt};
+///
+/// let query = Query::default().order_by_created(OrderDirection::Descending);
+/// let cursor = query.cursor_for(&UntypedEnt::empty_with_id(999));
+///
+/// let text = cursor.to_string();
+/// assert_eq!(text.parse::<Cursor>(), Ok(cursor.clone()));
+///
+/// // Continue after the ent in a later query
+/// let query = query.after(cursor);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cursor {
+    keys: Vec<Option<IndexKey>>,
+    id: Id,
+}
+
+impl Cursor {
+    /// Creates a cursor positioned at the given ent using the ordering
+    /// clauses of some query
+    pub fn from_ent(orders: &[Order], ent: &dyn Ent) -> Self {
+        Self {
+            keys: orders.iter().map(|o| o.key(ent)).collect(),
+            id: ent.id(),
+        }
+    }
+
+    /// Returns the id of the ent at the cursor's position
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns true if the cursor can be compared using the given ordering
+    /// clauses, meaning that it was produced using the same total clauses
+    pub fn is_compatible_with(&self, orders: &[Order]) -> bool {
+        self.keys.len() == orders.len()
+    }
+
+    /// Compares the positions of two cursors using the given ordering
+    /// clauses, falling back to the ids of the cursors to break ties
+    pub fn compare(&self, other: &Self, orders: &[Order]) -> cmp::Ordering {
+        orders
+            .iter()
+            .zip(self.keys.iter().zip(other.keys.iter()))
+            .map(|(o, (a, b))| o.compare_keys(a.as_ref(), b.as_ref()))
+            .find(|x| *x != cmp::Ordering::Equal)
+            .unwrap_or_else(|| self.id.cmp(&other.id))
+    }
+}
+
+/// Displays the cursor as text that can be parsed back into a cursor
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id)?;
+        for key in self.keys.iter() {
+            match key {
+                Some(key) => write!(f, ".{}", key)?,
+                None => write!(f, ".-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ParseCursorError;
+
+    /// Parses a cursor from text produced by displaying a cursor
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let id = parts
+            .next()
+            .and_then(|x| x.parse::<Id>().ok())
+            .ok_or(ParseCursorError)?;
+        let keys = parts
+            .map(|x| match x {
+                "-" => Ok(None),
+                x => x
+                    .parse::<IndexKey>()
+                    .map(Some)
+                    .map_err(|_| ParseCursorError),
+            })
+            .collect::<Result<Vec<Option<IndexKey>>, ParseCursorError>>()?;
+
+        Ok(Self { keys, id })
+    }
+}
+
+/// Represents an error that occurs when parsing text that is not a cursor
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseCursorError;
+
+impl fmt::Display for ParseCursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid cursor")
+    }
+}
+
+impl std::error::Error for ParseCursorError {}
@@ -2,7 +2,8 @@ use crate::{Id, Predicate, TypedPredicate};
 
 /// Represents some filter to apply against an ent when searching through
 /// a database
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub enum Filter {
     /// Filters by the ent's id
     Id(TypedPredicate<Id>),
@@ -16,15 +17,61 @@ pub enum Filter {
     /// Filters by the ent's last updated timestamp
     LastUpdated(TypedPredicate<u64>),
 
-    /// Filters by an ent's field
+    /// Filters by an ent's field, where the name can also be a path to a
+    /// value nested within the field such as `address.city` or `tags[0]`
+    /// as described by [`FieldPath`](crate::FieldPath)
     Field(String, Predicate),
 
     /// Filters by an ent connected by an edge; not the same as
     /// [`Filter::IntoEdge`], which converts an ent to its edge's ents
     Edge(String, Box<Filter>),
 
+    /// Filters by the total ids on an ent's edge, which never matches an ent
+    /// without the edge
+    EdgeCount(String, TypedPredicate<usize>),
+
+    /// Filters by ents whose field contains at least one of the words of the
+    /// search text, ignoring case and punctuation as described by
+    /// [`search_tokens`](crate::search_tokens)
+    ///
+    /// Results can be ranked by how well they match using
+    /// [`OrderBy::Relevance`](crate::OrderBy::Relevance).
+    Search(String, String),
+
     /// **(Special case)** Filters by converting an ent into the ents on its edge
+    ///
+    /// Because this transforms ents rather than checking them, it never
+    /// matches an ent when nested within another filter.
     IntoEdge(String),
+
+    /// Filters by ents that can reach an ent passing the filter by following
+    /// the edge of the same name from ent to ent, up to the maximum number
+    /// of hops or transitively if there is no maximum
+    ///
+    /// Each ent along the way is visited at most once, so cycles formed by
+    /// the edge are followed only as far as needed. With a maximum of one
+    /// hop, this is the same as [`Filter::Edge`].
+    EdgeRecursive(String, Option<usize>, Box<Filter>),
+
+    /// **(Special case)** Filters by converting an ent into the ents reached
+    /// by following the edge of the same name from ent to ent, up to the
+    /// maximum number of hops or transitively if there is no maximum
+    ///
+    /// An ent is only included if it is reached by at least one hop, so an
+    /// ent is not among its own descendants unless the edge forms a cycle
+    /// back to it. With a maximum of one hop, this is the same as
+    /// [`Filter::IntoEdge`]. Like [`Filter::IntoEdge`], this never matches
+    /// an ent when nested within another filter.
+    IntoEdgeRecursive(String, Option<usize>),
+
+    /// Filters by ents that pass all of the filters
+    And(Vec<Filter>),
+
+    /// Filters by ents that pass any of the filters
+    Or(Vec<Filter>),
+
+    /// Filters by ents that do not pass the filter
+    Not(Box<Filter>),
 }
 
 impl Filter {
@@ -55,4 +102,206 @@ impl Filter {
     pub fn where_into_edge<S: Into<String>>(name: S) -> Self {
         Self::IntoEdge(name.into())
     }
+
+    pub fn where_edge_count<S: Into<String>, P: Into<TypedPredicate<usize>>>(
+        name: S,
+        p: P,
+    ) -> Self {
+        Self::EdgeCount(name.into(), p.into())
+    }
+
+    pub fn where_search<S: Into<String>, T: Into<String>>(name: S, text: T) -> Self {
+        Self::Search(name.into(), text.into())
+    }
+
+    pub fn where_edge_recursive<S: Into<String>, F: Into<Filter>>(
+        name: S,
+        max_depth: Option<usize>,
+        filter: F,
+    ) -> Self {
+        Self::EdgeRecursive(name.into(), max_depth, Box::new(filter.into()))
+    }
+
+    pub fn where_into_edge_recursive<S: Into<String>>(name: S, max_depth: Option<usize>) -> Self {
+        Self::IntoEdgeRecursive(name.into(), max_depth)
+    }
+
+    /// Creates a new filter for [`Filter::And`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Filter, Predicate as P, TypedPredicate as TP};
+    ///
+    /// let f = Filter::and(vec![
+    ///     Filter::where_type(TP::equals(String::from("comment"))),
+    ///     Filter::where_field("score", P::greater_than(10)),
+    /// ]);
+    /// ```
+    pub fn and<F: Into<Filter>, I: IntoIterator<Item = F>>(i: I) -> Self {
+        Self::And(i.into_iter().map(|f| f.into()).collect())
+    }
+
+    /// Creates a new filter for [`Filter::Or`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Filter, Predicate as P, TypedPredicate as TP};
+    ///
+    /// let f = Filter::or(vec![
+    ///     Filter::where_field("a", P::equals(1)),
+    ///     Filter::where_edge("owner", Filter::where_id(TP::equals(3))),
+    /// ]);
+    /// ```
+    pub fn or<F: Into<Filter>, I: IntoIterator<Item = F>>(i: I) -> Self {
+        Self::Or(i.into_iter().map(|f| f.into()).collect())
+    }
+
+    /// Creates a new filter for [`Filter::Not`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Filter, TypedPredicate as TP};
+    ///
+    /// let f = Filter::not(Filter::where_type(TP::equals(String::from("post"))));
+    /// ```
+    pub fn not<F: Into<Filter>>(f: F) -> Self {
+        Self::Not(Box::new(f.into()))
+    }
+}
+
+impl std::ops::BitAnd for Filter {
+    type Output = Self;
+
+    /// Shorthand to produce [`Filter::And`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Filter, TypedPredicate as P};
+    ///
+    /// assert_eq!(
+    ///     Filter::where_id(P::equals(1)) & Filter::where_created(P::equals(2)),
+    ///     Filter::And(vec![
+    ///         Filter::where_id(P::equals(1)),
+    ///         Filter::where_created(P::equals(2)),
+    ///     ]),
+    /// );
+    /// ```
+    ///
+    /// If either side is already a [`Filter::And`], the returned filter will
+    /// be an updated instance:
+    ///
+    /// ```
+    /// use entity::{Filter, TypedPredicate as P};
+    ///
+    /// assert_eq!(
+    ///     Filter::And(vec![
+    ///         Filter::where_id(P::equals(1)),
+    ///         Filter::where_id(P::equals(2)),
+    ///     ]) & Filter::where_id(P::equals(3)),
+    ///     Filter::And(vec![
+    ///         Filter::where_id(P::equals(1)),
+    ///         Filter::where_id(P::equals(2)),
+    ///         Filter::where_id(P::equals(3)),
+    ///     ]),
+    /// );
+    /// ```
+    fn bitand(self, rhs: Self) -> Self {
+        let inner = match (self, rhs) {
+            (Self::And(mut list1), Self::And(mut list2)) => {
+                list1.append(&mut list2);
+                list1
+            }
+            (x, Self::And(mut list)) => {
+                list.insert(0, x);
+                list
+            }
+            (Self::And(mut list), x) => {
+                list.push(x);
+                list
+            }
+            (x1, x2) => vec![x1, x2],
+        };
+        Self::And(inner)
+    }
+}
+
+impl std::ops::BitOr for Filter {
+    type Output = Self;
+
+    /// Shorthand to produce [`Filter::Or`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Filter, TypedPredicate as P};
+    ///
+    /// assert_eq!(
+    ///     Filter::where_id(P::equals(1)) | Filter::where_created(P::equals(2)),
+    ///     Filter::Or(vec![
+    ///         Filter::where_id(P::equals(1)),
+    ///         Filter::where_created(P::equals(2)),
+    ///     ]),
+    /// );
+    /// ```
+    ///
+    /// If either side is already a [`Filter::Or`], the returned filter will
+    /// be an updated instance:
+    ///
+    /// ```
+    /// use entity::{Filter, TypedPredicate as P};
+    ///
+    /// assert_eq!(
+    ///     Filter::where_id(P::equals(1)) | Filter::Or(vec![
+    ///         Filter::where_id(P::equals(2)),
+    ///         Filter::where_id(P::equals(3)),
+    ///     ]),
+    ///     Filter::Or(vec![
+    ///         Filter::where_id(P::equals(1)),
+    ///         Filter::where_id(P::equals(2)),
+    ///         Filter::where_id(P::equals(3)),
+    ///     ]),
+    /// );
+    /// ```
+    fn bitor(self, rhs: Self) -> Self {
+        let inner = match (self, rhs) {
+            (Self::Or(mut list1), Self::Or(mut list2)) => {
+                list1.append(&mut list2);
+                list1
+            }
+            (x, Self::Or(mut list)) => {
+                list.insert(0, x);
+                list
+            }
+            (Self::Or(mut list), x) => {
+                list.push(x);
+                list
+            }
+            (x1, x2) => vec![x1, x2],
+        };
+        Self::Or(inner)
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Self;
+
+    /// Shorthand to produce [`Filter::Not`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Filter, TypedPredicate as P};
+    ///
+    /// assert_eq!(
+    ///     !Filter::where_id(P::equals(1)),
+    ///     Filter::Not(Box::new(Filter::where_id(P::equals(1)))),
+    /// );
+    /// ```
+    fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
 }
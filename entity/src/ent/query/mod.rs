@@ -1,25 +1,274 @@
-use crate::Id;
-use derive_more::{Constructor, IntoIterator};
-use std::fmt::Debug;
+use crate::{Ent, Id, Value};
+use std::{cmp, fmt::Debug};
+
+mod aggregate;
+pub use aggregate::*;
+
+mod cursor;
+pub use cursor::*;
 
 mod filter;
 pub use filter::*;
 
+mod order;
+pub use order::*;
+
+mod path;
+pub use path::*;
+
 mod predicate;
 pub use predicate::*;
 
-/// Represents a generic query to find ents within some database
-#[derive(Constructor, IntoIterator, Clone, Debug, Default)]
-pub struct Query(Vec<Filter>);
+mod search;
+pub use search::*;
+
+mod simplify;
+
+mod text;
+pub use text::*;
+
+// Queries are commonly built on one thread and executed on another (or held
+// across an await), so fail to compile if any part of a query stops being
+// thread-safe
+const _: fn() = || {
+    fn assert_thread_safe<T: Send + Sync>() {}
+    assert_thread_safe::<Query>();
+    assert_thread_safe::<Filter>();
+    assert_thread_safe::<Predicate>();
+    assert_thread_safe::<TypedPredicate<Value>>();
+    assert_thread_safe::<MapTypedPredicate<Value, std::collections::HashMap<String, Value>>>();
+    assert_thread_safe::<Order>();
+    assert_thread_safe::<Cursor>();
+    assert_thread_safe::<Aggregate>();
+    assert_thread_safe::<GroupBy>();
+};
+
+/// Represents a generic query to find ents within some database, comprised
+/// of filters to apply in order, followed by ordering clauses and an
+/// optional window (offset and limit) applied to the results
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{OrderDirection, Query, TypedPredicate as P};
+///
+/// // The 20 most recently created ents after skipping the first 40
+/// let query = Query::default()
+///     .where_created(P::greater_than(0))
+///     .order_by_created(OrderDirection::Descending)
+///     .offset(40)
+///     .limit(20);
+///
+/// assert_eq!(query.filters().len(), 1);
+/// assert_eq!(query.orders().len(), 1);
+/// assert_eq!(query.results_offset(), 40);
+/// assert_eq!(query.results_limit(), Some(20));
+/// ```
+///
+/// ### Text
+///
+/// Queries can also be written as text and parsed, and any query without a
+/// lambda predicate can be displayed as text that parses back into it.
+///
+/// Filters are separated by `and` and applied in order, where `or` and
+/// parentheses combine several filters into one and `not` negates a filter.
+/// A filter is one of `id`, `type`, `created`, `last_updated`,
+/// `field(name)`, or `edge_count(name)` followed by a predicate,
+/// `edge(name).` or `edge_recursive(name[, max_depth]).` followed by a
+/// filter, `search(name) "text"`, `into_edge(name)`, or
+/// `into_edge_recursive(name[, max_depth])`. Filters can be followed by
+/// `order by ... asc|desc`, where `relevance(name, "text")` orders by how
+/// well a field matches search text, `after "cursor"`, `offset n`, and
+/// `limit n`.
+///
+/// Predicates include comparisons (`=`, `!=`, `>`, `>=`, `<`, `<=`), ranges
+/// and sets (`in 1..=5`, `not_in (1, 2)`), collection checks (`contains`,
+/// `contains_all (..)`, `has_key "k" where ..`), text checks
+/// (`starts_with`, `ends_with_any (..)`, `equals_case_insensitive`, ...),
+/// and combinators (`and(..)`, `or(..)`, `xor(..)`, `not(..)`, `any(..)`).
+///
+/// Values are written like Rust literals, along with `none`, `some(..)`,
+/// `unit`, lists `[..]`, and maps `{"key": ..}`. Integers without a suffix
+/// are `usize` for ids and edge counts, `u64` for timestamps, and `i64` for
+/// fields.
+///
+/// ```
+/// use entity::{Filter, Predicate, Query};
+///
+/// let query: Query = "type = \"app::User\" and field(age) > 30 \
+///     and edge(friends).field(name) starts_with \"A\" \
+///     order by field(age) desc limit 10"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(query.filters()[1], Filter::where_field("age", Predicate::greater_than(30i64)));
+/// assert_eq!(query.results_limit(), Some(10));
+///
+/// // Displaying the query produces text in the same syntax
+/// assert_eq!(
+///     query.to_string(),
+///     "type = \"app::User\" and field(age) > 30 and \
+///     edge(friends).field(name) starts_with \"A\" \
+///     order by field(age) desc limit 10",
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Query {
+    filters: Vec<Filter>,
+    orders: Vec<Order>,
+    after: Option<Cursor>,
+    offset: usize,
+    limit: Option<usize>,
+}
 
 impl Query {
+    /// Creates a new query using the given filters with no ordering clauses
+    /// and no limit on the results
+    pub fn new(filters: Vec<Filter>) -> Self {
+        Self {
+            filters,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the filters of the query in the order they are applied
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Returns the ordering clauses of the query, where later clauses are
+    /// only used when earlier clauses consider two ents equal
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    /// Returns the position after which results are returned, if resuming
+    /// from an earlier query
+    pub fn results_after(&self) -> Option<&Cursor> {
+        self.after.as_ref()
+    }
+
+    /// Returns the total results to skip before returning any
+    pub fn results_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the maximum results to return, if limited
+    pub fn results_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Returns true if the query orders its results by anything other than
+    /// the ids of the ents
+    pub fn has_non_id_order(&self) -> bool {
+        self.orders.iter().any(|o| o.by() != &OrderBy::Id)
+    }
+
     /// Consumes query, producing a new query with the additional filter
     /// added to the end of the filters to be applied
-    pub fn chain(self, filter: Filter) -> Self {
-        let mut filters = self.0;
-        filters.push(filter);
+    pub fn chain(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Consumes query, producing a new query with the additional ordering
+    /// clause added to the end of the ordering clauses
+    pub fn order_by<O: Into<Order>>(mut self, order: O) -> Self {
+        self.orders.push(order.into());
+        self
+    }
+
+    pub fn order_by_id(self, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::Id, direction))
+    }
+
+    pub fn order_by_created(self, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::Created, direction))
+    }
+
+    pub fn order_by_last_updated(self, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::LastUpdated, direction))
+    }
+
+    pub fn order_by_field<S: Into<String>>(self, name: S, direction: OrderDirection) -> Self {
+        self.order_by(Order::new(OrderBy::Field(name.into()), direction))
+    }
+
+    pub fn order_by_relevance<S: Into<String>, T: Into<String>>(
+        self,
+        name: S,
+        text: T,
+        direction: OrderDirection,
+    ) -> Self {
+        self.order_by(Order::new(
+            OrderBy::Relevance(name.into(), text.into()),
+            direction,
+        ))
+    }
+
+    /// Consumes query, producing a new query that only returns results
+    /// positioned after the cursor, which is applied before the offset
+    pub fn after(mut self, cursor: Cursor) -> Self {
+        self.after = Some(cursor);
+        self
+    }
+
+    /// Consumes query, producing a new query that skips the given total
+    /// results before returning any
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Consumes query, producing a new query that returns no more than the
+    /// given total results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Consumes query, producing a new query that returns no more than the
+    /// first result, keeping any stricter limit already in place
+    pub(crate) fn first_only(self) -> Self {
+        let limit = self.limit.map_or(1, |limit| cmp::min(limit, 1));
+        self.limit(limit)
+    }
+
+    /// Produces a cursor positioned at the given ent using the ordering
+    /// clauses of the query
+    pub fn cursor_for(&self, ent: &dyn Ent) -> Cursor {
+        Cursor::from_ent(&self.orders, ent)
+    }
+
+    /// Orders the given ents using the ordering clauses of the query,
+    /// falling back to the ids of the ents to break ties such that the
+    /// order is always deterministic, and then removes any ents that are
+    /// not positioned after the cursor of the query
+    pub fn sort_ents<E: AsRef<dyn Ent>>(&self, ents: Vec<E>) -> Vec<E> {
+        let mut ents: Vec<(Cursor, E)> = ents
+            .into_iter()
+            .map(|ent| (self.cursor_for(ent.as_ref()), ent))
+            .collect();
+        ents.sort_by(|a, b| a.0.compare(&b.0, &self.orders));
+
+        ents.into_iter()
+            .filter(|(cursor, _)| match self.after.as_ref() {
+                Some(after) => cursor.compare(after, &self.orders) == std::cmp::Ordering::Greater,
+                None => true,
+            })
+            .map(|(_, ent)| ent)
+            .collect()
+    }
 
-        Query::new(filters)
+    /// Applies the offset and limit of the query to the given results, which
+    /// are assumed to already be ordered
+    pub fn window<T, I: IntoIterator<Item = T>>(&self, items: I) -> Vec<T> {
+        let items = items.into_iter().skip(self.offset);
+        match self.limit {
+            Some(limit) => items.take(limit).collect(),
+            None => items.collect(),
+        }
     }
 
     pub fn where_id<P: Into<TypedPredicate<Id>>>(self, p: P) -> Self {
@@ -49,4 +298,132 @@ impl Query {
     pub fn where_into_edge<S: Into<String>>(self, name: S) -> Self {
         self.chain(Filter::where_into_edge(name))
     }
+
+    pub fn where_edge_count<S: Into<String>, P: Into<TypedPredicate<usize>>>(
+        self,
+        name: S,
+        p: P,
+    ) -> Self {
+        self.chain(Filter::where_edge_count(name, p))
+    }
+
+    pub fn where_search<S: Into<String>, T: Into<String>>(self, name: S, text: T) -> Self {
+        self.chain(Filter::where_search(name, text))
+    }
+
+    /// Consumes query, producing a new query that only returns ents whose
+    /// field matches the search text as described by [`Filter::Search`],
+    /// ordered from most to least relevant
+    pub fn where_matches<S: Into<String>, T: Into<String>>(self, name: S, text: T) -> Self {
+        let name = name.into();
+        let text = text.into();
+        self.where_search(name.clone(), text.clone())
+            .order_by_relevance(name, text, OrderDirection::Descending)
+    }
+
+    pub fn where_edge_recursive<S: Into<String>, F: Into<Filter>>(
+        self,
+        name: S,
+        max_depth: Option<usize>,
+        filter: F,
+    ) -> Self {
+        self.chain(Filter::where_edge_recursive(name, max_depth, filter))
+    }
+
+    pub fn where_into_edge_recursive<S: Into<String>>(
+        self,
+        name: S,
+        max_depth: Option<usize>,
+    ) -> Self {
+        self.chain(Filter::where_into_edge_recursive(name, max_depth))
+    }
+
+    pub fn where_and<F: Into<Filter>, I: IntoIterator<Item = F>>(self, i: I) -> Self {
+        self.chain(Filter::and(i))
+    }
+
+    pub fn where_or<F: Into<Filter>, I: IntoIterator<Item = F>>(self, i: I) -> Self {
+        self.chain(Filter::or(i))
+    }
+
+    pub fn where_not<F: Into<Filter>>(self, f: F) -> Self {
+        self.chain(Filter::not(f))
+    }
+}
+
+impl IntoIterator for Query {
+    type Item = Filter;
+    type IntoIter = std::vec::IntoIter<Filter>;
+
+    /// Consumes the query, producing an iterator over its filters
+    fn into_iter(self) -> Self::IntoIter {
+        self.filters.into_iter()
+    }
+}
+
+#[cfg(all(test, feature = "serde-1"))]
+mod tests {
+    use super::*;
+    use crate::{Field, UntypedEnt};
+
+    #[test]
+    fn query_should_round_trip_through_serde() {
+        let query = Query::default()
+            .where_id(TypedPredicate::greater_than(3))
+            .where_type(TypedPredicate::equals(String::from("type")))
+            .where_created(TypedPredicate::less_than(100))
+            .where_last_updated(TypedPredicate::in_range(5..=10))
+            .where_field("a", Predicate::text_starts_with("abc"))
+            .where_edge("b", Filter::where_field("c", Predicate::IsNone))
+            .where_into_edge("d")
+            .where_edge_count("d", TypedPredicate::greater_than(2))
+            .where_search("g", "some words")
+            .where_edge_recursive("e", Some(2), Filter::where_id(TypedPredicate::equals(4)))
+            .where_into_edge_recursive("f", None)
+            .where_and(vec![
+                Filter::where_id(TypedPredicate::equals(1)),
+                Filter::where_id(TypedPredicate::equals(2)),
+            ])
+            .where_or(vec![Filter::where_type(TypedPredicate::equals(
+                String::from("other"),
+            ))])
+            .where_not(Filter::where_id(TypedPredicate::equals(999)));
+
+        let orders = vec![
+            Order::new(OrderBy::Field(String::from("a")), OrderDirection::Ascending),
+            Order::new(OrderBy::Id, OrderDirection::Descending),
+        ];
+        let cursor = Cursor::from_ent(
+            &orders,
+            &UntypedEnt::from_collections(5, vec![Field::new("a", 3u8)], vec![]),
+        );
+        let query = orders
+            .into_iter()
+            .fold(query, Query::order_by)
+            .after(cursor)
+            .offset(3)
+            .limit(7);
+
+        let json = serde_json::to_string(&query).unwrap();
+        let q: Query = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(q.filters(), query.filters());
+        assert_eq!(q.orders(), query.orders());
+        assert_eq!(q.results_after(), query.results_after());
+        assert_eq!(q.results_offset(), 3);
+        assert_eq!(q.results_limit(), Some(7));
+    }
+
+    #[test]
+    fn query_should_fail_to_serialize_if_it_contains_a_lambda() {
+        let query =
+            Query::default().where_not(Filter::where_field("a", Predicate::lambda(|_| true)));
+        let err = serde_json::to_string(&query).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Predicate::Lambda cannot be serialized"),
+            "Unexpected error: {}",
+            err
+        );
+    }
 }
@@ -0,0 +1,150 @@
+use crate::{Ent, FieldPath, IndexKey, Relevance, Value};
+use std::cmp;
+
+/// Represents the direction in which ents are ordered
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrderDirection {
+    /// Orders from smallest to largest
+    Ascending,
+
+    /// Orders from largest to smallest
+    Descending,
+}
+
+/// Represents the property of an ent used to order ents
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrderBy {
+    /// Orders by the ent's id
+    Id,
+
+    /// Orders by the ent's creation timestamp
+    Created,
+
+    /// Orders by the ent's last updated timestamp
+    LastUpdated,
+
+    /// Orders by the value of an ent's field
+    Field(String),
+
+    /// Orders by the [`Relevance`] of an ent's field to the search text,
+    /// where more relevant ents are larger
+    Relevance(String, String),
+}
+
+/// Represents a single ordering clause of a query, comprised of the property
+/// to order by and the direction in which to order it
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Field, Order, OrderBy, UntypedEnt};
+/// use std::cmp::Ordering;
+///
+/// let a = UntypedEnt::from_collections(1, vec![Field::new("age", 30)], vec![]);
+/// let b = UntypedEnt::from_collections(2, vec![Field::new("age", 20)], vec![]);
+/// let c = UntypedEnt::from_collections(3, vec![], vec![]);
+///
+/// let order = Order::ascending(OrderBy::Field(String::from("age")));
+/// assert_eq!(order.compare(&a, &b), Ordering::Greater);
+///
+/// // Ents missing the field are placed last regardless of direction
+/// assert_eq!(order.compare(&a, &c), Ordering::Less);
+/// assert_eq!(order.reversed().compare(&a, &c), Ordering::Less);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order {
+    by: OrderBy,
+    direction: OrderDirection,
+}
+
+impl Order {
+    /// Creates a new ordering clause using the given property and direction
+    pub fn new(by: OrderBy, direction: OrderDirection) -> Self {
+        Self { by, direction }
+    }
+
+    /// Creates a new ordering clause from smallest to largest
+    pub fn ascending(by: OrderBy) -> Self {
+        Self::new(by, OrderDirection::Ascending)
+    }
+
+    /// Creates a new ordering clause from largest to smallest
+    pub fn descending(by: OrderBy) -> Self {
+        Self::new(by, OrderDirection::Descending)
+    }
+
+    /// Returns the property used by the ordering clause
+    pub fn by(&self) -> &OrderBy {
+        &self.by
+    }
+
+    /// Returns the direction used by the ordering clause
+    pub fn direction(&self) -> OrderDirection {
+        self.direction
+    }
+
+    /// Produces a new ordering clause using the same property in the
+    /// opposite direction
+    pub fn reversed(&self) -> Self {
+        let direction = match self.direction {
+            OrderDirection::Ascending => OrderDirection::Descending,
+            OrderDirection::Descending => OrderDirection::Ascending,
+        };
+        Self::new(self.by.clone(), direction)
+    }
+
+    /// Produces the key used to order the ent, returning none if the ent
+    /// is missing the field being ordered or its value cannot be ordered
+    /// (such as lists and maps)
+    pub fn key(&self, ent: &dyn Ent) -> Option<IndexKey> {
+        match &self.by {
+            OrderBy::Id => IndexKey::from_value(&Value::from(ent.id())),
+            OrderBy::Created => IndexKey::from_value(&Value::from(ent.created())),
+            OrderBy::LastUpdated => IndexKey::from_value(&Value::from(ent.last_updated())),
+            OrderBy::Field(name) => FieldPath::lookup(ent, name)
+                .as_ref()
+                .and_then(IndexKey::from_value),
+            OrderBy::Relevance(name, text) => {
+                let relevance = Relevance::of(ent, name, text);
+                let mut bytes = Vec::with_capacity(16);
+                bytes.extend(&(relevance.matched_words() as u64).to_be_bytes());
+                bytes.extend(&(relevance.occurrences() as u64).to_be_bytes());
+                Some(IndexKey::from_bytes(bytes))
+            }
+        }
+    }
+
+    /// Compares two keys produced by [`Order::key`] using the ordering
+    /// clause, where missing keys are always placed after present keys
+    pub fn compare_keys(&self, a: Option<&IndexKey>, b: Option<&IndexKey>) -> cmp::Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => match self.direction {
+                OrderDirection::Ascending => a.cmp(b),
+                OrderDirection::Descending => b.cmp(a),
+            },
+            (Some(_), None) => cmp::Ordering::Less,
+            (None, Some(_)) => cmp::Ordering::Greater,
+            (None, None) => cmp::Ordering::Equal,
+        }
+    }
+
+    /// Compares two ents using the ordering clause
+    ///
+    /// Values of fields are ordered the same way as [`Value`] orders them;
+    /// values of different types are ordered by type. Ents missing the field
+    /// or whose value cannot be ordered are always placed after ents that
+    /// have an orderable value.
+    pub fn compare(&self, a: &dyn Ent, b: &dyn Ent) -> cmp::Ordering {
+        self.compare_keys(self.key(a).as_ref(), self.key(b).as_ref())
+    }
+}
+
+impl From<OrderBy> for Order {
+    /// Converts into an ascending ordering clause
+    fn from(by: OrderBy) -> Self {
+        Self::ascending(by)
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{Ent, Value};
+use std::{convert::Infallible, fmt};
+
+/// Represents a path to a value nested within a field of an ent, starting
+/// with the name of the field and followed by any number of keys into maps
+/// (`.key`) and indexes into lists (`[0]`)
+///
+/// Optional values are looked through along the way, so `address.city`
+/// resolves against a field holding `Some(address)`, while a path through
+/// a none value, a missing key, or an index beyond the end of a list
+/// resolves to nothing.
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{FieldPath, FieldPathSegment, Value};
+/// use std::collections::HashMap;
+///
+/// let path = FieldPath::parse("address.lines[1]").unwrap();
+/// assert_eq!(path.field(), "address");
+/// assert_eq!(path.segments(), &[
+///     FieldPathSegment::Key(String::from("lines")),
+///     FieldPathSegment::Index(1),
+/// ]);
+///
+/// let mut address = HashMap::new();
+/// address.insert(String::from("lines"), Value::from(vec!["123 Main St", "Apt 4"]));
+/// let value = Value::from(Some(Value::Map(address)));
+/// assert_eq!(path.resolve(value), Some(Value::from("Apt 4")));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FieldPath {
+    field: String,
+    segments: Vec<FieldPathSegment>,
+}
+
+/// Represents a single step into a value along a [`FieldPath`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FieldPathSegment {
+    /// Looks up the value with the key within a map
+    Key(String),
+
+    /// Looks up the value at the index within a list
+    Index(usize),
+}
+
+impl FieldPath {
+    /// Parses a path such as `address.city` or `tags[0]`, returning none if
+    /// the text is not a valid path
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::FieldPath;
+    ///
+    /// assert!(FieldPath::parse("name").is_some());
+    /// assert!(FieldPath::parse("matrix[0][1].value").is_some());
+    ///
+    /// assert!(FieldPath::parse("").is_none());
+    /// assert!(FieldPath::parse("address..city").is_none());
+    /// assert!(FieldPath::parse("tags[first]").is_none());
+    /// ```
+    pub fn parse(path: &str) -> Option<Self> {
+        let end = path.find(&['.', '['][..]).unwrap_or(path.len());
+        let (field, mut rest) = path.split_at(end);
+        if field.is_empty() {
+            return None;
+        }
+
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(&['.', '['][..]).unwrap_or(after.len());
+                let (key, after) = after.split_at(end);
+                if key.is_empty() {
+                    return None;
+                }
+                segments.push(FieldPathSegment::Key(key.to_string()));
+                rest = after;
+            } else {
+                let after = rest.strip_prefix('[')?;
+                let end = after.find(']')?;
+                let index = after[..end].parse::<usize>().ok()?;
+                segments.push(FieldPathSegment::Index(index));
+                rest = &after[end + 1..];
+            }
+        }
+
+        Some(Self {
+            field: field.to_string(),
+            segments,
+        })
+    }
+
+    /// Joins the name of a field with a path relative to the field, such as
+    /// `city` or `[0]`, into a single path
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::FieldPath;
+    ///
+    /// assert_eq!(FieldPath::join("address", "city"), "address.city");
+    /// assert_eq!(FieldPath::join("tags", "[0]"), "tags[0]");
+    /// assert_eq!(FieldPath::join("tags", ""), "tags");
+    /// ```
+    pub fn join(field: &str, path: &str) -> String {
+        if path.is_empty() || path.starts_with('[') {
+            format!("{}{}", field, path)
+        } else {
+            format!("{}.{}", field, path)
+        }
+    }
+
+    /// Returns the name of the field at the start of the path
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Returns the keys and indexes followed after the field
+    pub fn segments(&self) -> &[FieldPathSegment] {
+        &self.segments
+    }
+
+    /// Returns true if the path goes beyond the field itself
+    pub fn is_nested(&self) -> bool {
+        !self.segments.is_empty()
+    }
+
+    /// Follows the segments of the path within the given value of the
+    /// path's field, returning the value at the end of the path if found
+    pub fn resolve(&self, value: Value) -> Option<Value> {
+        let mut value = value;
+        for segment in self.segments.iter() {
+            while let Value::Optional(Some(x)) = value {
+                value = *x;
+            }
+
+            value = match (segment, value) {
+                (FieldPathSegment::Key(key), Value::Map(mut map)) => map.remove(key)?,
+                (FieldPathSegment::Index(i), Value::List(mut list)) if *i < list.len() => {
+                    list.swap_remove(*i)
+                }
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Looks up the value of a field of the ent by name, where the name is
+    /// either the name of a field or a path into a field
+    ///
+    /// A field whose name matches the entire text takes priority over
+    /// treating the text as a path.
+    pub fn lookup(ent: &dyn Ent, name: &str) -> Option<Value> {
+        match Self::try_lookup_with(name, |field| Ok::<_, Infallible>(ent.field(field))) {
+            Ok(value) => value,
+            Err(x) => match x {},
+        }
+    }
+
+    /// Looks up the value of a field by name like [`FieldPath::lookup`],
+    /// using the function to retrieve the value of a field with the given
+    /// name rather than an ent, which allows fields to be retrieved from
+    /// somewhere other than an ent
+    pub fn try_lookup_with<E, F>(name: &str, mut get: F) -> Result<Option<Value>, E>
+    where
+        F: FnMut(&str) -> Result<Option<Value>, E>,
+    {
+        if let Some(value) = get(name)? {
+            return Ok(Some(value));
+        }
+
+        match Self::parse(name).filter(Self::is_nested) {
+            Some(path) => Ok(get(path.field())?.and_then(|value| path.resolve(value))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.field)?;
+        for segment in self.segments.iter() {
+            match segment {
+                FieldPathSegment::Key(key) => write!(f, ".{}", key)?,
+                FieldPathSegment::Index(i) => write!(f, "[{}]", i)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Field, UntypedEnt};
+    use std::collections::HashMap;
+
+    fn map(entries: Vec<(&str, Value)>) -> Value {
+        Value::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<HashMap<String, Value>>(),
+        )
+    }
+
+    #[test]
+    fn parse_should_split_field_keys_and_indexes() {
+        let path = FieldPath::parse("a.b[3][0].c").unwrap();
+        assert_eq!(path.field(), "a");
+        assert_eq!(
+            path.segments(),
+            &[
+                FieldPathSegment::Key(String::from("b")),
+                FieldPathSegment::Index(3),
+                FieldPathSegment::Index(0),
+                FieldPathSegment::Key(String::from("c")),
+            ]
+        );
+        assert_eq!(path.to_string(), "a.b[3][0].c");
+
+        let path = FieldPath::parse("a").unwrap();
+        assert!(!path.is_nested());
+    }
+
+    #[test]
+    fn parse_should_fail_if_path_is_malformed() {
+        for path in &[
+            "", ".a", "[0]", "a.", "a..b", "a[", "a[0", "a[-1]", "a[x]", "a[0]b",
+        ] {
+            assert_eq!(FieldPath::parse(path), None, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn resolve_should_follow_maps_lists_and_optionals() {
+        let value = map(vec![
+            (
+                "address",
+                Value::from(Some(map(vec![("city", Value::from("Springfield"))]))),
+            ),
+            ("tags", Value::from(vec!["a", "b"])),
+            ("missing", Value::Optional(None)),
+        ]);
+
+        let resolve = |path| {
+            FieldPath::parse(&format!("x.{}", path))
+                .unwrap()
+                .resolve(value.clone())
+        };
+        assert_eq!(resolve("address.city"), Some(Value::from("Springfield")));
+        assert_eq!(resolve("tags[1]"), Some(Value::from("b")));
+        assert_eq!(resolve("tags[2]"), None);
+        assert_eq!(resolve("tags.a"), None);
+        assert_eq!(resolve("address[0]"), None);
+        assert_eq!(resolve("missing"), Some(Value::Optional(None)));
+        assert_eq!(resolve("missing.city"), None);
+        assert_eq!(resolve("unknown"), None);
+    }
+
+    #[test]
+    fn lookup_should_prefer_field_with_entire_name() {
+        let ent = UntypedEnt::from_collections(
+            1,
+            vec![
+                Field::new("a.b", 1),
+                Field::new("a", map(vec![("b", Value::from(2)), ("c", Value::from(3))])),
+            ],
+            vec![],
+        );
+
+        assert_eq!(FieldPath::lookup(&ent, "a.b"), Some(Value::from(1)));
+        assert_eq!(FieldPath::lookup(&ent, "a.c"), Some(Value::from(3)));
+        assert_eq!(FieldPath::lookup(&ent, "a.d"), None);
+        assert_eq!(FieldPath::lookup(&ent, "b.c"), None);
+    }
+}
@@ -1,3 +1,4 @@
+use super::simplify;
 use crate::Value;
 use derivative::Derivative;
 use std::{
@@ -5,13 +6,14 @@ use std::{
     convert::TryFrom,
     marker::PhantomData,
     ops::RangeInclusive,
-    rc::Rc,
+    sync::Arc,
 };
 
 /// Represents an untyped predicate that can be used to inspect a value for
 /// some specified condition
 #[derive(Clone, Derivative)]
 #[derivative(Debug, PartialEq)]
+#[cfg_attr(feature = "serde-1", derive(serde::Serialize, serde::Deserialize))]
 pub enum Predicate {
     /// Will always be true (not same as equals(true))
     ///
@@ -387,20 +389,38 @@ pub enum Predicate {
     /// Will be true if checked value passes the lambda function by having
     /// it return true
     ///
+    /// The lambda function must be thread-safe so that predicates, and the
+    /// filters and queries built from them, can be sent between threads.
+    ///
     /// ### Examples
     ///
     /// ```
     /// use entity::{Predicate, Value};
-    /// use std::rc::Rc;
+    /// use std::sync::Arc;
     /// let v = Value::from(123);
     ///
-    /// let p = Predicate::Lambda(Rc::new(|v| v == &Value::from(123)));
+    /// let p = Predicate::Lambda(Arc::new(|v| v == &Value::from(123)));
     /// assert_eq!(p.check(&v), true);
     ///
-    /// let p = Predicate::Lambda(Rc::new(|v| v == &Value::from(456)));
+    /// let p = Predicate::Lambda(Arc::new(|v| v == &Value::from(456)));
     /// assert_eq!(p.check(&v), false);
-    /// ```
-    Lambda(#[derivative(Debug = "ignore", PartialEq = "ignore")] Rc<dyn Fn(&Value) -> bool>),
+    ///
+    /// // Predicates can be checked on other threads
+    /// let p = Predicate::Lambda(Arc::new(|v| v == &Value::from(123)));
+    /// let handle = std::thread::spawn(move || p.check(&Value::from(123)));
+    /// assert_eq!(handle.join().unwrap(), true);
+    /// ```
+    Lambda(
+        #[derivative(Debug = "ignore", PartialEq = "ignore")]
+        #[cfg_attr(
+            feature = "serde-1",
+            serde(
+                serialize_with = "serialize_lambda",
+                deserialize_with = "deserialize_lambda"
+            )
+        )]
+        Arc<dyn Fn(&Value) -> bool + Send + Sync>,
+    ),
 
     /// Will be true if checked value is less than the specified value
     ///
@@ -855,6 +875,24 @@ pub enum Predicate {
     /// ```
     TextNotEqualsCaseInsensitive(String),
 
+    /// Will be true if checked value is text that can be turned into the
+    /// specified string using no more than the specified total of single
+    /// character insertions, deletions, and substitutions (case sensitive)
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Predicate, Value};
+    /// let v = Value::from(String::from("kitten"));
+    ///
+    /// let p = Predicate::TextFuzzyMatches(String::from("sitting"), 3);
+    /// assert_eq!(p.check(&v), true);
+    ///
+    /// let p = Predicate::TextFuzzyMatches(String::from("sitting"), 2);
+    /// assert_eq!(p.check(&v), false);
+    /// ```
+    TextFuzzyMatches(String, usize),
+
     /// Will be true if checked value is text that is found within the
     /// specified set of strings
     ///
@@ -997,6 +1035,108 @@ pub enum Predicate {
     /// assert_eq!(p.check(&v), false);
     /// ```
     Xor(Vec<Predicate>),
+
+    /// Will be true if checked value is text where the specified regular
+    /// expression matches anywhere within it
+    ///
+    /// Two of these predicates are equal if their expressions were written
+    /// the same way.
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{vendor::regex::Regex, Predicate, Value};
+    /// let v = Value::from(String::from("order #1234"));
+    ///
+    /// let p = Predicate::TextMatchesRegex(Regex::new(r"#\d+$").unwrap());
+    /// assert_eq!(p.check(&v), true);
+    ///
+    /// let p = Predicate::TextMatchesRegex(Regex::new(r"^#\d+").unwrap());
+    /// assert_eq!(p.check(&v), false);
+    /// ```
+    // NOTE: Kept last so that enabling the feature leaves the index of every
+    //       other variant unchanged for formats that serialize by index
+    #[cfg(feature = "regex")]
+    TextMatchesRegex(
+        #[derivative(PartialEq(compare_with = "regex_eq"))]
+        #[cfg_attr(
+            feature = "serde-1",
+            serde(
+                serialize_with = "serialize_regex",
+                deserialize_with = "deserialize_regex"
+            )
+        )]
+        regex::Regex,
+    ),
+}
+
+/// Returns the total single character insertions, deletions, and
+/// substitutions needed to turn one text into another, stopping early with
+/// a total above the limit once the texts are known to be further apart
+fn edit_distance(a: &str, b: &str, limit: usize) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        if curr.iter().all(|d| *d > limit) {
+            return limit + 1;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Compares regular expressions by the text they were written with, as two
+/// compiled expressions cannot be compared directly
+#[cfg(feature = "regex")]
+fn regex_eq(a: &regex::Regex, b: &regex::Regex) -> bool {
+    a.as_str() == b.as_str()
+}
+
+/// Serializes a regular expression as the text it was written with
+#[cfg(all(feature = "regex", feature = "serde-1"))]
+fn serialize_regex<S: serde::Serializer>(
+    x: &regex::Regex,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(x.as_str())
+}
+
+/// Deserializes a regular expression from text, failing if the text is not
+/// a valid expression
+#[cfg(all(feature = "regex", feature = "serde-1"))]
+fn deserialize_regex<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<regex::Regex, D::Error> {
+    let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+    regex::Regex::new(&text).map_err(serde::de::Error::custom)
+}
+
+/// Fails to serialize [`Predicate::Lambda`] as functions cannot be serialized
+#[cfg(feature = "serde-1")]
+fn serialize_lambda<S: serde::Serializer>(
+    _: &Arc<dyn Fn(&Value) -> bool + Send + Sync>,
+    _: S,
+) -> Result<S::Ok, S::Error> {
+    Err(serde::ser::Error::custom(
+        "Predicate::Lambda cannot be serialized",
+    ))
+}
+
+/// Fails to deserialize [`Predicate::Lambda`] as functions cannot be
+/// deserialized
+#[cfg(feature = "serde-1")]
+fn deserialize_lambda<'de, D: serde::Deserializer<'de>, T>(_: D) -> Result<T, D::Error> {
+    Err(serde::de::Error::custom(
+        "Predicate::Lambda cannot be deserialized",
+    ))
 }
 
 impl Predicate {
@@ -1055,6 +1195,15 @@ impl Predicate {
                 Value::Text(t) => list.iter().any(|s| t.to_lowercase() == s.to_lowercase()),
                 _ => false,
             },
+            Self::TextFuzzyMatches(s, max) => match value {
+                Value::Text(t) => edit_distance(t, s, *max) <= *max,
+                _ => false,
+            },
+            #[cfg(feature = "regex")]
+            Self::TextMatchesRegex(x) => match value {
+                Value::Text(t) => x.is_match(t),
+                _ => false,
+            },
             Self::IsNone => matches!(value, Value::Optional(None)),
             Self::Lambda(f) => f(value),
             Self::LessThan(v) => value < v,
@@ -1140,6 +1289,42 @@ impl Predicate {
         }
     }
 
+    /// Rewrites the predicate into an equivalent predicate that is cheaper
+    /// to check, producing the same result from [`Predicate::check`] for
+    /// every value
+    ///
+    /// 1. Folds [`Predicate::Always`] and [`Predicate::Never`] into the
+    ///    predicates containing them
+    /// 2. Flattens nested [`Predicate::And`] and [`Predicate::Or`]
+    /// 3. Removes double negation and pushes [`Predicate::Not`] inward using
+    ///    De Morgan's laws, inverting predicates with an exact opposite such
+    ///    as [`Predicate::Equals`] and [`Predicate::NotEquals`]
+    /// 4. Collects [`Predicate::Equals`] within [`Predicate::Or`] into a
+    ///    single [`Predicate::InSet`]
+    /// 5. Merges ranges (including greater/less than comparisons) that
+    ///    overlap, so long as their bounds are all numbers or all text
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Predicate as P, Value as V};
+    ///
+    /// let p = !(P::Never | (P::equals(1) | P::equals(2))) & P::always();
+    /// assert_eq!(
+    ///     p.simplify(),
+    ///     P::NotInSet(vec![V::from(1), V::from(2)].into_iter().collect()),
+    /// );
+    ///
+    /// let p = P::greater_than(1) & P::less_than_or_equals(10) & P::greater_than_or_equals(3);
+    /// assert_eq!(p.simplify(), P::InRange(V::from(3)..=V::from(10)));
+    ///
+    /// let p = P::in_range(1..=5) | P::in_range(3..=8);
+    /// assert_eq!(p.simplify(), P::InRange(V::from(1)..=V::from(8)));
+    /// ```
+    pub fn simplify(self) -> Self {
+        simplify::simplify(self)
+    }
+
     /// Creates a new predicate for [`Predicate::Always`]
     ///
     /// ### Examples
@@ -1181,8 +1366,8 @@ impl Predicate {
     /// assert_eq!(p.check(&V::from(4)), true);
     /// assert_eq!(p.check(&V::from(1)), false);
     /// ```
-    pub fn lambda<F: 'static + Fn(&Value) -> bool>(f: F) -> Self {
-        Self::Lambda(Rc::new(f))
+    pub fn lambda<F: 'static + Fn(&Value) -> bool + Send + Sync>(f: F) -> Self {
+        Self::Lambda(Arc::new(f))
     }
 
     /// Creates a new predicate for [`Predicate::And`]
@@ -1629,6 +1814,22 @@ impl Predicate {
         Self::TextEqualsCaseInsensitive(s.into())
     }
 
+    /// Creates a new predicate for [`Predicate::TextFuzzyMatches`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{Predicate as P, Value as V};
+    ///
+    /// let p = P::text_fuzzy_matches("colour", 1);
+    ///
+    /// assert_eq!(p.check(&V::from("color")), true);
+    /// assert_eq!(p.check(&V::from("collar")), false);
+    /// ```
+    pub fn text_fuzzy_matches<S: Into<String>>(s: S, max_distance: usize) -> Self {
+        Self::TextFuzzyMatches(s.into(), max_distance)
+    }
+
     /// Creates a new predicate for [`Predicate::TextInSetCaseInsensitive`]
     ///
     /// ### Examples
@@ -1645,6 +1846,23 @@ impl Predicate {
         Self::TextInSetCaseInsensitive(i.into_iter().map(|s| s.into()).collect())
     }
 
+    /// Creates a new predicate for [`Predicate::TextMatchesRegex`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{vendor::regex::Regex, Predicate as P, Value as V};
+    ///
+    /// let p = P::text_matches_regex(Regex::new("^[a-z]+$").unwrap());
+    ///
+    /// assert_eq!(p.check(&V::from("text")), true);
+    /// assert_eq!(p.check(&V::from("Text")), false);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn text_matches_regex(regex: regex::Regex) -> Self {
+        Self::TextMatchesRegex(regex)
+    }
+
     /// Creates a new predicate for [`Predicate::TextNotEqualsCaseInsensitive`]
     ///
     /// ### Examples
@@ -2194,7 +2412,15 @@ impl std::ops::Not for Predicate {
 /// Represents a typed [`Predicate`], ensuring that only valid conditions are
 /// used for a given type
 #[derive(Clone, Debug, PartialEq)]
-pub struct TypedPredicate<T: Into<Value>>(Predicate, PhantomData<T>);
+#[cfg_attr(
+    feature = "serde-1",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent, bound = "")
+)]
+pub struct TypedPredicate<T: Into<Value>>(
+    Predicate,
+    #[cfg_attr(feature = "serde-1", serde(skip))] PhantomData<T>,
+);
 
 impl<T: Into<Value>> PartialEq<Predicate> for TypedPredicate<T> {
     fn eq(&self, other: &Predicate) -> bool {
@@ -2256,8 +2482,8 @@ impl<T: Into<Value> + TryFrom<Value>> TypedPredicate<T> {
     /// assert_eq!(p.check(4), true);
     /// assert_eq!(p.check(1), false);
     /// ```
-    pub fn lambda<F: 'static + Fn(T) -> bool>(f: F) -> Self {
-        Self::new(Predicate::Lambda(Rc::new(move |v| {
+    pub fn lambda<F: 'static + Fn(T) -> bool + Send + Sync>(f: F) -> Self {
+        Self::new(Predicate::Lambda(Arc::new(move |v| {
             match T::try_from(v.clone()) {
                 Ok(x) => f(x),
                 Err(_) => false,
@@ -2287,6 +2513,21 @@ impl<T: Into<Value>> TypedPredicate<T> {
         self.0.check(&value.into())
     }
 
+    /// Rewrites the typed predicate into an equivalent predicate that is
+    /// cheaper to check as described by [`Predicate::simplify`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::TypedPredicate as P;
+    ///
+    /// let p: P<u32> = !!(P::equals(1) | P::equals(1) | P::never());
+    /// assert_eq!(p.simplify(), P::equals(1));
+    /// ```
+    pub fn simplify(self) -> Self {
+        Self::new(self.0.simplify())
+    }
+
     /// Creates a new typed predicate for [`Predicate::Always`]
     ///
     /// ### Examples
@@ -2791,6 +3032,22 @@ impl TypedPredicate<String> {
         Self::new(Predicate::text_equals_case_insensitive(s))
     }
 
+    /// Creates a new typed predicate for [`Predicate::TextFuzzyMatches`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::TypedPredicate as P;
+    ///
+    /// let p = P::text_fuzzy_matches("colour", 1);
+    ///
+    /// assert_eq!(p.check(String::from("color")), true);
+    /// assert_eq!(p.check(String::from("collar")), false);
+    /// ```
+    pub fn text_fuzzy_matches<S: Into<String>>(s: S, max_distance: usize) -> Self {
+        Self::new(Predicate::text_fuzzy_matches(s, max_distance))
+    }
+
     /// Creates a new typed predicate for [`Predicate::TextInSetCaseInsensitive`]
     ///
     /// ### Examples
@@ -2807,6 +3064,23 @@ impl TypedPredicate<String> {
         Self::new(Predicate::text_in_set_case_insensitive(i))
     }
 
+    /// Creates a new typed predicate for [`Predicate::TextMatchesRegex`]
+    ///
+    /// ### Examples
+    ///
+    /// ```
+    /// use entity::{vendor::regex::Regex, TypedPredicate as P};
+    ///
+    /// let p = P::text_matches_regex(Regex::new("^[a-z]+$").unwrap());
+    ///
+    /// assert_eq!(p.check(String::from("text")), true);
+    /// assert_eq!(p.check(String::from("Text")), false);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn text_matches_regex(regex: regex::Regex) -> Self {
+        Self::new(Predicate::text_matches_regex(regex))
+    }
+
     /// Creates a new typed predicate for [`Predicate::TextNotEqualsCaseInsensitive`]
     ///
     /// ### Examples
@@ -3031,10 +3305,15 @@ impl TypedPredicate<String> {
 /// This is required due to limitations in Rust's blanket impl functionality,
 /// which will be resolved once specialization is available.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde-1",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent, bound = "")
+)]
 pub struct MapTypedPredicate<T: Into<Value>, C: IntoIterator<Item = (String, T)> + Into<Value>>(
     Predicate,
-    PhantomData<T>,
-    PhantomData<C>,
+    #[cfg_attr(feature = "serde-1", serde(skip))] PhantomData<T>,
+    #[cfg_attr(feature = "serde-1", serde(skip))] PhantomData<C>,
 );
 
 impl<T: Into<Value>, C: IntoIterator<Item = (String, T)> + Into<Value>> PartialEq<Predicate>
@@ -3141,6 +3420,12 @@ impl<T: Into<Value>, C: IntoIterator<Item = (String, T)> + Into<Value>> MapTyped
     pub fn check(&self, value: C) -> bool {
         self.0.check(&value.into())
     }
+
+    /// Rewrites the typed predicate into an equivalent predicate that is
+    /// cheaper to check as described by [`Predicate::simplify`]
+    pub fn simplify(self) -> Self {
+        Self::new(self.0.simplify())
+    }
 }
 
 impl<T: Into<Value>, C: IntoIterator<Item = (String, T)> + Into<Value>> MapTypedPredicate<T, C> {
@@ -3236,3 +3521,252 @@ impl<T: Into<Value>, C: IntoIterator<Item = (String, T)> + Into<Value>> MapTyped
         Self::new(Predicate::contains_any(i))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names every variant of a predicate such that adding a new variant
+    /// fails to compile until it is covered by the tests below
+    #[cfg(feature = "serde-1")]
+    fn variant_name(p: &Predicate) -> &'static str {
+        match p {
+            Predicate::Always => "Always",
+            Predicate::Never => "Never",
+            Predicate::And(_) => "And",
+            Predicate::Any(_) => "Any",
+            Predicate::Contains(_) => "Contains",
+            Predicate::ContainsAll(_) => "ContainsAll",
+            Predicate::ContainsAny(_) => "ContainsAny",
+            Predicate::Equals(_) => "Equals",
+            Predicate::GreaterThan(_) => "GreaterThan",
+            Predicate::GreaterThanOrEquals(_) => "GreaterThanOrEquals",
+            Predicate::HasKey(_) => "HasKey",
+            Predicate::HasKeyWhereValue(_, _) => "HasKeyWhereValue",
+            Predicate::InRange(_) => "InRange",
+            Predicate::InSet(_) => "InSet",
+            Predicate::IsNone => "IsNone",
+            Predicate::Lambda(_) => "Lambda",
+            Predicate::LessThan(_) => "LessThan",
+            Predicate::LessThanOrEquals(_) => "LessThanOrEquals",
+            Predicate::Not(_) => "Not",
+            Predicate::NotEquals(_) => "NotEquals",
+            Predicate::NotInRange(_) => "NotInRange",
+            Predicate::NotInSet(_) => "NotInSet",
+            Predicate::NotNoneAnd(_) => "NotNoneAnd",
+            Predicate::NoneOr(_) => "NoneOr",
+            Predicate::Or(_) => "Or",
+            Predicate::TextContainedIn(_) => "TextContainedIn",
+            Predicate::TextContainedInCaseInsensitive(_) => "TextContainedInCaseInsensitive",
+            Predicate::TextContainsAll(_) => "TextContainsAll",
+            Predicate::TextContainsAllCaseInsensitive(_) => "TextContainsAllCaseInsensitive",
+            Predicate::TextContainsAny(_) => "TextContainsAny",
+            Predicate::TextContainsAnyCaseInsensitive(_) => "TextContainsAnyCaseInsensitive",
+            Predicate::TextEndsWith(_) => "TextEndsWith",
+            Predicate::TextEndsWithCaseInsensitive(_) => "TextEndsWithCaseInsensitive",
+            Predicate::TextEndsWithAny(_) => "TextEndsWithAny",
+            Predicate::TextEndsWithAnyCaseInsensitive(_) => "TextEndsWithAnyCaseInsensitive",
+            Predicate::TextEqualsCaseInsensitive(_) => "TextEqualsCaseInsensitive",
+            Predicate::TextNotEqualsCaseInsensitive(_) => "TextNotEqualsCaseInsensitive",
+            Predicate::TextInSetCaseInsensitive(_) => "TextInSetCaseInsensitive",
+            Predicate::TextFuzzyMatches(_, _) => "TextFuzzyMatches",
+            #[cfg(feature = "regex")]
+            Predicate::TextMatchesRegex(_) => "TextMatchesRegex",
+            Predicate::TextStartsWith(_) => "TextStartsWith",
+            Predicate::TextStartsWithCaseInsensitive(_) => "TextStartsWithCaseInsensitive",
+            Predicate::TextStartsWithAny(_) => "TextStartsWithAny",
+            Predicate::TextStartsWithAnyCaseInsensitive(_) => "TextStartsWithAnyCaseInsensitive",
+            Predicate::Xor(_) => "Xor",
+        }
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn predicate_should_round_trip_through_serde_for_every_variant() {
+        #[allow(unused_mut)]
+        let mut predicates = vec![
+            Predicate::Always,
+            Predicate::Never,
+            Predicate::And(vec![Predicate::Always, Predicate::equals(3u8)]),
+            Predicate::Any(Box::new(Predicate::equals("a"))),
+            Predicate::Contains(Value::from(-5i32)),
+            Predicate::ContainsAll(vec![Value::from(1.5f64), Value::from('c')]),
+            Predicate::ContainsAny(vec![Value::from(true), Value::Optional(None)]),
+            Predicate::Equals(Value::from(vec![1u16, 2, 3])),
+            Predicate::GreaterThan(Value::from(999u64)),
+            Predicate::GreaterThanOrEquals(Value::from(-999i64)),
+            Predicate::HasKey(String::from("key")),
+            Predicate::HasKeyWhereValue(String::from("key"), Box::new(Predicate::IsNone)),
+            Predicate::InRange(Value::from(1u32)..=Value::from(5u32)),
+            Predicate::InSet(
+                vec![Value::from("a"), Value::from("b")]
+                    .into_iter()
+                    .collect(),
+            ),
+            Predicate::IsNone,
+            Predicate::LessThan(Value::from(0.25f32)),
+            Predicate::LessThanOrEquals(Value::from(Some(7i8))),
+            Predicate::Not(Box::new(Predicate::Never)),
+            Predicate::NotEquals(Value::from(String::from("text"))),
+            Predicate::NotInRange(Value::from(-3i16)..=Value::from(3i16)),
+            Predicate::NotInSet(
+                vec![Value::from(1u8), Value::from(2u8)]
+                    .into_iter()
+                    .collect(),
+            ),
+            Predicate::NotNoneAnd(Box::new(Predicate::equals(1u8))),
+            Predicate::NoneOr(Box::new(Predicate::equals(1u8))),
+            Predicate::Or(vec![Predicate::Never, Predicate::Always]),
+            Predicate::TextContainedIn(String::from("abc")),
+            Predicate::TextContainedInCaseInsensitive(String::from("ABC")),
+            Predicate::TextContainsAll(vec![String::from("a"), String::from("b")]),
+            Predicate::TextContainsAllCaseInsensitive(vec![String::from("A")]),
+            Predicate::TextContainsAny(vec![String::from("a"), String::from("b")]),
+            Predicate::TextContainsAnyCaseInsensitive(vec![String::from("A")]),
+            Predicate::TextEndsWith(String::from("z")),
+            Predicate::TextEndsWithCaseInsensitive(String::from("Z")),
+            Predicate::TextEndsWithAny(vec![String::from("y"), String::from("z")]),
+            Predicate::TextEndsWithAnyCaseInsensitive(vec![String::from("Z")]),
+            Predicate::TextEqualsCaseInsensitive(String::from("Text")),
+            Predicate::TextNotEqualsCaseInsensitive(String::from("Text")),
+            Predicate::TextInSetCaseInsensitive(
+                vec![String::from("a"), String::from("B")]
+                    .into_iter()
+                    .collect(),
+            ),
+            Predicate::TextFuzzyMatches(String::from("text"), 2),
+            Predicate::TextStartsWith(String::from("a")),
+            Predicate::TextStartsWithCaseInsensitive(String::from("A")),
+            Predicate::TextStartsWithAny(vec![String::from("a"), String::from("b")]),
+            Predicate::TextStartsWithAnyCaseInsensitive(vec![String::from("A")]),
+            Predicate::Xor(vec![Predicate::Always, Predicate::Never]),
+        ];
+
+        #[cfg(feature = "regex")]
+        predicates.push(Predicate::TextMatchesRegex(
+            regex::Regex::new(r"^\w+@\w+$").unwrap(),
+        ));
+
+        let names: HashSet<&'static str> = predicates.iter().map(variant_name).collect();
+        assert_eq!(
+            names.len(),
+            predicates.len(),
+            "Each variant should be tested once"
+        );
+        assert_eq!(
+            names.len(),
+            if cfg!(feature = "regex") { 44 } else { 43 },
+            "Every variant but lambda should be tested"
+        );
+
+        for p in predicates {
+            let json = serde_json::to_string(&p).unwrap();
+            let q: Predicate = serde_json::from_str(&json).unwrap();
+            assert_eq!(p, q, "Round trip failed for {}", json);
+        }
+    }
+
+    #[test]
+    fn text_fuzzy_matches_should_count_edits_by_character() {
+        assert_eq!(edit_distance("", "", 0), 0);
+        assert_eq!(edit_distance("abc", "", 5), 3);
+        assert_eq!(edit_distance("", "abc", 5), 3);
+        assert_eq!(edit_distance("kitten", "sitting", 5), 3);
+        assert_eq!(edit_distance("über", "uber", 5), 1);
+
+        // Once every total exceeds the limit, the distance stops growing
+        assert_eq!(edit_distance("aaaaaaaa", "bbbbbbbb", 2), 3);
+
+        let p = Predicate::text_fuzzy_matches("flaw", 2);
+        assert!(p.check(&Value::from("lawn")));
+        assert!(p.check(&Value::from("flaw")));
+        assert!(!p.check(&Value::from("lawns")));
+        assert!(!p.check(&Value::from(3)));
+    }
+
+    #[test]
+    fn in_set_should_find_equal_values_of_different_types() {
+        let p = Predicate::in_set(vec![Value::from(3u8), Value::from(Some("a"))]);
+        assert!(p.check(&Value::from(3i64)));
+        assert!(p.check(&Value::from(3.0f64)));
+        assert!(p.check(&Value::from(Some(3u16))));
+        assert!(p.check(&Value::from("a")));
+        assert!(!p.check(&Value::from(4u8)));
+        assert!(!p.check(&Value::from(None::<u8>)));
+
+        let p = Predicate::not_in_set(vec![Value::from(Some(3u8))]);
+        assert!(!p.check(&Value::from(3i32)));
+        assert!(p.check(&Value::from(-3i32)));
+    }
+
+    #[cfg(all(feature = "regex", feature = "serde-1"))]
+    #[test]
+    fn predicate_should_fail_to_deserialize_invalid_regex() {
+        let err = serde_json::from_str::<Predicate>(r#"{"TextMatchesRegex":"("}"#).unwrap_err();
+        assert!(
+            err.to_string().contains("regex parse error"),
+            "Unexpected error: {}",
+            err
+        );
+    }
+
+    #[cfg(feature = "sled_db")]
+    #[test]
+    fn predicate_should_serialize_the_same_variant_index_with_or_without_regex() {
+        let index = |p: &Predicate| {
+            let bytes = bincode::serialize(p).unwrap();
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        };
+        assert_eq!(index(&Predicate::TextStartsWith(String::new())), 39);
+        assert_eq!(index(&Predicate::Xor(vec![])), 43);
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn predicate_should_fail_to_serialize_lambda() {
+        let p = Predicate::And(vec![Predicate::Always, Predicate::lambda(|_| true)]);
+        let err = serde_json::to_string(&p).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Predicate::Lambda cannot be serialized"),
+            "Unexpected error: {}",
+            err
+        );
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn predicate_should_fail_to_deserialize_lambda() {
+        let err = serde_json::from_str::<Predicate>(r#"{"Lambda":null}"#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Predicate::Lambda cannot be deserialized"),
+            "Unexpected error: {}",
+            err
+        );
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn typed_predicate_should_serialize_as_its_untyped_predicate() {
+        let p = TypedPredicate::<u32>::greater_than(5);
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(json, serde_json::to_string(p.as_untyped()).unwrap());
+
+        let q: TypedPredicate<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(p, q);
+        assert!(q.check(6));
+        assert!(!q.check(5));
+    }
+
+    #[cfg(feature = "serde-1")]
+    #[test]
+    fn map_typed_predicate_should_serialize_as_its_untyped_predicate() {
+        let p = MapTypedPredicate::<u32, HashMap<String, u32>>::contains(3);
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(json, serde_json::to_string(p.as_untyped()).unwrap());
+
+        let q: MapTypedPredicate<u32, HashMap<String, u32>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(p, q);
+    }
+}
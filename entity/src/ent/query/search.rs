@@ -0,0 +1,164 @@
+use crate::{Ent, FieldPath, Value};
+use std::collections::HashSet;
+
+/// Splits text into the words used by full-text search, where a word is a
+/// run of letters and digits folded into lowercase
+///
+/// ### Examples
+///
+/// ```
+/// use entity::search_tokens;
+///
+/// assert_eq!(
+///     search_tokens("Hello, World! It's 2021."),
+///     vec!["hello", "world", "it", "s", "2021"],
+/// );
+/// assert!(search_tokens("--").is_empty());
+/// ```
+pub fn search_tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Collects the words of all text within the value as produced by
+/// [`search_tokens`], looking through optional values, lists, and the
+/// values of maps; values that are not text have no words
+pub fn search_value_tokens(value: &Value) -> Vec<String> {
+    let mut tokens = Vec::new();
+    push_value_tokens(&mut tokens, value);
+    tokens
+}
+
+fn push_value_tokens(tokens: &mut Vec<String>, value: &Value) {
+    match value {
+        Value::Text(x) => tokens.extend(search_tokens(x)),
+        Value::Optional(Some(x)) => push_value_tokens(tokens, x),
+        Value::List(x) => {
+            for value in x {
+                push_value_tokens(tokens, value);
+            }
+        }
+        Value::Map(x) => {
+            for value in x.values() {
+                push_value_tokens(tokens, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Represents how well the text of a field matches the words of search
+/// text, where a field is more relevant the more distinct words of the
+/// search it contains and, among fields with the same total, the more
+/// times those words appear
+///
+/// ### Examples
+///
+/// ```
+/// use entity::{Field, Relevance, UntypedEnt};
+///
+/// let a = UntypedEnt::from_collections(1, vec![Field::new("bio", "Rust and more rust")], vec![]);
+/// let b = UntypedEnt::from_collections(2, vec![Field::new("bio", "Rust or Go")], vec![]);
+///
+/// let relevance = Relevance::of(&a, "bio", "rust go");
+/// assert_eq!(relevance.matched_words(), 1);
+/// assert_eq!(relevance.occurrences(), 2);
+///
+/// // Matching more distinct words outweighs matching the same word often
+/// assert!(Relevance::of(&b, "bio", "rust go") > relevance);
+/// assert!(!Relevance::of(&a, "bio", "python").is_match());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Relevance {
+    matched_words: usize,
+    occurrences: usize,
+}
+
+impl Relevance {
+    /// Computes the relevance of the words of a field to the words of
+    /// search text
+    pub fn from_tokens<S: AsRef<str>, T: AsRef<str>>(
+        field_tokens: &[S],
+        search_tokens: &[T],
+    ) -> Self {
+        let words: HashSet<&str> = search_tokens.iter().map(AsRef::as_ref).collect();
+        let mut matched = HashSet::new();
+        let mut occurrences = 0;
+        for token in field_tokens.iter().map(AsRef::as_ref) {
+            if words.contains(token) {
+                matched.insert(token);
+                occurrences += 1;
+            }
+        }
+
+        Self {
+            matched_words: matched.len(),
+            occurrences,
+        }
+    }
+
+    /// Computes the relevance of the field of the ent with the given name,
+    /// which can also be a path into a field, to the search text
+    ///
+    /// An ent without the field is not relevant at all.
+    pub fn of(ent: &dyn Ent, name: &str, text: &str) -> Self {
+        match FieldPath::lookup(ent, name) {
+            Some(value) => Self::from_tokens(&search_value_tokens(&value), &search_tokens(text)),
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the total distinct words of the search found in the field
+    pub fn matched_words(&self) -> usize {
+        self.matched_words
+    }
+
+    /// Returns the total times that words of the search appear in the field
+    pub fn occurrences(&self) -> usize {
+        self.occurrences
+    }
+
+    /// Returns true if at least one word of the search is found in the field
+    pub fn is_match(&self) -> bool {
+        self.matched_words > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn search_tokens_should_fold_case_and_split_on_non_alphanumerics() {
+        assert_eq!(
+            search_tokens("ÉCOLE über-Straße"),
+            vec!["école", "über", "straße"]
+        );
+        assert_eq!(search_tokens("  a\tb\nc  "), vec!["a", "b", "c"]);
+        assert!(search_tokens("").is_empty());
+    }
+
+    #[test]
+    fn search_value_tokens_should_include_nested_text() {
+        let mut map = HashMap::new();
+        map.insert(String::from("k"), Value::from(vec!["Two Words"]));
+        let value = Value::from(Some(Value::Map(map)));
+        assert_eq!(search_value_tokens(&value), vec!["two", "words"]);
+        assert!(search_value_tokens(&Value::from(3)).is_empty());
+    }
+
+    #[test]
+    fn relevance_should_rank_distinct_words_before_occurrences() {
+        let rel = |field: &str, search: &str| {
+            Relevance::from_tokens(&search_tokens(field), &search_tokens(search))
+        };
+
+        assert!(rel("a b", "a b") > rel("a a a", "a b"));
+        assert!(rel("a a", "a b") > rel("a", "a b"));
+        assert_eq!(rel("a", "b"), Relevance::default());
+        assert!(!rel("a", "").is_match());
+    }
+}
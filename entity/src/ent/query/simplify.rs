@@ -0,0 +1,750 @@
+use super::Predicate;
+use crate::{PrimitiveValue, Value};
+use std::{cmp::Ordering, collections::HashSet};
+
+/// Rewrites the predicate into an equivalent predicate that is cheaper to
+/// check, as described by [`Predicate::simplify`]
+pub fn simplify(predicate: Predicate) -> Predicate {
+    match predicate {
+        Predicate::And(list) => and(list.into_iter().map(simplify).collect()),
+        Predicate::Or(list) => or(list.into_iter().map(simplify).collect()),
+        Predicate::Xor(list) => xor(list.into_iter().map(simplify).collect()),
+        Predicate::Not(p) => not(simplify(*p)),
+        Predicate::Any(p) => match simplify(*p) {
+            Predicate::Never => Predicate::Never,
+            p => Predicate::Any(Box::new(p)),
+        },
+        Predicate::HasKeyWhereValue(k, p) => match simplify(*p) {
+            Predicate::Always => Predicate::HasKey(k),
+            Predicate::Never => Predicate::Never,
+            p => Predicate::HasKeyWhereValue(k, Box::new(p)),
+        },
+        Predicate::NotNoneAnd(p) => match simplify(*p) {
+            p @ Predicate::Always | p @ Predicate::Never => p,
+            p => Predicate::NotNoneAnd(Box::new(p)),
+        },
+        Predicate::NoneOr(p) => match simplify(*p) {
+            Predicate::Always => Predicate::Always,
+            Predicate::Never => Predicate::IsNone,
+            p => Predicate::NoneOr(Box::new(p)),
+        },
+        Predicate::InSet(set) => in_set(set),
+        Predicate::NotInSet(set) => not(in_set(set)),
+        p @ Predicate::InRange(_) if Family::of_range(&p).is_some() => {
+            let interval = Interval::from_predicate(p);
+            if interval.is_empty() {
+                Predicate::Never
+            } else {
+                interval.into_predicate()
+            }
+        }
+        p => p,
+    }
+}
+
+/// Combines already-simplified predicates that must all be true, flattening
+/// nested ands and intersecting ranges of comparable bounds
+fn and(list: Vec<Predicate>) -> Predicate {
+    let mut parts = Vec::new();
+    let mut ranges: Vec<(Family, Interval)> = Vec::new();
+
+    for p in flatten(list, Flatten::And) {
+        match p {
+            Predicate::Always => {}
+            Predicate::Never => return Predicate::Never,
+            p => match Family::of_range(&p) {
+                Some(family) => {
+                    let interval = Interval::from_predicate(p);
+                    match ranges.iter().position(|(f, _)| *f == family) {
+                        Some(i) => ranges[i].1.intersect(interval),
+                        None => {
+                            parts.push(Part::Range(ranges.len()));
+                            ranges.push((family, interval));
+                        }
+                    }
+                }
+                None => parts.push(Part::Predicate(p)),
+            },
+        }
+    }
+
+    let mut list = Vec::new();
+    for part in parts {
+        match part {
+            Part::Predicate(p) => list.push(p),
+            Part::Range(i) if ranges[i].1.is_empty() => return Predicate::Never,
+            Part::Range(i) => list.extend(ranges[i].1.clone().into_predicates()),
+            Part::Set => unreachable!("ands never collect sets"),
+        }
+    }
+
+    match list.len() {
+        0 => Predicate::Always,
+        1 => list.pop().unwrap(),
+        _ => Predicate::And(list),
+    }
+}
+
+/// Combines already-simplified predicates where any must be true, flattening
+/// nested ors, collecting equality checks into a set, and joining
+/// overlapping ranges of comparable bounds
+fn or(list: Vec<Predicate>) -> Predicate {
+    let mut parts = Vec::new();
+    let mut set: Option<HashSet<Value>> = None;
+    let mut ranges: Vec<(Family, Vec<Interval>)> = Vec::new();
+
+    for p in flatten(list, Flatten::Or) {
+        match p {
+            Predicate::Always => return Predicate::Always,
+            Predicate::Never => {}
+            Predicate::Equals(v) => {
+                if set.is_none() {
+                    parts.push(Part::Set);
+                }
+                set.get_or_insert_with(HashSet::new).insert(v);
+            }
+            Predicate::InSet(values) => {
+                if set.is_none() {
+                    parts.push(Part::Set);
+                }
+                set.get_or_insert_with(HashSet::new).extend(values);
+            }
+            p => match Family::of_range(&p) {
+                Some(family) => {
+                    let interval = Interval::from_predicate(p);
+                    match ranges.iter().position(|(f, _)| *f == family) {
+                        Some(i) => ranges[i].1.push(interval),
+                        None => {
+                            parts.push(Part::Range(ranges.len()));
+                            ranges.push((family, vec![interval]));
+                        }
+                    }
+                }
+                None => parts.push(Part::Predicate(p)),
+            },
+        }
+    }
+
+    let mut list = Vec::new();
+    for part in parts {
+        match part {
+            Part::Predicate(p) => list.push(p),
+            Part::Set => list.push(in_set(set.take().unwrap_or_default())),
+            Part::Range(i) => list.extend(
+                Interval::union(std::mem::take(&mut ranges[i].1))
+                    .into_iter()
+                    .map(Interval::into_predicate),
+            ),
+        }
+    }
+
+    match list.len() {
+        0 => Predicate::Never,
+        1 => list.pop().unwrap(),
+        _ => Predicate::Or(list),
+    }
+}
+
+/// Combines already-simplified predicates where exactly one must be true,
+/// removing those that are never true and resolving those always true
+fn xor(list: Vec<Predicate>) -> Predicate {
+    let mut list: Vec<Predicate> = list
+        .into_iter()
+        .filter(|p| !matches!(p, Predicate::Never))
+        .collect();
+    let always = list
+        .iter()
+        .filter(|p| matches!(p, Predicate::Always))
+        .count();
+
+    match always {
+        // With exactly one always true, all others must be false
+        1 => not(or(list
+            .into_iter()
+            .filter(|p| !matches!(p, Predicate::Always))
+            .collect())),
+        0 => match list.len() {
+            0 => Predicate::Never,
+            1 => list.pop().unwrap(),
+            _ => Predicate::Xor(list),
+        },
+        _ => Predicate::Never,
+    }
+}
+
+/// Negates an already-simplified predicate, pushing the negation inward
+/// using De Morgan's laws and inverting predicates that have an exact
+/// opposite
+fn not(predicate: Predicate) -> Predicate {
+    match predicate {
+        Predicate::Always => Predicate::Never,
+        Predicate::Never => Predicate::Always,
+        Predicate::Not(p) => *p,
+        Predicate::And(list) => or(list.into_iter().map(not).collect()),
+        Predicate::Or(list) => and(list.into_iter().map(not).collect()),
+        Predicate::Equals(v) => Predicate::NotEquals(v),
+        Predicate::NotEquals(v) => Predicate::Equals(v),
+        Predicate::InSet(set) => Predicate::NotInSet(set),
+        Predicate::NotInSet(set) => Predicate::InSet(set),
+        p => Predicate::Not(Box::new(p)),
+    }
+}
+
+/// Converts a set into the simplest predicate checking membership
+fn in_set(mut set: HashSet<Value>) -> Predicate {
+    match set.len() {
+        0 => Predicate::Never,
+        1 => Predicate::Equals(set.drain().next().unwrap()),
+        _ => Predicate::InSet(set),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Flatten {
+    And,
+    Or,
+}
+
+/// Replaces any nested and/or (matching the kind being flattened) with the
+/// predicates it contains
+fn flatten(list: Vec<Predicate>, kind: Flatten) -> Vec<Predicate> {
+    let mut flat = Vec::new();
+    for p in list {
+        match p {
+            Predicate::And(list) if kind == Flatten::And => flat.extend(flatten(list, kind)),
+            Predicate::Or(list) if kind == Flatten::Or => flat.extend(flatten(list, kind)),
+            p => flat.push(p),
+        }
+    }
+    flat
+}
+
+/// Placeholder keeping the position of a predicate within a simplified
+/// and/or, where sets and ranges are only known once all predicates are seen
+enum Part {
+    Predicate(Predicate),
+    Set,
+    Range(usize),
+}
+
+/// Represents the values whose bounds can be merged, as every value that is
+/// comparable to a bound in the family is comparable to all of them in the
+/// same order
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Family {
+    /// Numbers that are zero or normal, excluding nan, infinity, and
+    /// subnormal floats that cannot be compared
+    Number,
+
+    /// Text and characters, which are compared as text
+    Text,
+}
+
+impl Family {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Primitive(PrimitiveValue::Number(x)) if x.is_normal() || x.is_zero() => {
+                Some(Self::Number)
+            }
+            Value::Text(_) | Value::Primitive(PrimitiveValue::Char(_)) => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    /// Returns the family shared by the bounds of a range predicate, or none
+    /// if the predicate is not a range or its bounds cannot be merged
+    fn of_range(predicate: &Predicate) -> Option<Self> {
+        match predicate {
+            Predicate::GreaterThan(v)
+            | Predicate::GreaterThanOrEquals(v)
+            | Predicate::LessThan(v)
+            | Predicate::LessThanOrEquals(v) => Self::of(v),
+            Predicate::InRange(range) => match (Self::of(range.start()), Self::of(range.end())) {
+                (Some(a), Some(b)) if a == b => Some(a),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Represents a bound of an interval alongside whether the bound itself is
+/// included within the interval
+#[derive(Clone)]
+struct Bound {
+    value: Value,
+    inclusive: bool,
+}
+
+impl Bound {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value
+            .partial_cmp(&other.value)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Represents the values between two optional bounds, where a missing bound
+/// leaves that side of the interval unbounded
+#[derive(Clone)]
+struct Interval {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+impl Interval {
+    /// Converts a range predicate whose bounds belong to a family, as
+    /// determined by [`Family::of_range`], into an interval
+    fn from_predicate(predicate: Predicate) -> Self {
+        let bound = |value, inclusive| Some(Bound { value, inclusive });
+        match predicate {
+            Predicate::GreaterThan(v) => Self {
+                lower: bound(v, false),
+                upper: None,
+            },
+            Predicate::GreaterThanOrEquals(v) => Self {
+                lower: bound(v, true),
+                upper: None,
+            },
+            Predicate::LessThan(v) => Self {
+                lower: None,
+                upper: bound(v, false),
+            },
+            Predicate::LessThanOrEquals(v) => Self {
+                lower: None,
+                upper: bound(v, true),
+            },
+            Predicate::InRange(range) => {
+                let (start, end) = range.into_inner();
+                Self {
+                    lower: bound(start, true),
+                    upper: bound(end, true),
+                }
+            }
+            _ => unreachable!("only range predicates convert into intervals"),
+        }
+    }
+
+    /// Returns true if no value can fall within the interval
+    fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Some(lower), Some(upper)) => match lower.cmp(upper) {
+                Ordering::Greater => true,
+                Ordering::Equal => !lower.inclusive || !upper.inclusive,
+                Ordering::Less => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Narrows the interval to the values also within the other interval
+    fn intersect(&mut self, other: Self) {
+        if let Some(lower) = other.lower {
+            self.lower = match self.lower.take() {
+                Some(x) => Some(match x.cmp(&lower) {
+                    Ordering::Less => lower,
+                    Ordering::Equal if !lower.inclusive => lower,
+                    _ => x,
+                }),
+                None => Some(lower),
+            };
+        }
+
+        if let Some(upper) = other.upper {
+            self.upper = match self.upper.take() {
+                Some(x) => Some(match x.cmp(&upper) {
+                    Ordering::Greater => upper,
+                    Ordering::Equal if !upper.inclusive => upper,
+                    _ => x,
+                }),
+                None => Some(upper),
+            };
+        }
+    }
+
+    /// Joins intervals that overlap or touch, dropping empty intervals and
+    /// keeping apart any intervals that would join into one without bounds
+    fn union(intervals: Vec<Self>) -> Vec<Self> {
+        let mut intervals: Vec<Self> = intervals.into_iter().filter(|x| !x.is_empty()).collect();
+        intervals.sort_by(|a, b| match (&a.lower, &b.lower) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b).then_with(|| b.inclusive.cmp(&a.inclusive)),
+        });
+
+        let mut joined: Vec<Self> = Vec::new();
+        for interval in intervals {
+            match joined.last_mut() {
+                Some(last) if last.joins(&interval) => last.upper = last.upper_with(interval),
+                _ => joined.push(interval),
+            }
+        }
+        joined
+    }
+
+    /// Returns true if the other interval, whose lower bound is not below
+    /// this interval's lower bound, overlaps or touches this interval and
+    /// the two can be joined without losing every bound
+    fn joins(&self, other: &Self) -> bool {
+        let touches = match (&self.upper, &other.lower) {
+            (None, _) | (_, None) => true,
+            (Some(upper), Some(lower)) => match lower.cmp(upper) {
+                Ordering::Less => true,
+                Ordering::Equal => lower.inclusive || upper.inclusive,
+                Ordering::Greater => false,
+            },
+        };
+
+        touches && (self.lower.is_some() || (self.upper.is_some() && other.upper.is_some()))
+    }
+
+    /// Returns the greater of the upper bounds of this and the other interval
+    fn upper_with(&mut self, other: Self) -> Option<Bound> {
+        match (self.upper.take(), other.upper) {
+            (Some(a), Some(b)) => Some(match a.cmp(&b) {
+                Ordering::Less => b,
+                Ordering::Equal if b.inclusive => b,
+                _ => a,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Converts the interval into the predicates that must all be true for a
+    /// value to fall within it
+    fn into_predicates(self) -> Vec<Predicate> {
+        match (self.lower, self.upper) {
+            (Some(lower), Some(upper)) if lower.inclusive && upper.inclusive => {
+                vec![Predicate::InRange(lower.value..=upper.value)]
+            }
+            (lower, upper) => {
+                let mut list = Vec::new();
+                if let Some(lower) = lower {
+                    list.push(if lower.inclusive {
+                        Predicate::GreaterThanOrEquals(lower.value)
+                    } else {
+                        Predicate::GreaterThan(lower.value)
+                    });
+                }
+                if let Some(upper) = upper {
+                    list.push(if upper.inclusive {
+                        Predicate::LessThanOrEquals(upper.value)
+                    } else {
+                        Predicate::LessThan(upper.value)
+                    });
+                }
+                list
+            }
+        }
+    }
+
+    /// Converts the interval into a single predicate
+    fn into_predicate(self) -> Predicate {
+        let mut list = self.into_predicates();
+        match list.len() {
+            1 => list.pop().unwrap(),
+            _ => Predicate::And(list),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TypedPredicate;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    /// Produces values that mix types which compare as equal or ordered
+    /// against one another, such as numbers of different sizes, optional
+    /// values, and characters against text
+    fn value() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            (-4i8..4).prop_map(Value::from),
+            (0u8..4).prop_map(Value::from),
+            (-4i32..4).prop_map(Value::from),
+            (-4i64..4).prop_map(Value::from),
+            (-4i32..4).prop_map(|x| Value::from(x as f64 / 2.0)),
+            prop_oneof![Just(f64::NAN), Just(f64::INFINITY), Just(f64::NEG_INFINITY)]
+                .prop_map(Value::from),
+            prop_oneof![Just('a'), Just('b'), Just('c')].prop_map(Value::from),
+            prop_oneof![Just("a"), Just("ab"), Just("b"), Just("")].prop_map(Value::from),
+            any::<bool>().prop_map(Value::from),
+            Just(Value::Primitive(PrimitiveValue::Unit)),
+            Just(Value::Optional(None)),
+        ];
+
+        leaf.prop_recursive(2, 8, 3, |inner| {
+            prop_oneof![
+                inner
+                    .clone()
+                    .prop_map(|v| Value::Optional(Some(Box::new(v)))),
+                prop::collection::vec(inner.clone(), 0..3).prop_map(Value::List),
+                prop::collection::vec((prop_oneof![Just("a"), Just("b")], inner), 0..3).prop_map(
+                    |x| Value::Map(
+                        x.into_iter()
+                            .map(|(k, v)| (k.to_string(), v))
+                            .collect::<HashMap<String, Value>>()
+                    )
+                ),
+            ]
+        })
+    }
+
+    fn predicate() -> impl Strategy<Value = Predicate> {
+        let leaf = prop_oneof![
+            Just(Predicate::Always),
+            Just(Predicate::Never),
+            Just(Predicate::IsNone),
+            value().prop_map(Predicate::Equals),
+            value().prop_map(Predicate::NotEquals),
+            value().prop_map(Predicate::GreaterThan),
+            value().prop_map(Predicate::GreaterThanOrEquals),
+            value().prop_map(Predicate::LessThan),
+            value().prop_map(Predicate::LessThanOrEquals),
+            (value(), value()).prop_map(|(a, b)| Predicate::InRange(a..=b)),
+            (value(), value()).prop_map(|(a, b)| Predicate::NotInRange(a..=b)),
+            prop::collection::hash_set(value(), 0..3).prop_map(Predicate::InSet),
+            prop::collection::hash_set(value(), 0..3).prop_map(Predicate::NotInSet),
+            value().prop_map(Predicate::Contains),
+            prop_oneof![Just("a"), Just("b")].prop_map(|k| Predicate::HasKey(k.to_string())),
+            prop_oneof![Just("a"), Just("b")]
+                .prop_map(|s| Predicate::TextStartsWith(s.to_string())),
+        ];
+
+        leaf.prop_recursive(4, 32, 4, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Predicate::And),
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Predicate::Or),
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Predicate::Xor),
+                inner.clone().prop_map(|p| Predicate::Not(Box::new(p))),
+                inner.clone().prop_map(|p| Predicate::Any(Box::new(p))),
+                inner
+                    .clone()
+                    .prop_map(|p| Predicate::NotNoneAnd(Box::new(p))),
+                inner.clone().prop_map(|p| Predicate::NoneOr(Box::new(p))),
+                (prop_oneof![Just("a"), Just("b")], inner)
+                    .prop_map(|(k, p)| Predicate::HasKeyWhereValue(k.to_string(), Box::new(p))),
+            ]
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2048))]
+
+        #[test]
+        fn simplify_should_not_change_result_of_check(
+            p in predicate(),
+            values in prop::collection::vec(value(), 1..16),
+        ) {
+            let simplified = p.clone().simplify();
+            for v in values.iter() {
+                prop_assert_eq!(
+                    p.check(v),
+                    simplified.check(v),
+                    "{:?} simplified to {:?} for {:?}",
+                    p,
+                    simplified,
+                    v
+                );
+            }
+        }
+
+        #[test]
+        fn simplify_should_not_change_result_of_check_for_typed_predicate(
+            p in predicate(),
+            values in prop::collection::vec(-8i32..8, 1..16),
+        ) {
+            let p = TypedPredicate::<i32>::new(p);
+            let simplified = p.clone().simplify();
+            for v in values {
+                prop_assert_eq!(p.check(v), simplified.check(v));
+            }
+        }
+
+        #[test]
+        fn simplify_should_remove_always_and_never_from_and_or(p in predicate()) {
+            fn has_constant_member(p: &Predicate) -> bool {
+                match p {
+                    Predicate::And(list) | Predicate::Or(list) | Predicate::Xor(list) => list
+                        .iter()
+                        .any(|p| matches!(p, Predicate::Always | Predicate::Never)
+                            || has_constant_member(p)),
+                    // Any(Always) remains as it requires a non-empty collection
+                    Predicate::Any(p) => {
+                        matches!(**p, Predicate::Never) || has_constant_member(p)
+                    }
+                    Predicate::Not(p)
+                    | Predicate::NotNoneAnd(p)
+                    | Predicate::NoneOr(p)
+                    | Predicate::HasKeyWhereValue(_, p) => {
+                        matches!(**p, Predicate::Always | Predicate::Never)
+                            || has_constant_member(p)
+                    }
+                    _ => false,
+                }
+            }
+
+            let simplified = p.simplify();
+            prop_assert!(!has_constant_member(&simplified), "{:?}", simplified);
+        }
+    }
+
+    #[test]
+    fn simplify_should_flatten_nested_and_or() {
+        let p = Predicate::And(vec![
+            Predicate::And(vec![
+                Predicate::IsNone,
+                Predicate::HasKey(String::from("a")),
+            ]),
+            Predicate::And(vec![Predicate::And(vec![Predicate::Contains(
+                Value::from(1),
+            )])]),
+        ]);
+        assert_eq!(
+            p.simplify(),
+            Predicate::And(vec![
+                Predicate::IsNone,
+                Predicate::HasKey(String::from("a")),
+                Predicate::Contains(Value::from(1)),
+            ])
+        );
+
+        let p = Predicate::Or(vec![
+            Predicate::IsNone,
+            Predicate::Or(vec![Predicate::Never, Predicate::HasKey(String::from("a"))]),
+        ]);
+        assert_eq!(
+            p.simplify(),
+            Predicate::Or(vec![
+                Predicate::IsNone,
+                Predicate::HasKey(String::from("a"))
+            ])
+        );
+    }
+
+    #[test]
+    fn simplify_should_fold_constants() {
+        assert_eq!(Predicate::And(vec![]).simplify(), Predicate::Always);
+        assert_eq!(Predicate::Or(vec![]).simplify(), Predicate::Never);
+        assert_eq!(Predicate::Xor(vec![]).simplify(), Predicate::Never);
+        assert_eq!(
+            Predicate::And(vec![Predicate::IsNone, Predicate::Never]).simplify(),
+            Predicate::Never
+        );
+        assert_eq!(
+            Predicate::Or(vec![Predicate::IsNone, Predicate::Always]).simplify(),
+            Predicate::Always
+        );
+        assert_eq!(
+            Predicate::Xor(vec![Predicate::Always, Predicate::Always]).simplify(),
+            Predicate::Never
+        );
+        assert_eq!(
+            Predicate::Xor(vec![Predicate::Always, Predicate::Equals(Value::from(1))]).simplify(),
+            Predicate::NotEquals(Value::from(1))
+        );
+        assert_eq!(
+            Predicate::NoneOr(Box::new(Predicate::Never)).simplify(),
+            Predicate::IsNone
+        );
+        assert_eq!(
+            Predicate::HasKeyWhereValue(String::from("a"), Box::new(Predicate::Always)).simplify(),
+            Predicate::HasKey(String::from("a"))
+        );
+    }
+
+    #[test]
+    fn simplify_should_push_negation_inward() {
+        let p = !!Predicate::IsNone;
+        assert_eq!(p.simplify(), Predicate::IsNone);
+
+        let p = !(Predicate::IsNone & Predicate::Equals(Value::from(1)));
+        assert_eq!(
+            p.simplify(),
+            Predicate::Or(vec![
+                Predicate::Not(Box::new(Predicate::IsNone)),
+                Predicate::NotEquals(Value::from(1)),
+            ])
+        );
+
+        let p = !(Predicate::IsNone | Predicate::NotEquals(Value::from(1)));
+        assert_eq!(
+            p.simplify(),
+            Predicate::And(vec![
+                Predicate::Not(Box::new(Predicate::IsNone)),
+                Predicate::Equals(Value::from(1)),
+            ])
+        );
+    }
+
+    #[test]
+    fn simplify_should_collect_equals_into_set() {
+        let p = Predicate::IsNone
+            | Predicate::Equals(Value::from(1))
+            | Predicate::Equals(Value::from("a"))
+            | Predicate::InSet(vec![Value::from(2)].into_iter().collect());
+        assert_eq!(
+            p.simplify(),
+            Predicate::Or(vec![
+                Predicate::IsNone,
+                Predicate::InSet(
+                    vec![Value::from(1), Value::from("a"), Value::from(2)]
+                        .into_iter()
+                        .collect()
+                ),
+            ])
+        );
+
+        let p = Predicate::Equals(Value::from(1)) | Predicate::Equals(Value::from(1));
+        assert_eq!(p.simplify(), Predicate::Equals(Value::from(1)));
+    }
+
+    #[test]
+    fn simplify_should_merge_overlapping_ranges() {
+        let p = Predicate::GreaterThan(Value::from(1))
+            & Predicate::GreaterThanOrEquals(Value::from(3))
+            & Predicate::LessThan(Value::from(8))
+            & Predicate::InRange(Value::from(2)..=Value::from(10));
+        assert_eq!(
+            p.simplify(),
+            Predicate::And(vec![
+                Predicate::GreaterThanOrEquals(Value::from(3)),
+                Predicate::LessThan(Value::from(8)),
+            ])
+        );
+
+        let p = Predicate::GreaterThan(Value::from(5)) & Predicate::LessThan(Value::from(5));
+        assert_eq!(p.simplify(), Predicate::Never);
+
+        let p = Predicate::LessThan(Value::from(1))
+            | Predicate::InRange(Value::from(5)..=Value::from(7))
+            | Predicate::InRange(Value::from(1)..=Value::from(3));
+        assert_eq!(
+            p.simplify(),
+            Predicate::Or(vec![
+                Predicate::LessThanOrEquals(Value::from(3)),
+                Predicate::InRange(Value::from(5)..=Value::from(7)),
+            ])
+        );
+
+        // Text and numbers are merged separately
+        let p = Predicate::InRange(Value::from("a")..=Value::from("c"))
+            | Predicate::InRange(Value::from(1)..=Value::from(3))
+            | Predicate::GreaterThanOrEquals(Value::from('b'));
+        assert_eq!(
+            p.simplify(),
+            Predicate::Or(vec![
+                Predicate::GreaterThanOrEquals(Value::from("a")),
+                Predicate::InRange(Value::from(1)..=Value::from(3)),
+            ])
+        );
+
+        // Ranges covering every number are kept apart as other values still
+        // fail the comparisons
+        let p =
+            Predicate::LessThan(Value::from(1)) | Predicate::GreaterThanOrEquals(Value::from(1));
+        assert_eq!(p.clone().simplify(), p);
+    }
+}
@@ -0,0 +1,355 @@
+use super::{lexer::is_ident, IntegerType};
+use crate::{Filter, Number, OrderBy, OrderDirection, Predicate, PrimitiveValue, Query, Value};
+use std::fmt;
+
+/// Displays the query as text that can be parsed back into a query,
+/// unless the query contains a lambda predicate
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut clauses = Vec::new();
+
+        if !self.filters().is_empty() {
+            let filters: Vec<String> = self
+                .filters()
+                .iter()
+                .map(|filter| Operand(filter).to_string())
+                .collect();
+            clauses.push(filters.join(" and "));
+        }
+
+        if !self.orders().is_empty() {
+            let orders: Vec<String> = self
+                .orders()
+                .iter()
+                .map(|order| {
+                    let by = match order.by() {
+                        OrderBy::Id => String::from("id"),
+                        OrderBy::Created => String::from("created"),
+                        OrderBy::LastUpdated => String::from("last_updated"),
+                        OrderBy::Field(name) => format!("field({})", Name(name)),
+                        OrderBy::Relevance(name, text) => {
+                            format!("relevance({}, {:?})", Name(name), text)
+                        }
+                    };
+                    let direction = match order.direction() {
+                        OrderDirection::Ascending => "asc",
+                        OrderDirection::Descending => "desc",
+                    };
+                    format!("{} {}", by, direction)
+                })
+                .collect();
+            clauses.push(format!("order by {}", orders.join(", ")));
+        }
+
+        if let Some(cursor) = self.results_after() {
+            clauses.push(format!("after {:?}", cursor.to_string()));
+        }
+
+        if self.results_offset() > 0 {
+            clauses.push(format!("offset {}", self.results_offset()));
+        }
+
+        if let Some(limit) = self.results_limit() {
+            clauses.push(format!("limit {}", limit));
+        }
+
+        write!(f, "{}", clauses.join(" "))
+    }
+}
+
+/// Displays the filter as text that can be parsed back into a filter,
+/// unless the filter contains a lambda predicate
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(p) => write!(f, "id {}", Pred(p.as_untyped(), IntegerType::Id)),
+            Self::Type(p) => write!(f, "type {}", Pred(p.as_untyped(), IntegerType::Signed)),
+            Self::Created(p) => {
+                write!(f, "created {}", Pred(p.as_untyped(), IntegerType::Unsigned))
+            }
+            Self::LastUpdated(p) => write!(
+                f,
+                "last_updated {}",
+                Pred(p.as_untyped(), IntegerType::Unsigned)
+            ),
+            Self::Field(name, p) => {
+                write!(f, "field({}) {}", Name(name), Pred(p, IntegerType::Signed))
+            }
+            Self::Edge(name, filter) => write!(f, "edge({}).{}", Name(name), Operand(filter)),
+            Self::EdgeCount(name, p) => write!(
+                f,
+                "edge_count({}) {}",
+                Name(name),
+                Pred(p.as_untyped(), IntegerType::Id)
+            ),
+            Self::Search(name, text) => write!(f, "search({}) {:?}", Name(name), text),
+            Self::IntoEdge(name) => write!(f, "into_edge({})", Name(name)),
+            Self::EdgeRecursive(name, max_depth, filter) => write!(
+                f,
+                "edge_recursive({}).{}",
+                NameAndDepth(name, *max_depth),
+                Operand(filter)
+            ),
+            Self::IntoEdgeRecursive(name, max_depth) => {
+                write!(f, "into_edge_recursive({})", NameAndDepth(name, *max_depth))
+            }
+
+            // Chains of two or more filters are written using infix operators
+            // while any other total requires a call-like form
+            Self::And(filters) | Self::Or(filters) => {
+                let op = if let Self::And(_) = self { "and" } else { "or" };
+                if filters.len() >= 2 {
+                    let filters: Vec<String> =
+                        filters.iter().map(|x| Operand(x).to_string()).collect();
+                    write!(f, "{}", filters.join(&format!(" {} ", op)))
+                } else {
+                    let filters: Vec<String> = filters.iter().map(|x| x.to_string()).collect();
+                    write!(f, "{}({})", op, filters.join(", "))
+                }
+            }
+            Self::Not(filter) => write!(f, "not {}", Operand(filter)),
+        }
+    }
+}
+
+/// Displays the predicate as text that can be parsed back into a predicate,
+/// unless the predicate is or contains a lambda predicate
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Pred(self, IntegerType::Signed))
+    }
+}
+
+/// Displays a filter that is used within another filter, surrounding it
+/// with parentheses if it is written using infix operators
+struct Operand<'a>(&'a Filter);
+
+impl<'a> fmt::Display for Operand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Filter::And(x) | Filter::Or(x) if x.len() >= 2 => write!(f, "({})", self.0),
+            filter => write!(f, "{}", filter),
+        }
+    }
+}
+
+/// Displays the name of a field or edge, quoting it if needed
+struct Name<'a>(&'a str);
+
+impl<'a> fmt::Display for Name<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_ident(self.0) {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "{:?}", self.0)
+        }
+    }
+}
+
+/// Displays the name of an edge followed by the most hops to follow, if any
+struct NameAndDepth<'a>(&'a str, Option<usize>);
+
+impl<'a> fmt::Display for NameAndDepth<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(max_depth) => write!(f, "{}, {}", Name(self.0), max_depth),
+            None => write!(f, "{}", Name(self.0)),
+        }
+    }
+}
+
+/// Displays a predicate where integers of the given type are written
+/// without a suffix
+struct Pred<'a>(&'a Predicate, IntegerType);
+
+impl<'a> fmt::Display for Pred<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ints = self.1;
+        let value = |v: &'a Value| Val(v, ints);
+        let values = |vs: &'a [Value]| vals(vs, ints).join(", ");
+        let preds = |ps: &[Predicate]| {
+            ps.iter()
+                .map(|p| Pred(p, ints).to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        match self.0 {
+            Predicate::Always => write!(f, "always"),
+            Predicate::Never => write!(f, "never"),
+            Predicate::And(ps) => write!(f, "and({})", preds(ps)),
+            Predicate::Any(p) => write!(f, "any({})", Pred(p, ints)),
+            Predicate::Contains(v) => write!(f, "contains {}", value(v)),
+            Predicate::ContainsAll(vs) => {
+                write!(f, "contains_all ({})", values(vs))
+            }
+            Predicate::ContainsAny(vs) => {
+                write!(f, "contains_any ({})", values(vs))
+            }
+            Predicate::Equals(v) => write!(f, "= {}", value(v)),
+            Predicate::GreaterThan(v) => write!(f, "> {}", value(v)),
+            Predicate::GreaterThanOrEquals(v) => write!(f, ">= {}", value(v)),
+            Predicate::HasKey(k) => write!(f, "has_key {:?}", k),
+            Predicate::HasKeyWhereValue(k, p) => {
+                write!(f, "has_key {:?} where {}", k, Pred(p, ints))
+            }
+            Predicate::InRange(r) => write!(f, "in {}..={}", value(r.start()), value(r.end())),
+            Predicate::InSet(vs) => write!(f, "in ({})", sorted(vals(vs, ints))),
+            Predicate::IsNone => write!(f, "is_none"),
+            Predicate::Lambda(_) => write!(f, "lambda"),
+            Predicate::LessThan(v) => write!(f, "< {}", value(v)),
+            Predicate::LessThanOrEquals(v) => write!(f, "<= {}", value(v)),
+            Predicate::Not(p) => write!(f, "not({})", Pred(p, ints)),
+            Predicate::NotEquals(v) => write!(f, "!= {}", value(v)),
+            Predicate::NotInRange(r) => {
+                write!(f, "not_in {}..={}", value(r.start()), value(r.end()))
+            }
+            Predicate::NotInSet(vs) => {
+                write!(f, "not_in ({})", sorted(vals(vs, ints)))
+            }
+            Predicate::NotNoneAnd(p) => write!(f, "not_none_and({})", Pred(p, ints)),
+            Predicate::NoneOr(p) => write!(f, "none_or({})", Pred(p, ints)),
+            Predicate::Or(ps) => write!(f, "or({})", preds(ps)),
+            Predicate::TextContainedIn(s) => write!(f, "contained_in {:?}", s),
+            Predicate::TextContainedInCaseInsensitive(s) => {
+                write!(f, "contained_in_case_insensitive {:?}", s)
+            }
+            Predicate::TextContainsAll(ss) => write!(f, "contains_all_text ({})", texts(ss)),
+            Predicate::TextContainsAllCaseInsensitive(ss) => {
+                write!(f, "contains_all_text_case_insensitive ({})", texts(ss))
+            }
+            Predicate::TextContainsAny(ss) => write!(f, "contains_any_text ({})", texts(ss)),
+            Predicate::TextContainsAnyCaseInsensitive(ss) => {
+                write!(f, "contains_any_text_case_insensitive ({})", texts(ss))
+            }
+            Predicate::TextEndsWith(s) => write!(f, "ends_with {:?}", s),
+            Predicate::TextEndsWithCaseInsensitive(s) => {
+                write!(f, "ends_with_case_insensitive {:?}", s)
+            }
+            Predicate::TextEndsWithAny(ss) => write!(f, "ends_with_any ({})", texts(ss)),
+            Predicate::TextEndsWithAnyCaseInsensitive(ss) => {
+                write!(f, "ends_with_any_case_insensitive ({})", texts(ss))
+            }
+            Predicate::TextEqualsCaseInsensitive(s) => {
+                write!(f, "equals_case_insensitive {:?}", s)
+            }
+            Predicate::TextNotEqualsCaseInsensitive(s) => {
+                write!(f, "not_equals_case_insensitive {:?}", s)
+            }
+            Predicate::TextFuzzyMatches(s, max) => {
+                write!(f, "fuzzy_matches {:?} within {}", s, max)
+            }
+            #[cfg(feature = "regex")]
+            Predicate::TextMatchesRegex(x) => write!(f, "matches_regex {:?}", x.as_str()),
+            Predicate::TextInSetCaseInsensitive(ss) => {
+                let mut ss: Vec<&String> = ss.iter().collect();
+                ss.sort();
+                write!(f, "in_case_insensitive ({})", texts(ss))
+            }
+            Predicate::TextStartsWith(s) => write!(f, "starts_with {:?}", s),
+            Predicate::TextStartsWithCaseInsensitive(s) => {
+                write!(f, "starts_with_case_insensitive {:?}", s)
+            }
+            Predicate::TextStartsWithAny(ss) => write!(f, "starts_with_any ({})", texts(ss)),
+            Predicate::TextStartsWithAnyCaseInsensitive(ss) => {
+                write!(f, "starts_with_any_case_insensitive ({})", texts(ss))
+            }
+            Predicate::Xor(ps) => write!(f, "xor({})", preds(ps)),
+        }
+    }
+}
+
+/// Displays a value where integers of the given type are written without
+/// a suffix
+struct Val<'a>(&'a Value, IntegerType);
+
+impl<'a> fmt::Display for Val<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ints = self.1;
+        match self.0 {
+            Value::List(x) => {
+                let values: Vec<String> = x.iter().map(|v| Val(v, ints).to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Value::Map(x) => {
+                let mut entries: Vec<(&String, &Value)> = x.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                let entries: Vec<String> = entries
+                    .into_iter()
+                    .map(|(k, v)| format!("{:?}: {}", k, Val(v, ints)))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Value::Optional(None) => write!(f, "none"),
+            Value::Optional(Some(x)) => write!(f, "some({})", Val(x, ints)),
+            Value::Primitive(PrimitiveValue::Bool(x)) => write!(f, "{}", x),
+            Value::Primitive(PrimitiveValue::Char(x)) => write!(f, "{:?}", x),
+            Value::Primitive(PrimitiveValue::Number(x)) => fmt_number(f, *x, ints),
+            Value::Primitive(PrimitiveValue::Unit) => write!(f, "unit"),
+            Value::Text(x) => write!(f, "{:?}", x),
+        }
+    }
+}
+
+/// Writes a number followed by its type unless the type is implied,
+/// which is the case for f64 and the given integer type
+fn fmt_number(f: &mut fmt::Formatter<'_>, n: Number, ints: IntegerType) -> fmt::Result {
+    macro_rules! float {
+        ($x:expr, $suffix:expr) => {{
+            if $x.is_nan() {
+                write!(f, "nan{}", $suffix)
+            } else if $x.is_infinite() {
+                let sign = if $x < 0.0 { "-" } else { "" };
+                write!(f, "{}inf{}", sign, $suffix)
+            } else {
+                write!(f, "{:?}{}", $x, $suffix)
+            }
+        }};
+    }
+
+    let implied = match ints {
+        IntegerType::Signed => matches!(n, Number::I64(_)),
+        IntegerType::Id => matches!(n, Number::Usize(_)),
+        IntegerType::Unsigned => matches!(n, Number::U64(_)),
+    };
+
+    match n {
+        Number::F32(x) => float!(x, "f32"),
+        Number::F64(x) => float!(x, ""),
+        Number::I128(x) => write!(f, "{}", x),
+        Number::I16(x) => write!(f, "{}", x),
+        Number::I32(x) => write!(f, "{}", x),
+        Number::I64(x) => write!(f, "{}", x),
+        Number::I8(x) => write!(f, "{}", x),
+        Number::Isize(x) => write!(f, "{}", x),
+        Number::U128(x) => write!(f, "{}", x),
+        Number::U16(x) => write!(f, "{}", x),
+        Number::U32(x) => write!(f, "{}", x),
+        Number::U64(x) => write!(f, "{}", x),
+        Number::U8(x) => write!(f, "{}", x),
+        Number::Usize(x) => write!(f, "{}", x),
+    }?;
+
+    if implied || n.is_float() {
+        Ok(())
+    } else {
+        write!(f, "{}", n.to_type())
+    }
+}
+
+fn vals<'a, I: IntoIterator<Item = &'a Value>>(i: I, ints: IntegerType) -> Vec<String> {
+    i.into_iter().map(|v| Val(v, ints).to_string()).collect()
+}
+
+fn texts<I: IntoIterator<Item = S>, S: AsRef<str>>(i: I) -> String {
+    i.into_iter()
+        .map(|s| format!("{:?}", s.as_ref()))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Joins values in a consistent order, used for sets that have no order
+fn sorted(mut values: Vec<String>) -> String {
+    values.sort();
+    values.join(", ")
+}
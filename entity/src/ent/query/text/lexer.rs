@@ -0,0 +1,337 @@
+use super::ParseQueryError;
+use std::{fmt, ops::Range};
+
+/// Represents a single token of query text alongside the position of the
+/// token within the text
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+/// Represents the different kinds of tokens found in query text
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    /// Word such as a keyword or the name of a field or edge
+    Ident(String),
+
+    /// Double-quoted text with escapes resolved
+    Text(String),
+
+    /// Single-quoted character with escapes resolved
+    Char(char),
+
+    /// Number that is not yet converted into a specific type as the default
+    /// type depends on where the number is used
+    Number(NumberLiteral),
+
+    /// Punctuation such as brackets, commas, and comparison operators
+    Symbol(&'static str),
+
+    /// Marks the end of the text
+    End,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(x) => write!(f, "`{}`", x),
+            Self::Text(x) => write!(f, "text {:?}", x),
+            Self::Char(x) => write!(f, "character {:?}", x),
+            Self::Number(x) => write!(f, "number `{}`", x),
+            Self::Symbol(x) => write!(f, "`{}`", x),
+            Self::End => write!(f, "end of query"),
+        }
+    }
+}
+
+/// Represents a number as written, comprised of its digits (including sign,
+/// decimal point, and exponent) and an optional type suffix such as `u8`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumberLiteral {
+    pub digits: String,
+    pub suffix: Option<String>,
+}
+
+impl NumberLiteral {
+    /// Returns true if the digits can only describe a floating point number
+    pub fn is_float(&self) -> bool {
+        self.digits.contains(&['.', 'e', 'E', 'n', 'i'][..])
+    }
+}
+
+impl fmt::Display for NumberLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.digits)?;
+        if let Some(suffix) = self.suffix.as_ref() {
+            write!(f, "{}", suffix)?;
+        }
+        Ok(())
+    }
+}
+
+/// Symbols ordered such that longer symbols are matched before any shorter
+/// symbol that is a prefix of them
+const SYMBOLS: &[&str] = &[
+    "..=", "!=", ">=", "<=", "=", ">", "<", "(", ")", "[", "]", "{", "}", ",", ":", ".",
+];
+
+/// Splits query text into tokens, always ending with a token marking the
+/// end of the text
+pub fn tokenize(text: &str) -> Result<Vec<Token>, ParseQueryError> {
+    let mut lexer = Lexer { text, pos: 0 };
+    let mut tokens = Vec::new();
+
+    loop {
+        let token = lexer.next_token()?;
+        let is_end = token.kind == TokenKind::End;
+        tokens.push(token);
+
+        if is_end {
+            break;
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.rest().chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if f(c)) {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn next_token(&mut self) -> Result<Token, ParseQueryError> {
+        self.eat_while(char::is_whitespace);
+
+        let start = self.pos;
+        let kind = match self.peek() {
+            None => TokenKind::End,
+            Some(c) if is_ident_start(c) => {
+                TokenKind::Ident(self.eat_while(is_ident_continue).to_string())
+            }
+            Some(c) if c.is_ascii_digit() => TokenKind::Number(self.number()?),
+            Some('-') if matches!(self.peek_second(), Some(c) if c.is_ascii_digit()) => {
+                TokenKind::Number(self.number()?)
+            }
+            Some('-') if self.rest()[1..].starts_with("inf") => TokenKind::Number(self.number()?),
+            Some('"') => TokenKind::Text(self.text()?),
+            Some('\'') => TokenKind::Char(self.char()?),
+            Some(c) => match SYMBOLS.iter().find(|s| self.rest().starts_with(*s)) {
+                Some(symbol) => {
+                    self.pos += symbol.len();
+                    TokenKind::Symbol(symbol)
+                }
+                None => {
+                    self.bump();
+                    return Err(ParseQueryError::new(
+                        start..self.pos,
+                        format!("unexpected character {:?}", c),
+                    ));
+                }
+            },
+        };
+
+        // Words that begin like numbers such as inf and nan are floats
+        let kind = match kind {
+            TokenKind::Ident(x) if is_special_float(&x) => {
+                let (digits, suffix) = split_special_float(&x);
+                TokenKind::Number(NumberLiteral { digits, suffix })
+            }
+            kind => kind,
+        };
+
+        Ok(Token {
+            kind,
+            span: start..self.pos,
+        })
+    }
+
+    fn number(&mut self) -> Result<NumberLiteral, ParseQueryError> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.bump();
+        }
+
+        // Negative infinity is the only special float that can be negated
+        if self.rest().starts_with("inf") {
+            let word = self.eat_while(is_ident_continue);
+            if !is_special_float(word) {
+                return Err(ParseQueryError::new(
+                    start..self.pos,
+                    format!("invalid number `-{}`", word),
+                ));
+            }
+            let (digits, suffix) = split_special_float(word);
+            return Ok(NumberLiteral {
+                digits: format!("-{}", digits),
+                suffix,
+            });
+        }
+
+        self.eat_while(|c| c.is_ascii_digit());
+
+        // Only consume a decimal point when followed by a digit so ranges
+        // like 1..=5 are not mistaken for floats
+        if self.peek() == Some('.') && matches!(self.peek_second(), Some(c) if c.is_ascii_digit()) {
+            self.bump();
+            self.eat_while(|c| c.is_ascii_digit());
+        }
+
+        if matches!(self.peek(), Some('e') | Some('E')) {
+            let after = &self.rest()[1..];
+            let after = after
+                .strip_prefix('-')
+                .or_else(|| after.strip_prefix('+'))
+                .unwrap_or(after);
+            if after.starts_with(|c: char| c.is_ascii_digit()) {
+                self.bump();
+                if matches!(self.peek(), Some('-') | Some('+')) {
+                    self.bump();
+                }
+                self.eat_while(|c| c.is_ascii_digit());
+            }
+        }
+
+        let digits = self.text[start..self.pos].to_string();
+        let suffix = self.eat_while(is_ident_continue);
+        let suffix = if suffix.is_empty() {
+            None
+        } else {
+            Some(suffix.to_string())
+        };
+
+        Ok(NumberLiteral { digits, suffix })
+    }
+
+    fn text(&mut self) -> Result<String, ParseQueryError> {
+        let start = self.pos;
+        self.bump();
+
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape()?),
+                Some(c) => s.push(c),
+                None => return Err(ParseQueryError::new(start..self.pos, "unterminated text")),
+            }
+        }
+    }
+
+    fn char(&mut self) -> Result<char, ParseQueryError> {
+        let start = self.pos;
+        self.bump();
+
+        let c = match self.bump() {
+            Some('\\') => self.escape()?,
+            Some('\'') | None => {
+                return Err(ParseQueryError::new(
+                    start..self.pos,
+                    "expected a character",
+                ))
+            }
+            Some(c) => c,
+        };
+
+        match self.bump() {
+            Some('\'') => Ok(c),
+            _ => Err(ParseQueryError::new(
+                start..self.pos,
+                "unterminated character",
+            )),
+        }
+    }
+
+    /// Resolves the escape following a backslash
+    fn escape(&mut self) -> Result<char, ParseQueryError> {
+        let start = self.pos - 1;
+        let c = match self.bump() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') if self.peek() == Some('{') => {
+                self.bump();
+                let hex = self.eat_while(|c| c.is_ascii_hexdigit());
+                let c = u32::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(std::char::from_u32);
+                match (c, self.bump()) {
+                    (Some(c), Some('}')) => c,
+                    _ => {
+                        return Err(ParseQueryError::new(
+                            start..self.pos,
+                            "invalid unicode escape",
+                        ))
+                    }
+                }
+            }
+            _ => return Err(ParseQueryError::new(start..self.pos, "invalid escape")),
+        };
+        Ok(c)
+    }
+}
+
+/// Returns true if the given text is a valid identifier, meaning that it
+/// can be written in a query without quotes
+pub fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if is_ident_start(c) => chars.all(is_ident_continue) && !is_special_float(s),
+        _ => false,
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Returns true if the word is infinity or not-a-number, optionally with a
+/// float suffix
+fn is_special_float(s: &str) -> bool {
+    matches!(s, "inf" | "inff32" | "inff64" | "nan" | "nanf32" | "nanf64")
+}
+
+fn split_special_float(s: &str) -> (String, Option<String>) {
+    let (digits, suffix) = s.split_at(3);
+    let suffix = if suffix.is_empty() {
+        None
+    } else {
+        Some(suffix.to_string())
+    };
+    (digits.to_string(), suffix)
+}
@@ -0,0 +1,594 @@
+use crate::{Filter, Predicate, Query};
+use std::{fmt, ops::Range, str::FromStr};
+
+mod display;
+mod lexer;
+mod parser;
+
+use parser::Parser;
+
+/// Represents the type given to integers written without a suffix, which
+/// depends on where in a query the integer appears
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum IntegerType {
+    /// Integers are i64, used for field values
+    Signed,
+
+    /// Integers are usize, used for ids and edge counts
+    Id,
+
+    /// Integers are u64, used for timestamps
+    Unsigned,
+}
+
+impl FromStr for Query {
+    type Err = ParseQueryError;
+
+    /// Parses a query from text, such as text produced by displaying a query
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let query = parser.query()?;
+        parser.finish()?;
+        Ok(query)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseQueryError;
+
+    /// Parses a filter from text, such as text produced by displaying a
+    /// filter
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let filter = parser.filter()?;
+        parser.finish()?;
+        Ok(filter)
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = ParseQueryError;
+
+    /// Parses a predicate from text, such as text produced by displaying a
+    /// predicate, where integers without a suffix are i64
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s)?;
+        let predicate = parser.predicate(IntegerType::Signed)?;
+        parser.finish()?;
+        Ok(predicate)
+    }
+}
+
+/// Represents an error that occurs when parsing text that is not a valid
+/// query, filter, or predicate
+///
+/// ### Examples
+///
+/// ```
+/// use entity::Query;
+///
+/// let text = "type = \"app::User\" and field(age) >";
+/// let err = text.parse::<Query>().unwrap_err();
+/// assert_eq!(err.span(), 35..35);
+/// assert_eq!(err.to_string(), "expected a value, found end of query at 35..35");
+///
+/// // Point at the problem within the original text
+/// assert_eq!(
+///     err.render(text),
+///     concat!(
+///         "error: expected a value, found end of query\n",
+///         " --> 1:36\n",
+///         "  |\n",
+///         "1 | type = \"app::User\" and field(age) >\n",
+///         "  |                                    ^\n",
+///     ),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseQueryError {
+    span: Range<usize>,
+    message: String,
+}
+
+impl ParseQueryError {
+    fn new<S: Into<String>>(span: Range<usize>, message: S) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the byte range within the parsed text where the error occurred
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns a description of the error without its position
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Produces a multi-line description of the error that includes the
+    /// line of the given text (which should be the text that was parsed)
+    /// where the error occurred, underlining the span of the error
+    pub fn render(&self, text: &str) -> String {
+        let start = floor_char_boundary(text, self.span.start);
+        let end = floor_char_boundary(text, self.span.end).max(start);
+
+        let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+        let line = &text[line_start..line_end];
+        let line_no = text[..start].matches('\n').count() + 1;
+        let column = text[line_start..start].chars().count() + 1;
+
+        // Underline at least one character, even at the end of the text,
+        // and never past the end of the line
+        let width = text[start..end.min(line_end)].chars().count().max(1);
+        let gutter = " ".repeat(line_no.to_string().len());
+
+        format!(
+            "error: {}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self.message,
+            gutter,
+            line_no,
+            column,
+            gutter,
+            line_no,
+            line,
+            gutter,
+            " ".repeat(column - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl fmt::Display for ParseQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseQueryError {}
+
+/// Moves the index backward until it lands on a character boundary within
+/// the text, clamping it to the end of the text
+fn floor_char_boundary(text: &str, mut i: usize) -> usize {
+    i = i.min(text.len());
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Cursor, Field, Number, Order, OrderBy, OrderDirection, PrimitiveValue, TypedPredicate,
+        UntypedEnt, Value,
+    };
+
+    /// Parses the text, displays the result, and checks that the displayed
+    /// text is unchanged, which also verifies the types of all numbers
+    fn assert_round_trip<T: FromStr<Err = ParseQueryError> + fmt::Display>(text: &str) -> T {
+        let parsed = text
+            .parse::<T>()
+            .unwrap_or_else(|x| panic!("{}", x.render(text)));
+        assert_eq!(parsed.to_string(), text);
+        parsed
+    }
+
+    #[test]
+    fn query_should_parse_filters_separated_by_and_in_order() {
+        let query: Query = "type = \"app::User\" and field(age) > 30 and \
+            edge(friends).field(name) starts_with \"A\""
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            query.filters(),
+            &[
+                Filter::where_type(TypedPredicate::equals(String::from("app::User"))),
+                Filter::where_field("age", Predicate::GreaterThan(Value::from(30i64))),
+                Filter::where_edge(
+                    "friends",
+                    Filter::where_field("name", Predicate::text_starts_with("A"))
+                ),
+            ]
+        );
+        assert!(query.orders().is_empty());
+        assert_eq!(query.results_after(), None);
+        assert_eq!(query.results_offset(), 0);
+        assert_eq!(query.results_limit(), None);
+    }
+
+    #[test]
+    fn query_should_parse_ordering_cursor_offset_and_limit() {
+        let orders = vec![
+            Order::new(
+                OrderBy::Field(String::from("age")),
+                OrderDirection::Descending,
+            ),
+            Order::new(OrderBy::Created, OrderDirection::Ascending),
+        ];
+        let ent = UntypedEnt::from_collections(5, vec![Field::new("age", 3u8)], vec![]);
+        let cursor = Cursor::from_ent(&orders, &ent);
+
+        let text = format!(
+            "id > 3 order by field(age) desc, created asc after {:?} offset 10 limit 20",
+            cursor.to_string()
+        );
+        let query: Query = assert_round_trip(&text);
+
+        assert_eq!(
+            query.filters(),
+            &[Filter::where_id(TypedPredicate::greater_than(3))]
+        );
+        assert_eq!(query.orders(), orders.as_slice());
+        assert_eq!(query.results_after(), Some(&cursor));
+        assert_eq!(query.results_offset(), 10);
+        assert_eq!(query.results_limit(), Some(20));
+
+        let query: Query = assert_round_trip("order by relevance(bio, \"rust go\") desc");
+        assert_eq!(
+            query.orders(),
+            &[Order::new(
+                OrderBy::Relevance(String::from("bio"), String::from("rust go")),
+                OrderDirection::Descending,
+            )]
+        );
+
+        // Direction defaults to ascending and clauses can be used alone
+        let query: Query = "order by id limit 1".parse().unwrap();
+        assert!(query.filters().is_empty());
+        assert_eq!(
+            query.orders(),
+            &[Order::new(OrderBy::Id, OrderDirection::Ascending)]
+        );
+        assert_eq!(query.results_limit(), Some(1));
+
+        let query: Query = "".parse().unwrap();
+        assert!(query.filters().is_empty());
+        assert_eq!(query.to_string(), "");
+    }
+
+    #[test]
+    fn filter_should_bind_and_tighter_than_or() {
+        let filter: Filter = "id = 1 or id = 2 and not id = 3".parse().unwrap();
+        assert_eq!(
+            filter,
+            Filter::or(vec![
+                Filter::where_id(TypedPredicate::equals(1)),
+                Filter::and(vec![
+                    Filter::where_id(TypedPredicate::equals(2)),
+                    Filter::not(Filter::where_id(TypedPredicate::equals(3))),
+                ]),
+            ])
+        );
+        assert_eq!(filter.to_string(), "id = 1 or (id = 2 and not id = 3)");
+
+        // Top-level query filters are kept separate, but grouped filters
+        // and filters combined with or are not
+        let query: Query = "id = 1 and (id = 2 and id = 3) and (id = 4 or id = 5)"
+            .parse()
+            .unwrap();
+        assert_eq!(query.filters().len(), 3);
+        assert!(matches!(&query.filters()[1], Filter::And(x) if x.len() == 2));
+        assert!(matches!(&query.filters()[2], Filter::Or(x) if x.len() == 2));
+
+        let query: Query = "id = 1 or id = 2".parse().unwrap();
+        assert_eq!(query.filters().len(), 1);
+        assert_eq!(query.to_string(), "(id = 1 or id = 2)");
+    }
+
+    #[test]
+    fn filter_should_round_trip_every_variant() {
+        for text in &[
+            "id in (1, 2, 3)",
+            "type = \"app::User\"",
+            "created >= 1000",
+            "last_updated < 99999",
+            "field(age) in 18..=65",
+            "field(\"first name\") equals_case_insensitive \"ALICE\"",
+            "edge(friends).edge(\"best friend\").into_edge(pets)",
+            "edge(friends).(field(a) = 1 or field(b) = 2)",
+            "edge(friends).not field(a) = 1",
+            "into_edge(friends)",
+            "edge_count(friends) > 5",
+            "edge_count(\"best friend\") in (0, 1)",
+            "search(bio) \"rust or go\"",
+            "search(\"full name\") \"Alice\"",
+            "edge_recursive(children).field(a) = 1",
+            "edge_recursive(\"best friend\", 3).not id = 1",
+            "into_edge_recursive(children)",
+            "into_edge_recursive(children, 0)",
+            "not (id = 1 and id = 2)",
+            "not not id = 1",
+            "field(a) = 1 and field(b) = 2 and field(c) = 3",
+            "field(a) = 1 or (field(b) = 2 and field(c) = 3)",
+            "(field(a) = 1 or field(b) = 2) and field(c) = 3",
+            "id = 1 and (id = 2 and id = 3)",
+            "and()",
+            "or(id = 1)",
+            "and(id = 1 and id = 2)",
+            "id = 1 and or() and and(id = 2 or id = 3)",
+        ] {
+            assert_round_trip::<Filter>(text);
+        }
+    }
+
+    #[test]
+    fn predicate_should_round_trip_every_variant() {
+        for text in &[
+            "always",
+            "never",
+            "and(> 1, < 5)",
+            "any(= \"a\")",
+            "contains -5i32",
+            "contains_all (1.5, 'c')",
+            "contains_any (true, none)",
+            "= [1u16, 2u16, 3u16]",
+            "> 999u64",
+            ">= -999",
+            "has_key \"key\"",
+            "has_key \"key\" where is_none",
+            "in 1u32..=5u32",
+            "in (\"a\", \"b\")",
+            "is_none",
+            "< 0.25f32",
+            "<= some(7i8)",
+            "not(never)",
+            "!= \"text\"",
+            "not_in -3i16..=3i16",
+            "not_in (1u8, 2u8)",
+            "not_none_and(= 1u8)",
+            "none_or(= 1u8)",
+            "or(never, always)",
+            "contained_in \"abc\"",
+            "contained_in_case_insensitive \"ABC\"",
+            "contains_all_text (\"a\", \"b\")",
+            "contains_all_text_case_insensitive (\"A\")",
+            "contains_any_text (\"a\", \"b\")",
+            "contains_any_text_case_insensitive ()",
+            "ends_with \"z\"",
+            "ends_with_case_insensitive \"Z\"",
+            "ends_with_any (\"y\", \"z\")",
+            "ends_with_any_case_insensitive (\"Z\")",
+            "equals_case_insensitive \"Text\"",
+            "not_equals_case_insensitive \"Text\"",
+            "fuzzy_matches \"kitten\" within 2",
+            "in_case_insensitive (\"B\", \"a\")",
+            "starts_with \"a\"",
+            "starts_with_case_insensitive \"A\"",
+            "starts_with_any (\"a\", \"b\")",
+            "starts_with_any_case_insensitive (\"A\")",
+            "xor(always, never)",
+        ] {
+            assert_round_trip::<Predicate>(text);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn predicate_should_round_trip_regex_as_written() {
+        let p: Predicate = assert_round_trip("matches_regex \"^\\\\d+\\\\.\\\\d*$\"");
+        assert!(p.check(&Value::from("12.5")));
+        assert!(!p.check(&Value::from("12")));
+
+        let err = "matches_regex \"(\"".parse::<Predicate>().unwrap_err();
+        assert!(
+            err.message().contains("regex parse error"),
+            "{}",
+            err.message()
+        );
+        assert_eq!(err.span(), 14..17);
+    }
+
+    #[test]
+    fn predicate_should_round_trip_every_kind_of_value() {
+        for text in &[
+            "= \"quote \\\" and \\\\ and \\n and \\u{1b}\"",
+            "= '\\''",
+            "= true",
+            "= false",
+            "= unit",
+            "= none",
+            "= some(some(none))",
+            "= []",
+            "= [1, [2, 3], \"four\"]",
+            "= {}",
+            "= {\"a\": 1, \"b\": {\"c\": [true]}}",
+            "= 1.0",
+            "= -1e-7",
+            "= 1e20f32",
+            "= inf",
+            "= -inff32",
+            "= nan",
+            "= 170141183460469231731687303715884105727i128",
+            "= 340282366920938463463374607431768211455u128",
+            "= 5isize",
+            "= 5usize",
+        ] {
+            assert_round_trip::<Predicate>(text);
+        }
+    }
+
+    #[test]
+    fn integers_without_suffix_should_use_type_of_their_position() {
+        let filter: Filter = "id = 3".parse().unwrap();
+        assert!(matches!(
+            filter,
+            Filter::Id(ref p) if matches!(
+                p.as_untyped(),
+                Predicate::Equals(Value::Primitive(PrimitiveValue::Number(Number::Usize(3))))
+            )
+        ));
+
+        let filter: Filter = "field(a) = 3".parse().unwrap();
+        assert!(matches!(
+            filter,
+            Filter::Field(
+                _,
+                Predicate::Equals(Value::Primitive(PrimitiveValue::Number(Number::I64(3))))
+            )
+        ));
+
+        // Other types are written with a suffix in either position
+        let filter = Filter::where_field("a", Predicate::equals(3u64));
+        assert_eq!(filter.to_string(), "field(a) = 3u64");
+        let filter = Filter::where_created(TypedPredicate::equals(3));
+        assert_eq!(filter.to_string(), "created = 3");
+        let filter = Filter::where_last_updated(TypedPredicate::new(Predicate::equals(3i64)));
+        assert_eq!(filter.to_string(), "last_updated = 3i64");
+    }
+
+    #[test]
+    fn display_should_order_sets_and_maps_consistently() {
+        let p = Predicate::InSet(
+            vec![Value::from(3), Value::from(1), Value::from(2)]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(p.to_string(), "in (1i32, 2i32, 3i32)");
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(String::from("b"), 2i64);
+        map.insert(String::from("a"), 1i64);
+        map.insert(String::from("c"), 3i64);
+        assert_eq!(
+            Predicate::equals(map).to_string(),
+            "= {\"a\": 1, \"b\": 2, \"c\": 3}"
+        );
+    }
+
+    #[test]
+    fn display_should_write_lambda_as_text_that_fails_to_parse() {
+        let filter = Filter::where_field("a", Predicate::lambda(|_| true));
+        let text = filter.to_string();
+        assert_eq!(text, "field(a) lambda");
+
+        let err = text.parse::<Filter>().unwrap_err();
+        assert_eq!(err.message(), "lambda predicates cannot be written as text");
+        assert_eq!(err.span(), 9..15);
+    }
+
+    #[test]
+    fn parse_should_fail_with_span_of_problem() {
+        fn err(text: &str) -> (String, Range<usize>) {
+            let err = text.parse::<Query>().unwrap_err();
+            (err.message().to_string(), err.span())
+        }
+
+        assert_eq!(
+            err("field(a) = 3u9"),
+            (String::from("unknown number suffix `u9`"), 11..14)
+        );
+        assert_eq!(
+            err("field(a) = 300u8"),
+            (String::from("number `300u8` is not a valid u8"), 11..16)
+        );
+        assert_eq!(
+            err("id = -1"),
+            (String::from("number `-1` is not a valid usize"), 5..7)
+        );
+        assert_eq!(
+            err("field(a) = 1.5i32"),
+            (String::from("number `1.5i32` is not a valid i32"), 11..17)
+        );
+        assert_eq!(
+            err("field(a) = \"abc"),
+            (String::from("unterminated text"), 11..15)
+        );
+        assert_eq!(
+            err("field(a) = \"\\q\""),
+            (String::from("invalid escape"), 12..14)
+        );
+        assert_eq!(
+            err("field(a) = 1 #"),
+            (String::from("unexpected character '#'"), 13..14)
+        );
+        assert_eq!(
+            err("field(a) = 1 field(b) = 2"),
+            (
+                String::from(
+                    "expected `and`, `or`, `order`, `after`, `offset`, or `limit`, found `field`"
+                ),
+                13..18
+            )
+        );
+        assert_eq!(
+            err("field(a) fuzzy_matches \"a\" 2"),
+            (String::from("expected `within`, found number `2`"), 27..28)
+        );
+        assert_eq!(
+            err("field(a) bigger 3"),
+            (String::from("expected a predicate, found `bigger`"), 9..15)
+        );
+        assert_eq!(
+            err("(id = 1"),
+            (String::from("expected `)`, found end of query"), 7..7)
+        );
+        assert_eq!(
+            err("wherever id = 1"),
+            (String::from("expected a filter, found `wherever`"), 0..8)
+        );
+        assert_eq!(
+            err("id = 1 order id"),
+            (String::from("expected `by`, found `id`"), 13..15)
+        );
+        assert_eq!(
+            err("id = 1 after \"abc\""),
+            (String::from("Invalid cursor"), 13..18)
+        );
+        assert_eq!(
+            err("id = 1 limit 5 offset 3"),
+            (
+                String::from("expected end of query, found `offset`"),
+                15..21
+            )
+        );
+    }
+
+    #[test]
+    fn parse_should_fail_when_nested_too_deeply() {
+        fn err(text: &str) -> (String, Range<usize>) {
+            let err = text.parse::<Query>().unwrap_err();
+            (err.message().to_string(), err.span())
+        }
+        let message = String::from("query is nested more than 64 levels deep");
+
+        assert_eq!(err(&"(".repeat(500)), (message.clone(), 64..65));
+        assert_eq!(err(&"not ".repeat(500)), (message.clone(), 256..259));
+        assert_eq!(
+            err(&format!("field(a) {}", "not(".repeat(500))),
+            (message.clone(), 261..264)
+        );
+        assert_eq!(
+            err(&format!("field(a) = {}", "[".repeat(500))),
+            (message, 73..74)
+        );
+
+        let text = format!("{}id = 1{}", "(".repeat(61), ")".repeat(61));
+        assert!(text.parse::<Query>().is_ok());
+    }
+
+    #[test]
+    fn render_should_underline_span_on_its_line() {
+        let text = "id = 1\nand field(\"ü\") = 3u9\nand id = 2";
+        let err = text.parse::<Query>().unwrap_err();
+        assert_eq!(
+            err.render(text),
+            concat!(
+                "error: unknown number suffix `u9`\n",
+                " --> 2:18\n",
+                "  |\n",
+                "2 | and field(\"ü\") = 3u9\n",
+                "  |                  ^^^\n",
+            )
+        );
+    }
+}
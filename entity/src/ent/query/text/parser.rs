@@ -0,0 +1,762 @@
+use super::{
+    lexer::{tokenize, NumberLiteral, Token, TokenKind},
+    IntegerType, ParseQueryError,
+};
+use crate::{
+    Cursor, Filter, Number, NumberType, Order, OrderBy, OrderDirection, Predicate, PrimitiveValue,
+    Query, TypedPredicate, Value,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    ops::{Neg, Range},
+    str::FromStr,
+};
+
+/// Keywords that begin the clauses following the filters of a query
+const CLAUSES: &[&str] = &["order", "after", "offset", "limit"];
+
+/// Maximum number of filters, predicates, and values that can be nested
+/// within one another, keeping deeply-nested text from exhausting the stack
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser that converts tokens of query text into queries,
+/// filters, and predicates
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    pub fn new(text: &str) -> Result<Self, ParseQueryError> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            pos: 0,
+            depth: 0,
+        })
+    }
+
+    /// Fails if any tokens remain other than the end of the text
+    pub fn finish(&self) -> Result<(), ParseQueryError> {
+        match self.peek().kind {
+            TokenKind::End => Ok(()),
+            _ => Err(self.unexpected("end of query")),
+        }
+    }
+
+    /// Parses the filters of a query, which are separated by `and` and
+    /// applied in order, followed by any ordering, cursor, offset, and limit
+    pub fn query(&mut self) -> Result<Query, ParseQueryError> {
+        let filters = if self.at_clause() || self.peek().kind == TokenKind::End {
+            Vec::new()
+        } else {
+            let mut chains = self.or_chains()?;
+            if chains.len() == 1 {
+                chains.remove(0)
+            } else {
+                vec![Filter::Or(chains.into_iter().map(from_chain).collect())]
+            }
+        };
+
+        if !self.at_clause() && self.peek().kind != TokenKind::End {
+            return Err(self.unexpected("`and`, `or`, `order`, `after`, `offset`, or `limit`"));
+        }
+
+        let mut query = Query::new(filters);
+
+        if self.eat_ident("order") {
+            self.expect_ident("by")?;
+            loop {
+                query = query.order_by(self.order()?);
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+
+        if self.eat_ident("after") {
+            let (text, span) = self.text()?;
+            let cursor = text
+                .parse::<Cursor>()
+                .map_err(|x| ParseQueryError::new(span, x.to_string()))?;
+            query = query.after(cursor);
+        }
+
+        if self.eat_ident("offset") {
+            query = query.offset(self.count()?);
+        }
+
+        if self.eat_ident("limit") {
+            query = query.limit(self.count()?);
+        }
+
+        Ok(query)
+    }
+
+    /// Parses a single filter, where filters separated by `and` or `or` are
+    /// combined into one filter
+    pub fn filter(&mut self) -> Result<Filter, ParseQueryError> {
+        let mut chains = self.or_chains()?;
+        if chains.len() == 1 {
+            Ok(from_chain(chains.remove(0)))
+        } else {
+            Ok(Filter::Or(chains.into_iter().map(from_chain).collect()))
+        }
+    }
+
+    /// Parses filters separated by `or`, where each is a chain of filters
+    /// separated by `and`, as `and` binds tighter than `or`
+    fn or_chains(&mut self) -> Result<Vec<Vec<Filter>>, ParseQueryError> {
+        let mut chains = vec![self.and_chain()?];
+        while self.eat_ident("or") {
+            chains.push(self.and_chain()?);
+        }
+        Ok(chains)
+    }
+
+    fn and_chain(&mut self) -> Result<Vec<Filter>, ParseQueryError> {
+        let mut chain = vec![self.unary_filter()?];
+        while self.eat_ident("and") {
+            chain.push(self.unary_filter()?);
+        }
+        Ok(chain)
+    }
+
+    fn unary_filter(&mut self) -> Result<Filter, ParseQueryError> {
+        self.nested(Self::nested_unary_filter)
+    }
+
+    fn nested_unary_filter(&mut self) -> Result<Filter, ParseQueryError> {
+        if self.eat_symbol("(") {
+            let filter = self.filter()?;
+            self.expect_symbol(")")?;
+            return Ok(filter);
+        }
+
+        let word = match &self.peek().kind {
+            TokenKind::Ident(x) => x.to_string(),
+            _ => return Err(self.unexpected("a filter")),
+        };
+
+        match word.as_str() {
+            "not" => {
+                self.advance();
+                Ok(Filter::Not(Box::new(self.unary_filter()?)))
+            }
+            "id" => {
+                self.advance();
+                Ok(Filter::Id(TypedPredicate::new(
+                    self.predicate(IntegerType::Id)?,
+                )))
+            }
+            "type" => {
+                self.advance();
+                Ok(Filter::Type(TypedPredicate::new(
+                    self.predicate(IntegerType::Signed)?,
+                )))
+            }
+            "created" => {
+                self.advance();
+                Ok(Filter::Created(TypedPredicate::new(
+                    self.predicate(IntegerType::Unsigned)?,
+                )))
+            }
+            "last_updated" => {
+                self.advance();
+                Ok(Filter::LastUpdated(TypedPredicate::new(
+                    self.predicate(IntegerType::Unsigned)?,
+                )))
+            }
+            "field" => {
+                self.advance();
+                let name = self.name()?;
+                Ok(Filter::Field(name, self.predicate(IntegerType::Signed)?))
+            }
+            "edge" => {
+                self.advance();
+                let name = self.name()?;
+                self.expect_symbol(".")?;
+                Ok(Filter::Edge(name, Box::new(self.unary_filter()?)))
+            }
+            "edge_count" => {
+                self.advance();
+                let name = self.name()?;
+                Ok(Filter::EdgeCount(
+                    name,
+                    TypedPredicate::new(self.predicate(IntegerType::Id)?),
+                ))
+            }
+            "search" => {
+                self.advance();
+                let name = self.name()?;
+                let (text, _) = self.text()?;
+                Ok(Filter::Search(name, text))
+            }
+            "into_edge" => {
+                self.advance();
+                Ok(Filter::IntoEdge(self.name()?))
+            }
+            "edge_recursive" => {
+                self.advance();
+                let (name, max_depth) = self.name_and_depth()?;
+                self.expect_symbol(".")?;
+                Ok(Filter::EdgeRecursive(
+                    name,
+                    max_depth,
+                    Box::new(self.unary_filter()?),
+                ))
+            }
+            "into_edge_recursive" => {
+                self.advance();
+                let (name, max_depth) = self.name_and_depth()?;
+                Ok(Filter::IntoEdgeRecursive(name, max_depth))
+            }
+            "and" | "or" if self.peek_second_is_symbol("(") => {
+                self.advance();
+                self.advance();
+                let mut filters = Vec::new();
+                if !self.eat_symbol(")") {
+                    loop {
+                        filters.push(self.filter()?);
+                        if self.eat_symbol(")") {
+                            break;
+                        }
+                        self.expect_symbol(",")?;
+                    }
+                }
+                if word == "and" {
+                    Ok(Filter::And(filters))
+                } else {
+                    Ok(Filter::Or(filters))
+                }
+            }
+            _ => Err(self.unexpected("a filter")),
+        }
+    }
+
+    /// Parses a predicate, where integers without a suffix are given the
+    /// specified type
+    pub fn predicate(&mut self, ints: IntegerType) -> Result<Predicate, ParseQueryError> {
+        self.nested(|parser| parser.nested_predicate(ints))
+    }
+
+    fn nested_predicate(&mut self, ints: IntegerType) -> Result<Predicate, ParseQueryError> {
+        let token = self.peek().clone();
+        let word = match token.kind {
+            TokenKind::Symbol(x) => x,
+            TokenKind::Ident(ref x) => x.as_str(),
+            _ => return Err(self.unexpected("a predicate")),
+        };
+
+        let p = match word {
+            "=" | "!=" | ">" | ">=" | "<" | "<=" | "contains" => {
+                self.advance();
+                let value = self.value(ints)?;
+                match word {
+                    "=" => Predicate::Equals(value),
+                    "!=" => Predicate::NotEquals(value),
+                    ">" => Predicate::GreaterThan(value),
+                    ">=" => Predicate::GreaterThanOrEquals(value),
+                    "<" => Predicate::LessThan(value),
+                    "<=" => Predicate::LessThanOrEquals(value),
+                    _ => Predicate::Contains(value),
+                }
+            }
+            "always" | "never" | "is_none" => {
+                self.advance();
+                match word {
+                    "always" => Predicate::Always,
+                    "never" => Predicate::Never,
+                    _ => Predicate::IsNone,
+                }
+            }
+            "in" | "not_in" => {
+                self.advance();
+                let is_in = word == "in";
+                if self.at_symbol("(") {
+                    let set = self.values(ints)?.into_iter().collect::<HashSet<Value>>();
+                    if is_in {
+                        Predicate::InSet(set)
+                    } else {
+                        Predicate::NotInSet(set)
+                    }
+                } else {
+                    let start = self.value(ints)?;
+                    self.expect_symbol("..=")?;
+                    let range = start..=self.value(ints)?;
+                    if is_in {
+                        Predicate::InRange(range)
+                    } else {
+                        Predicate::NotInRange(range)
+                    }
+                }
+            }
+            "contains_all" => {
+                self.advance();
+                Predicate::ContainsAll(self.values(ints)?)
+            }
+            "contains_any" => {
+                self.advance();
+                Predicate::ContainsAny(self.values(ints)?)
+            }
+            "has_key" => {
+                self.advance();
+                let (key, _) = self.text()?;
+                if self.eat_ident("where") {
+                    Predicate::HasKeyWhereValue(key, Box::new(self.predicate(ints)?))
+                } else {
+                    Predicate::HasKey(key)
+                }
+            }
+            "any" | "not" | "not_none_and" | "none_or" => {
+                self.advance();
+                self.expect_symbol("(")?;
+                let p = Box::new(self.predicate(ints)?);
+                self.expect_symbol(")")?;
+                match word {
+                    "any" => Predicate::Any(p),
+                    "not" => Predicate::Not(p),
+                    "not_none_and" => Predicate::NotNoneAnd(p),
+                    _ => Predicate::NoneOr(p),
+                }
+            }
+            "and" | "or" | "xor" => {
+                self.advance();
+                let ps = self.list(|parser| parser.predicate(ints))?;
+                match word {
+                    "and" => Predicate::And(ps),
+                    "or" => Predicate::Or(ps),
+                    _ => Predicate::Xor(ps),
+                }
+            }
+            "fuzzy_matches" => {
+                self.advance();
+                let (text, _) = self.text()?;
+                self.expect_ident("within")?;
+                Predicate::TextFuzzyMatches(text, self.count()?)
+            }
+            #[cfg(feature = "regex")]
+            "matches_regex" => {
+                self.advance();
+                let (text, span) = self.text()?;
+                let regex = regex::Regex::new(&text)
+                    .map_err(|x| ParseQueryError::new(span, x.to_string()))?;
+                Predicate::TextMatchesRegex(regex)
+            }
+            "lambda" => {
+                return Err(ParseQueryError::new(
+                    token.span,
+                    "lambda predicates cannot be written as text",
+                ))
+            }
+            _ => return self.text_predicate(word.to_string()),
+        };
+
+        Ok(p)
+    }
+
+    /// Parses a predicate that applies to text
+    fn text_predicate(&mut self, word: String) -> Result<Predicate, ParseQueryError> {
+        type Single = fn(String) -> Predicate;
+        type Multiple = fn(Vec<String>) -> Predicate;
+
+        let single: Option<Single> = match word.as_str() {
+            "contained_in" => Some(Predicate::TextContainedIn),
+            "contained_in_case_insensitive" => Some(Predicate::TextContainedInCaseInsensitive),
+            "ends_with" => Some(Predicate::TextEndsWith),
+            "ends_with_case_insensitive" => Some(Predicate::TextEndsWithCaseInsensitive),
+            "equals_case_insensitive" => Some(Predicate::TextEqualsCaseInsensitive),
+            "not_equals_case_insensitive" => Some(Predicate::TextNotEqualsCaseInsensitive),
+            "starts_with" => Some(Predicate::TextStartsWith),
+            "starts_with_case_insensitive" => Some(Predicate::TextStartsWithCaseInsensitive),
+            _ => None,
+        };
+
+        let multiple: Option<Multiple> = match word.as_str() {
+            "contains_all_text" => Some(Predicate::TextContainsAll),
+            "contains_all_text_case_insensitive" => Some(Predicate::TextContainsAllCaseInsensitive),
+            "contains_any_text" => Some(Predicate::TextContainsAny),
+            "contains_any_text_case_insensitive" => Some(Predicate::TextContainsAnyCaseInsensitive),
+            "ends_with_any" => Some(Predicate::TextEndsWithAny),
+            "ends_with_any_case_insensitive" => Some(Predicate::TextEndsWithAnyCaseInsensitive),
+            "in_case_insensitive" => {
+                Some(|x: Vec<String>| Predicate::TextInSetCaseInsensitive(x.into_iter().collect()))
+            }
+            "starts_with_any" => Some(Predicate::TextStartsWithAny),
+            "starts_with_any_case_insensitive" => Some(Predicate::TextStartsWithAnyCaseInsensitive),
+            _ => None,
+        };
+
+        match (single, multiple) {
+            (Some(f), _) => {
+                self.advance();
+                Ok(f(self.text()?.0))
+            }
+            (_, Some(f)) => {
+                self.advance();
+                Ok(f(self.list(|parser| Ok(parser.text()?.0))?))
+            }
+            _ => Err(self.unexpected("a predicate")),
+        }
+    }
+
+    /// Parses a value, where integers without a suffix are given the
+    /// specified type
+    fn value(&mut self, ints: IntegerType) -> Result<Value, ParseQueryError> {
+        self.nested(|parser| parser.nested_value(ints))
+    }
+
+    fn nested_value(&mut self, ints: IntegerType) -> Result<Value, ParseQueryError> {
+        let start = self.pos;
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Text(x) => Ok(Value::Text(x)),
+            TokenKind::Char(x) => Ok(Value::from(x)),
+            TokenKind::Number(x) => Ok(Value::from(number(&x, token.span, ints)?)),
+            TokenKind::Ident(x) if x == "true" => Ok(Value::from(true)),
+            TokenKind::Ident(x) if x == "false" => Ok(Value::from(false)),
+            TokenKind::Ident(x) if x == "unit" => Ok(Value::Primitive(PrimitiveValue::Unit)),
+            TokenKind::Ident(x) if x == "none" => Ok(Value::Optional(None)),
+            TokenKind::Ident(x) if x == "some" => {
+                self.expect_symbol("(")?;
+                let value = self.value(ints)?;
+                self.expect_symbol(")")?;
+                Ok(Value::Optional(Some(Box::new(value))))
+            }
+            TokenKind::Symbol("[") => {
+                let mut values = Vec::new();
+                if !self.eat_symbol("]") {
+                    loop {
+                        values.push(self.value(ints)?);
+                        if self.eat_symbol("]") {
+                            break;
+                        }
+                        self.expect_symbol(",")?;
+                    }
+                }
+                Ok(Value::List(values))
+            }
+            TokenKind::Symbol("{") => {
+                let mut map = HashMap::new();
+                if !self.eat_symbol("}") {
+                    loop {
+                        let (key, _) = self.text()?;
+                        self.expect_symbol(":")?;
+                        map.insert(key, self.value(ints)?);
+                        if self.eat_symbol("}") {
+                            break;
+                        }
+                        self.expect_symbol(",")?;
+                    }
+                }
+                Ok(Value::Map(map))
+            }
+            _ => {
+                self.pos = start;
+                Err(self.unexpected("a value"))
+            }
+        }
+    }
+
+    /// Parses values separated by commas and surrounded by parentheses
+    fn values(&mut self, ints: IntegerType) -> Result<Vec<Value>, ParseQueryError> {
+        self.list(|parser| parser.value(ints))
+    }
+
+    /// Parses items separated by commas and surrounded by parentheses
+    fn list<T, F: Fn(&mut Self) -> Result<T, ParseQueryError>>(
+        &mut self,
+        f: F,
+    ) -> Result<Vec<T>, ParseQueryError> {
+        self.expect_symbol("(")?;
+        let mut items = Vec::new();
+        if !self.eat_symbol(")") {
+            loop {
+                items.push(f(self)?);
+                if self.eat_symbol(")") {
+                    break;
+                }
+                self.expect_symbol(",")?;
+            }
+        }
+        Ok(items)
+    }
+
+    fn order(&mut self) -> Result<Order, ParseQueryError> {
+        let by = if self.eat_ident("id") {
+            OrderBy::Id
+        } else if self.eat_ident("created") {
+            OrderBy::Created
+        } else if self.eat_ident("last_updated") {
+            OrderBy::LastUpdated
+        } else if self.eat_ident("field") {
+            OrderBy::Field(self.name()?)
+        } else if self.eat_ident("relevance") {
+            self.expect_symbol("(")?;
+            let name = self.bare_name()?;
+            self.expect_symbol(",")?;
+            let (text, _) = self.text()?;
+            self.expect_symbol(")")?;
+            OrderBy::Relevance(name, text)
+        } else {
+            return Err(self.unexpected("`id`, `created`, `last_updated`, `field`, or `relevance`"));
+        };
+
+        let direction = if self.eat_ident("desc") {
+            OrderDirection::Descending
+        } else {
+            self.eat_ident("asc");
+            OrderDirection::Ascending
+        };
+
+        Ok(Order::new(by, direction))
+    }
+
+    /// Parses the name of a field or edge surrounded by parentheses, which
+    /// is either a word or quoted text
+    fn name(&mut self) -> Result<String, ParseQueryError> {
+        self.expect_symbol("(")?;
+        let name = self.bare_name()?;
+        self.expect_symbol(")")?;
+        Ok(name)
+    }
+
+    /// Parses a name followed by the most hops to follow, if any, such as
+    /// `(children)` or `(children, 3)`
+    fn name_and_depth(&mut self) -> Result<(String, Option<usize>), ParseQueryError> {
+        self.expect_symbol("(")?;
+        let name = self.bare_name()?;
+        let max_depth = if self.eat_symbol(",") {
+            Some(self.count()?)
+        } else {
+            None
+        };
+        self.expect_symbol(")")?;
+        Ok((name, max_depth))
+    }
+
+    fn bare_name(&mut self) -> Result<String, ParseQueryError> {
+        let start = self.pos;
+        match self.advance().kind {
+            TokenKind::Ident(x) | TokenKind::Text(x) => Ok(x),
+            _ => {
+                self.pos = start;
+                Err(self.unexpected("a name"))
+            }
+        }
+    }
+
+    fn text(&mut self) -> Result<(String, Range<usize>), ParseQueryError> {
+        let start = self.pos;
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Text(x) => Ok((x, token.span)),
+            _ => {
+                self.pos = start;
+                Err(self.unexpected("text"))
+            }
+        }
+    }
+
+    /// Parses an integer without a suffix used as a total, such as a limit
+    fn count(&mut self) -> Result<usize, ParseQueryError> {
+        let start = self.pos;
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Number(ref x) if x.suffix.is_none() && !x.digits.starts_with('-') => {
+                x.digits.parse::<usize>().map_err(|_| {
+                    ParseQueryError::new(token.span.clone(), format!("number `{}` is too large", x))
+                })
+            }
+            _ => {
+                self.pos = start;
+                Err(self.unexpected("a non-negative integer"))
+            }
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        // Tokens always end with the end token, which is never advanced past
+        &self.tokens[self.pos]
+    }
+
+    fn peek_second_is_symbol(&self, symbol: &str) -> bool {
+        matches!(
+            self.tokens.get(self.pos + 1),
+            Some(Token { kind: TokenKind::Symbol(x), .. }) if *x == symbol
+        )
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn at_symbol(&self, symbol: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Symbol(x) if *x == symbol)
+    }
+
+    fn at_clause(&self) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(x) if CLAUSES.contains(&x.as_str()))
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.at_symbol(symbol);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn eat_ident(&mut self, word: &str) -> bool {
+        let found = matches!(&self.peek().kind, TokenKind::Ident(x) if x == word);
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseQueryError> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", symbol)))
+        }
+    }
+
+    fn expect_ident(&mut self, word: &str) -> Result<(), ParseQueryError> {
+        if self.eat_ident(word) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", word)))
+        }
+    }
+
+    /// Parses something nested one level deeper than its surroundings,
+    /// failing once nesting goes past [`MAX_DEPTH`]
+    fn nested<T, F: FnOnce(&mut Self) -> Result<T, ParseQueryError>>(
+        &mut self,
+        f: F,
+    ) -> Result<T, ParseQueryError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(ParseQueryError::new(
+                self.peek().span.clone(),
+                format!("query is nested more than {} levels deep", MAX_DEPTH),
+            ));
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Produces an error describing the next token as unexpected
+    fn unexpected(&self, expected: &str) -> ParseQueryError {
+        let token = self.peek();
+        ParseQueryError::new(
+            token.span.clone(),
+            format!("expected {}, found {}", expected, token.kind),
+        )
+    }
+}
+
+/// Combines filters separated by `and` into a single filter
+fn from_chain(mut chain: Vec<Filter>) -> Filter {
+    if chain.len() == 1 {
+        chain.remove(0)
+    } else {
+        Filter::And(chain)
+    }
+}
+
+/// Converts a number as written into a number of the type given by its
+/// suffix, falling back to a float or the specified integer type
+fn number(
+    literal: &NumberLiteral,
+    span: Range<usize>,
+    ints: IntegerType,
+) -> Result<Number, ParseQueryError> {
+    let ty = match literal.suffix.as_deref() {
+        Some(suffix) => suffix.parse::<NumberType>().map_err(|_| {
+            ParseQueryError::new(span.clone(), format!("unknown number suffix `{}`", suffix))
+        })?,
+        None if literal.is_float() => NumberType::F64,
+        None => match ints {
+            IntegerType::Signed => NumberType::I64,
+            IntegerType::Id => NumberType::Usize,
+            IntegerType::Unsigned => NumberType::U64,
+        },
+    };
+
+    let invalid = || {
+        ParseQueryError::new(
+            span.clone(),
+            format!("number `{}` is not a valid {}", literal, ty),
+        )
+    };
+
+    match ty {
+        NumberType::F32 => float(&literal.digits, f32::INFINITY, f32::NAN)
+            .map(Number::F32)
+            .ok_or_else(invalid),
+        NumberType::F64 => float(&literal.digits, f64::INFINITY, f64::NAN)
+            .map(Number::F64)
+            .ok_or_else(invalid),
+        _ if literal.is_float() => Err(invalid()),
+        _ => {
+            let wide = if literal.digits.starts_with('-') {
+                literal.digits.parse::<i128>().ok().map(Integer::Signed)
+            } else {
+                literal.digits.parse::<u128>().ok().map(Integer::Unsigned)
+            };
+
+            macro_rules! convert {
+                ($variant:ident, $type:ty) => {
+                    wide.and_then(|x| match x {
+                        Integer::Signed(x) => <$type>::try_from(x).ok(),
+                        Integer::Unsigned(x) => <$type>::try_from(x).ok(),
+                    })
+                    .map(Number::$variant)
+                };
+            }
+
+            let n = match ty {
+                NumberType::I8 => convert!(I8, i8),
+                NumberType::I16 => convert!(I16, i16),
+                NumberType::I32 => convert!(I32, i32),
+                NumberType::I64 => convert!(I64, i64),
+                NumberType::I128 => convert!(I128, i128),
+                NumberType::Isize => convert!(Isize, isize),
+                NumberType::U8 => convert!(U8, u8),
+                NumberType::U16 => convert!(U16, u16),
+                NumberType::U32 => convert!(U32, u32),
+                NumberType::U64 => convert!(U64, u64),
+                NumberType::U128 => convert!(U128, u128),
+                NumberType::Usize => convert!(Usize, usize),
+                NumberType::F32 | NumberType::F64 => unreachable!(),
+            };
+            n.ok_or_else(invalid)
+        }
+    }
+}
+
+/// Represents an integer as written before it is given a specific type
+enum Integer {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+/// Parses the digits of a float, including infinity and not-a-number
+fn float<T: FromStr + Neg<Output = T>>(digits: &str, inf: T, nan: T) -> Option<T> {
+    match digits {
+        "inf" => Some(inf),
+        "-inf" => Some(-inf),
+        "nan" => Some(nan),
+        x => x.parse::<T>().ok(),
+    }
+}
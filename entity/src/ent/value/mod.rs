@@ -68,6 +68,15 @@ impl Value {
         self.to_primitive().map(PrimitiveValueType::from)
     }
 
+    /// Converts into underlying number if representing one
+    #[inline]
+    pub fn to_number(&self) -> Option<Number> {
+        match self {
+            Self::Primitive(PrimitiveValue::Number(x)) => Some(*x),
+            _ => None,
+        }
+    }
+
     /// Attempts to convert the value to an underlying option type,
     /// succeeding if Value is the Optional variant and the inner
     /// value can be converted to the specified type.
@@ -92,24 +101,18 @@ impl Value {
 }
 
 impl Hash for Value {
+    /// Hashes consistently with equality, meaning that an optional value
+    /// hashes the same as the value it contains
     fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
             Self::List(x) => x.hash(state),
             Self::Map(x) => {
-                let mut keys = x.keys().collect::<Vec<&String>>();
-                keys.sort_unstable();
-                keys.hash(state);
-
-                // TODO: Is there a better way to approach hashing when a value
-                //       might not support ordering? Should we filter out all
-                //       values that are not comparable? If so, we would need
-                //       to provide some method on value, primitive, and number
-                //       that can tell us if it is comparable
-                let mut values = x.values().collect::<Vec<&Value>>();
-                values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Less));
-                values.hash(state);
+                let mut entries = x.iter().collect::<Vec<(&String, &Value)>>();
+                entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                entries.hash(state);
             }
-            Self::Optional(x) => x.hash(state),
+            Self::Optional(Some(x)) => x.hash(state),
+            Self::Optional(None) => Option::<()>::None.hash(state),
             Self::Primitive(x) => x.hash(state),
             Self::Text(x) => x.hash(state),
         }
@@ -240,6 +243,13 @@ impl From<PrimitiveValue> for Value {
     }
 }
 
+impl From<Number> for Value {
+    /// Converts a number into a primitive value without any allocation
+    fn from(v: Number) -> Self {
+        Self::from(PrimitiveValue::from(v))
+    }
+}
+
 impl From<String> for Value {
     /// Converts a string into a text value without any allocation
     fn from(s: String) -> Self {
@@ -631,7 +641,7 @@ impl Hash for PrimitiveValue {
             Self::Bool(x) => x.hash(state),
             Self::Char(x) => x.hash(state),
             Self::Number(x) => x.hash(state),
-            Self::Unit => Self::Unit.hash(state),
+            Self::Unit => {}
         }
     }
 }
@@ -339,22 +339,19 @@ impl NumberSign {
     }
 }
 impl Hash for Number {
+    /// Hashes the sign and magnitude that are used for equality, so numbers
+    /// of different types that are equal also share the same hash
     fn hash<H: Hasher>(&self, state: &mut H) {
-        match self {
-            Self::F32(x) => x.to_string().hash(state),
-            Self::F64(x) => x.to_string().hash(state),
-            Self::I128(x) => x.hash(state),
-            Self::I16(x) => x.hash(state),
-            Self::I32(x) => x.hash(state),
-            Self::I64(x) => x.hash(state),
-            Self::I8(x) => x.hash(state),
-            Self::Isize(x) => x.hash(state),
-            Self::U128(x) => x.hash(state),
-            Self::U16(x) => x.hash(state),
-            Self::U32(x) => x.hash(state),
-            Self::U64(x) => x.hash(state),
-            Self::U8(x) => x.hash(state),
-            Self::Usize(x) => x.hash(state),
+        // Numbers that are not comparable never equal anything
+        if !self.is_normal() && !self.is_zero() {
+            return self.to_f64().to_bits().hash(state);
+        }
+
+        let sign = self.sign();
+        sign.to_i8().hash(state);
+        match sign {
+            NumberSign::Negative => self.to_absolute().to_u128().hash(state),
+            NumberSign::Positive | NumberSign::Zero => self.to_u128().hash(state),
         }
     }
 }
@@ -718,4 +715,21 @@ mod tests {
         check_abs_match!(U128, U64, U32, U16, U8, Usize; 0, 0);
         check_abs_match!(U128, U64, U32, U16, U8, Usize; 1, 1);
     }
+
+    #[test]
+    fn hash_should_match_for_equal_numbers_of_different_types() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(number: Number) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            number.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        assert_eq!(hash(Number::from(3u8)), hash(Number::from(3i64)));
+        assert_eq!(hash(Number::from(3u8)), hash(Number::from(3.0f64)));
+        assert_eq!(hash(Number::from(-3i8)), hash(Number::from(-3.0f32)));
+        assert_eq!(hash(Number::from(0u128)), hash(Number::from(-0.0f64)));
+        assert_ne!(hash(Number::from(3)), hash(Number::from(-3)));
+    }
 }
@@ -12,11 +12,30 @@ pub use entity_macros::*;
 
 /// Vendor module to re-expose relevant libraries
 pub mod vendor {
+    #[cfg(feature = "regex")]
+    pub use ::regex;
+
     #[cfg(feature = "sled_db")]
     pub use ::sled;
 
     /// Re-exported macros, useful only to [`entity_macros`] crate
     pub mod macros {
+        /// Re-export of attribute that keeps async-only items
+        pub mod async_support {
+            /// Indicates whether or not the included attr macro keeps the
+            /// items it is attached to (true) or removes them (false)
+            #[inline]
+            pub const fn exists() -> bool {
+                cfg!(feature = "async")
+            }
+
+            #[cfg(feature = "async")]
+            pub use ::entity_noop_macros::noop_attr as include;
+
+            #[cfg(not(feature = "async"))]
+            pub use ::entity_noop_macros::remove_attr as include;
+        }
+
         /// Re-export of serde
         pub mod serde {
             /// Indicates whether or not the included serde derive macros are
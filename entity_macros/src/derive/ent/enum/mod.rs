@@ -140,6 +140,48 @@ fn impl_query(root: &Path, ent: &Ent) -> darling::Result<TokenStream> {
                 Self(self.0.where_field(name, p), #(#default_phantoms),*)
             }
 
+            #[doc = "Orders returned ents by id in the given direction"]
+            pub fn order_by_id(self, direction: #root::OrderDirection) -> Self {
+                Self(self.0.order_by_id(direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Orders returned ents by created timestamp in the given direction"]
+            pub fn order_by_created(self, direction: #root::OrderDirection) -> Self {
+                Self(self.0.order_by_created(direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Orders returned ents by last updated timestamp in the given direction"]
+            pub fn order_by_last_updated(self, direction: #root::OrderDirection) -> Self {
+                Self(self.0.order_by_last_updated(direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Orders returned ents by field in the given direction"]
+            pub fn order_by_field(
+                self,
+                name: &::std::primitive::str,
+                direction: #root::OrderDirection,
+            ) -> Self {
+                Self(self.0.order_by_field(name, direction), #(#default_phantoms),*)
+            }
+
+            #[doc = "Skips the given total ents before returning any"]
+            pub fn offset(self, offset: ::std::primitive::usize) -> Self {
+                Self(self.0.offset(offset), #(#default_phantoms),*)
+            }
+
+            #[doc = "Returns no more than the given total ents"]
+            pub fn limit(self, limit: ::std::primitive::usize) -> Self {
+                Self(self.0.limit(limit), #(#default_phantoms),*)
+            }
+
+            #[allow(dead_code)]
+            fn __entity_assert_thread_safe() {
+                fn __entity_assert<
+                    __entity_T: ::std::marker::Send + ::std::marker::Sync,
+                >() {}
+                __entity_assert::<Self>();
+            }
+
             #[doc = "Executes query against the given database"]
             pub fn execute<__entity_D: #root::Database>(
                 self,
@@ -156,6 +198,97 @@ fn impl_query(root: &Path, ent: &Ent) -> darling::Result<TokenStream> {
                     )
                 )
             }
+
+            #[doc = "Computes the aggregate over the results of the query against the given database"]
+            pub fn aggregate<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                aggregate: #root::Aggregate,
+            ) -> #root::DatabaseResult<#root::Value> {
+                #root::Database::aggregate(database, self.0, aggregate)
+            }
+
+            #[doc = "Computes the aggregate separately for each group of results of the query against the given database"]
+            pub fn aggregate_by<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                aggregate: #root::Aggregate,
+                group_by: #root::GroupBy,
+            ) -> #root::DatabaseResult<::std::vec::Vec<(#root::Value, #root::Value)>> {
+                #root::Database::aggregate_by(database, self.0, aggregate, group_by)
+            }
+
+            #[doc = "Computes the aggregate separately for each type of result of the query against the given database"]
+            pub fn aggregate_by_type<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                aggregate: #root::Aggregate,
+            ) -> #root::DatabaseResult<::std::vec::Vec<(#root::Value, #root::Value)>> {
+                #root::Database::aggregate_by(database, self.0, aggregate, #root::GroupBy::Type)
+            }
+
+            #[doc = "Counts the results of the query against the given database"]
+            pub fn count<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::primitive::usize> {
+                #root::Database::count(database, self.0)
+            }
+
+            #[doc = "Executes query against the given database, returning the first result"]
+            pub fn first<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::option::Option<#name #ty_generics>> {
+                ::std::result::Result::Ok(
+                    ::std::option::Option::and_then(
+                        #root::Database::find_first(database, self.0)?,
+                        <#name #ty_generics as #root::EntWrapper>::wrap_ent,
+                    )
+                )
+            }
+        }
+
+        #[#root::vendor::macros::async_support::include]
+        #[automatically_derived]
+        impl #impl_generics #query_name #ty_generics #where_clause {
+            #[doc = "Executes query against the given async database"]
+            pub async fn execute_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::vec::Vec<#name #ty_generics>> {
+                ::std::result::Result::Ok(
+                    ::std::iter::Iterator::collect(
+                        ::std::iter::Iterator::filter_map(
+                            ::std::iter::IntoIterator::into_iter(
+                                #root::AsyncDatabase::find_all(database, self.0).await?
+                            ),
+                            <#name #ty_generics as #root::EntWrapper>::wrap_ent,
+                        )
+                    )
+                )
+            }
+
+            #[doc = "Counts the results of the query against the given async database"]
+            pub async fn count_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::primitive::usize> {
+                #root::AsyncDatabase::count(database, self.0).await
+            }
+
+            #[doc = "Executes query against the given async database, returning the first result"]
+            pub async fn first_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::option::Option<#name #ty_generics>> {
+                ::std::result::Result::Ok(
+                    ::std::option::Option::and_then(
+                        #root::AsyncDatabase::find_first(database, self.0).await?,
+                        <#name #ty_generics as #root::EntWrapper>::wrap_ent,
+                    )
+                )
+            }
         }
     })
 }
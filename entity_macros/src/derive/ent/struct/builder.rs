@@ -189,5 +189,30 @@ pub fn impl_ent_builder(
                 })
             }
         }
+
+        #[#root::vendor::macros::async_support::include]
+        #[automatically_derived]
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            /// Called when finished constructing the ent, will consume the
+            /// builder and return a new ent after committing it to the
+            /// associated database without blocking the async executor. If
+            /// no database is connected to the ent, this will fail.
+            pub async fn finish_and_commit_async(self) -> ::std::result::Result<
+                #root::DatabaseResult<#ent_name #ty_generics>,
+                #builder_error_name,
+            > {
+                match self.finish() {
+                    ::std::result::Result::Ok(mut ent) => {
+                        ::std::result::Result::Ok(
+                            match #root::AsyncEntExt::commit_async(&mut ent).await {
+                                ::std::result::Result::Ok(()) => ::std::result::Result::Ok(ent),
+                                ::std::result::Result::Err(x) => ::std::result::Result::Err(x),
+                            }
+                        )
+                    }
+                    ::std::result::Result::Err(x) => ::std::result::Result::Err(x),
+                }
+            }
+        }
     })
 }
@@ -47,6 +47,7 @@ pub struct EntField {
 pub struct FieldAttr {
     pub indexed: bool,
     pub mutable: bool,
+    pub searchable: bool,
 }
 
 impl FromMeta for FieldAttr {
@@ -57,6 +58,7 @@ impl FromMeta for FieldAttr {
     fn from_list(items: &[NestedMeta]) -> darling::Result<Self> {
         let mut indexed = false;
         let mut mutable = false;
+        let mut searchable = false;
 
         for item in items {
             match item {
@@ -70,6 +72,7 @@ impl FromMeta for FieldAttr {
                     {
                         "indexed" => indexed = true,
                         "mutable" => mutable = true,
+                        "searchable" => searchable = true,
                         x => {
                             return Err(darling::Error::custom(format!("Unknown attribute: {}", x))
                                 .with_span(&x.span()))
@@ -86,7 +89,11 @@ impl FromMeta for FieldAttr {
             }
         }
 
-        Ok(Self { indexed, mutable })
+        Ok(Self {
+            indexed,
+            mutable,
+            searchable,
+        })
     }
 }
 
@@ -99,4 +106,6 @@ pub struct EdgeAttr {
     pub wrap: bool,
     #[darling(default, rename = "policy")]
     pub deletion_policy: EntEdgeDeletionPolicy,
+    #[darling(default)]
+    pub inverse: Option<syn::LitStr>,
 }
@@ -56,6 +56,10 @@ pub struct EntField {
     /// able to be mutated and that a typed method for mutation should
     /// be included when generating typed methods
     pub mutable: bool,
+
+    /// If field(searchable) provided, signifies that the words of this
+    /// field should be indexed by the database for full-text search
+    pub searchable: bool,
 }
 
 /// Information about a specific edge for an ent
@@ -67,6 +71,7 @@ pub struct EntEdge {
     pub wrap: bool,
     pub kind: EntEdgeKind,
     pub deletion_policy: EntEdgeDeletionPolicy,
+    pub inverse: Option<Ident>,
 }
 
 /// Information about an an edge's deletion policy
@@ -75,6 +80,8 @@ pub enum EntEdgeDeletionPolicy {
     Nothing,
     Shallow,
     Deep,
+    Restrict,
+    Nullify,
 }
 
 impl Default for EntEdgeDeletionPolicy {
@@ -145,6 +152,7 @@ impl FromDeriveInput for Ent {
                     ty,
                     indexed: attr.indexed,
                     mutable: attr.mutable,
+                    searchable: attr.searchable,
                 });
             } else if let Some(attr) = f.edge_attr {
                 let kind = match &ty {
@@ -161,6 +169,22 @@ impl FromDeriveInput for Ent {
                     x => return Err(darling::Error::custom("Unexpected edge id type").with_span(x)),
                 };
 
+                if let (EntEdgeKind::One, EntEdgeDeletionPolicy::Nullify) =
+                    (&kind, &attr.deletion_policy)
+                {
+                    return Err(darling::Error::custom(
+                        "Nullify policy requires an optional or many edge id type",
+                    )
+                    .with_span(&ty));
+                }
+
+                let inverse = match &attr.inverse {
+                    Some(lit) => Some(lit.parse::<Ident>().map_err(|_| {
+                        darling::Error::custom("Inverse must be the name of an edge").with_span(lit)
+                    })?),
+                    None => None,
+                };
+
                 edges.push(EntEdge {
                     name,
                     ty,
@@ -168,6 +192,7 @@ impl FromDeriveInput for Ent {
                     wrap: attr.wrap,
                     kind,
                     deletion_policy: attr.deletion_policy,
+                    inverse,
                 });
             } else if ent.strict {
                 return Err(darling::Error::custom("Missing ent(...) attribute").with_span(&name));
@@ -177,6 +202,7 @@ impl FromDeriveInput for Ent {
                     ty,
                     indexed: false,
                     mutable: false,
+                    searchable: false,
                 });
             }
         }
@@ -35,6 +35,7 @@ pub(crate) fn impl_ent(
 
     let field_definitions = make_field_definitions(root, fields)?;
     let edge_definitions = make_edge_definitions(root, edges);
+    let inverse_edge_checks = make_inverse_edge_checks(name, generics, edges);
 
     let typetag_root = utils::typetag_crate()?;
     let typetag_t = quote!(#[#typetag_root::serde]);
@@ -289,6 +290,8 @@ pub(crate) fn impl_ent(
                 )
             }
         }
+
+        #inverse_edge_checks
     })
 }
 
@@ -305,6 +308,10 @@ fn make_field_definitions(root: &Path, fields: &[EntField]) -> darling::Result<V
             attrs.push(quote! { #root::FieldAttribute::Indexed });
         }
 
+        if f.searchable {
+            attrs.push(quote! { #root::FieldAttribute::Searchable });
+        }
+
         if !f.mutable {
             attrs.push(quote! { #root::FieldAttribute::Immutable });
         }
@@ -432,17 +439,76 @@ fn make_edge_definitions(root: &Path, edges: &[EntEdge]) -> Vec<TokenStream> {
         let deletion_policy = match e.deletion_policy {
             EntEdgeDeletionPolicy::Deep => quote! { #root::EdgeDeletionPolicy::DeepDelete },
             EntEdgeDeletionPolicy::Shallow => quote! { #root::EdgeDeletionPolicy::ShallowDelete },
+            EntEdgeDeletionPolicy::Restrict => quote! { #root::EdgeDeletionPolicy::Restrict },
+            EntEdgeDeletionPolicy::Nullify => quote! { #root::EdgeDeletionPolicy::Nullify },
             EntEdgeDeletionPolicy::Nothing => quote! { #root::EdgeDeletionPolicy::Nothing },
         };
 
+        let with_inverse = e.inverse.as_ref().map(|inverse| {
+            quote! { .with_inverse(::std::stringify!(#inverse)) }
+        });
+
         token_streams.push(quote! {
             #root::EdgeDefinition::new_with_deletion_policy(
                 ::std::stringify!(#name),
                 #ty,
                 #deletion_policy,
-            )
+            )#with_inverse
         });
     }
 
     token_streams
 }
+
+/// Produces a hidden marker function for each edge that can serve as the
+/// inverse of another edge, along with a hidden function for each edge with
+/// an inverse that calls the marker of that inverse on the other ent, so a
+/// missing or incompatible inverse edge fails to compile
+fn make_inverse_edge_checks(name: &Ident, generics: &Generics, edges: &[EntEdge]) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut token_streams = Vec::new();
+
+    for e in edges {
+        if let EntEdgeKind::Maybe | EntEdgeKind::Many = e.kind {
+            let marker = inverse_edge_marker(&e.name);
+            token_streams.push(quote! {
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                pub fn #marker() {}
+            });
+        }
+
+        if let Some(inverse) = &e.inverse {
+            let check = Ident::new(
+                &format!("__entity_check_inverse_edge_{}", e.name),
+                Span::call_site(),
+            );
+            let ent_ty = &e.ent_ty;
+            let marker = inverse_edge_marker(inverse);
+            token_streams.push(quote! {
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                fn #check() {
+                    <#ent_ty>::#marker();
+                }
+            });
+        }
+    }
+
+    if token_streams.is_empty() {
+        TokenStream::new()
+    } else {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#token_streams)*
+            }
+        }
+    }
+}
+
+/// Names the hidden marker function of an edge, keeping the span of the
+/// edge name so errors point at where the edge is named
+fn inverse_edge_marker(name: &Ident) -> Ident {
+    Ident::new(&format!("__entity_inverse_edge_{}", name), name.span())
+}
@@ -35,6 +35,14 @@ pub fn impl_ent_query(
         }
     });
 
+    let method_name = format_ident!("order_by_{}", ent.id);
+    methods.push(quote! {
+        #[doc = "Orders returned ents by id in the given direction"]
+        pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+            Self(self.0.order_by_id(direction), #(#default_phantoms),*)
+        }
+    });
+
     let method_name = format_ident!("where_{}", ent.created);
     methods.push(quote! {
         #[doc = "Filters to return all ents where created timestamp passes the given predicate"]
@@ -43,6 +51,14 @@ pub fn impl_ent_query(
         }
     });
 
+    let method_name = format_ident!("order_by_{}", ent.created);
+    methods.push(quote! {
+        #[doc = "Orders returned ents by created timestamp in the given direction"]
+        pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+            Self(self.0.order_by_created(direction), #(#default_phantoms),*)
+        }
+    });
+
     let method_name = format_ident!("where_{}", ent.last_updated);
     methods.push(quote! {
         #[doc = "Filters to return all ents where last updated timestamp passes the given predicate"]
@@ -51,6 +67,14 @@ pub fn impl_ent_query(
         }
     });
 
+    let method_name = format_ident!("order_by_{}", ent.last_updated);
+    methods.push(quote! {
+        #[doc = "Orders returned ents by last updated timestamp in the given direction"]
+        pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+            Self(self.0.order_by_last_updated(direction), #(#default_phantoms),*)
+        }
+    });
+
     for f in &ent.fields {
         let name = &f.name;
         let ty = &f.ty;
@@ -76,8 +100,201 @@ pub fn impl_ent_query(
                 )
             }
         });
+
+        if !utils::is_scalar_type(ty) {
+            let method_name = format_ident!("where_{}_at", name);
+            let doc_string = format!(
+                concat!(
+                    "Filters to return all ents where the value at the path ",
+                    "(such as \"city\" or \"[0]\") within the field \"{}\" ",
+                    "passes the given predicate",
+                ),
+                name
+            );
+
+            methods.push(quote! {
+                #[doc = #doc_string]
+                pub fn #method_name(
+                    self,
+                    path: &::std::primitive::str,
+                    p: #root::Predicate,
+                ) -> Self {
+                    Self(
+                        self.0.where_field(
+                            #root::FieldPath::join(::std::stringify!(#name), path),
+                            p,
+                        ),
+                        #(#default_phantoms),*
+                    )
+                }
+            });
+        }
+
+        if f.searchable {
+            let method_name = format_ident!("where_{}_matches", name);
+            let doc_string = format!(
+                concat!(
+                    "Filters to return all ents where the field \"{}\" contains ",
+                    "any of the words of the text, ordering them from most to ",
+                    "least relevant",
+                ),
+                name
+            );
+
+            methods.push(quote! {
+                #[doc = #doc_string]
+                pub fn #method_name(self, text: &::std::primitive::str) -> Self {
+                    Self(
+                        self.0.where_matches(::std::stringify!(#name), text),
+                        #(#default_phantoms),*
+                    )
+                }
+            });
+        }
+
+        let method_name = format_ident!("order_by_{}", name);
+        let doc_string = format!(
+            "Orders returned ents by the field \"{}\" in the given direction",
+            name
+        );
+
+        methods.push(quote! {
+            #[doc = #doc_string]
+            pub fn #method_name(self, direction: #root::OrderDirection) -> Self {
+                Self(
+                    self.0.order_by_field(::std::stringify!(#name), direction),
+                    #(#default_phantoms),*
+                )
+            }
+        });
+
+        let method_name = format_ident!("project_{}", name);
+        let doc_string = format!(
+            concat!(
+                "Finds the value of the field \"{}\" of each ent that matches ",
+                "the query without loading the ents themselves",
+            ),
+            name
+        );
+        let value_ident = format_ident!("value");
+        let value_to_typed_field = utils::convert_from_value(&value_ident, ty);
+
+        methods.push(quote! {
+            #[doc = #doc_string]
+            pub fn #method_name<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::vec::Vec<(#root::Id, #ty)>> {
+                let name = ::std::string::ToString::to_string(::std::stringify!(#name));
+                let values = #root::Database::project(
+                    database,
+                    self.0,
+                    ::std::vec![::std::clone::Clone::clone(&name)],
+                )?;
+
+                let mut typed_values = ::std::vec::Vec::new();
+                for (id, mut values) in values {
+                    if let ::std::option::Option::Some(#value_ident) = values.remove(&name) {
+                        let converted: ::std::result::Result<
+                            #ty,
+                            &'static ::std::primitive::str
+                        > = #value_to_typed_field;
+                        typed_values.push((id, converted.map_err(
+                            |x| #root::DatabaseError::CorruptedEnt {
+                                id,
+                                source: ::std::convert::Into::into(x),
+                            }
+                        )?));
+                    }
+                }
+                ::std::result::Result::Ok(typed_values)
+            }
+        });
+
+        let method_name = format_ident!("aggregate_by_{}", name);
+        let doc_string = format!(
+            "Computes the aggregate separately for each value of the field \"{}\"",
+            name
+        );
+
+        methods.push(quote! {
+            #[doc = #doc_string]
+            pub fn #method_name<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                aggregate: #root::Aggregate,
+            ) -> #root::DatabaseResult<::std::vec::Vec<(#root::Value, #root::Value)>> {
+                #root::Database::aggregate_by(
+                    database,
+                    self.0,
+                    aggregate,
+                    #root::GroupBy::Field(::std::string::ToString::to_string(
+                        ::std::stringify!(#name)
+                    )),
+                )
+            }
+        });
+
+        if utils::is_number_type(ty) {
+            for (prefix, label) in &[
+                ("sum", "Sums"),
+                ("min", "Finds the smallest of"),
+                ("max", "Finds the largest of"),
+            ] {
+                let constructor = format_ident!("{}", prefix);
+                let method_name = format_ident!("{}_{}", prefix, name);
+                let doc_string = format!(
+                    "{} the values of the field \"{}\" across the results of the query",
+                    label, name
+                );
+
+                methods.push(quote! {
+                    #[doc = #doc_string]
+                    pub fn #method_name<__entity_D: #root::Database>(
+                        self,
+                        database: &__entity_D,
+                    ) -> #root::DatabaseResult<::std::option::Option<#root::Number>> {
+                        ::std::result::Result::map(
+                            #root::Database::aggregate(
+                                database,
+                                self.0,
+                                #root::Aggregate::#constructor(::std::stringify!(#name)),
+                            ),
+                            |value| #root::Value::to_number(&value),
+                        )
+                    }
+                });
+            }
+
+            let method_name = format_ident!("average_{}", name);
+            let doc_string = format!(
+                "Averages the values of the field \"{}\" across the results of the query",
+                name
+            );
+
+            methods.push(quote! {
+                #[doc = #doc_string]
+                pub fn #method_name<__entity_D: #root::Database>(
+                    self,
+                    database: &__entity_D,
+                ) -> #root::DatabaseResult<::std::option::Option<::std::primitive::f64>> {
+                    ::std::result::Result::map(
+                        #root::Database::aggregate(
+                            database,
+                            self.0,
+                            #root::Aggregate::average(::std::stringify!(#name)),
+                        ),
+                        |value| ::std::option::Option::map(
+                            #root::Value::to_number(&value),
+                            |x| #root::Number::to_f64(&x),
+                        ),
+                    )
+                }
+            });
+        }
     }
 
+    let ent_name = name;
     for e in &ent.edges {
         let name = &e.name;
         let ent_ty = &e.ent_ty;
@@ -102,11 +319,28 @@ pub fn impl_ent_query(
             }
         });
 
-        let method_name = format_ident!("query_{}", name);
-        let edge_query_ty = format_ident!(
-            "{}Query",
-            utils::type_to_ident(ent_ty).expect("Bad edge ent type")
+        let method_name = format_ident!("where_{}_count", name);
+        let doc_string = format!(
+            "Filters to return all ents where the total ids of edge \"{}\" passes the given predicate",
+            name,
         );
+
+        methods.push(quote! {
+            #[doc = #doc_string]
+            pub fn #method_name(
+                self,
+                p: #root::TypedPredicate<::std::primitive::usize>,
+            ) -> Self {
+                Self(
+                    self.0.where_edge_count(::std::stringify!(#name), p),
+                    #(#default_phantoms),*
+                )
+            }
+        });
+
+        let method_name = format_ident!("query_{}", name);
+        let edge_ent_ident = utils::type_to_ident(ent_ty).expect("Bad edge ent type");
+        let edge_query_ty = format_ident!("{}Query", edge_ent_ident);
         let doc_string = format!(
             concat!(
                 "Returns a query for \"{}\" that is pre-filtered to ents ",
@@ -123,6 +357,33 @@ pub fn impl_ent_query(
                 )
             }
         });
+
+        // Only an edge back to the same type of ent can be followed more
+        // than once, as the ents reached must also have the edge
+        if edge_ent_ident == ent_name {
+            let method_name = format_ident!("query_{}_recursive", name);
+            let doc_string = format!(
+                concat!(
+                    "Returns a query for \"{}\" that is pre-filtered to ents ",
+                    "reached by following the edge from ents contained in the ",
+                    "current query, up to the maximum number of hops or ",
+                    "transitively if there is no maximum",
+                ),
+                name,
+            );
+
+            methods.push(quote! {
+                #[doc = #doc_string]
+                pub fn #method_name(
+                    self,
+                    max_depth: ::std::option::Option<::std::primitive::usize>,
+                ) -> #edge_query_ty {
+                    <#edge_query_ty as ::std::convert::From<#root::Query>>::from(
+                        self.0.where_into_edge_recursive(::std::stringify!(#name), max_depth)
+                    )
+                }
+            });
+        }
     }
 
     let default_doc_str = format!("Creates new query that selects all {} by default", name);
@@ -171,6 +432,24 @@ pub fn impl_ent_query(
         impl #impl_generics #query_name #ty_generics #where_clause {
             #(#methods)*
 
+            #[doc = "Skips the given total ents before returning any"]
+            pub fn offset(self, offset: ::std::primitive::usize) -> Self {
+                Self(self.0.offset(offset), #(#default_phantoms),*)
+            }
+
+            #[doc = "Returns no more than the given total ents"]
+            pub fn limit(self, limit: ::std::primitive::usize) -> Self {
+                Self(self.0.limit(limit), #(#default_phantoms),*)
+            }
+
+            #[allow(dead_code)]
+            fn __entity_assert_thread_safe() {
+                fn __entity_assert<
+                    __entity_T: ::std::marker::Send + ::std::marker::Sync,
+                >() {}
+                __entity_assert::<Self>();
+            }
+
             #[doc = "Executes query against the given database"]
             pub fn execute<__entity_D: #root::Database>(
                 self,
@@ -181,6 +460,116 @@ pub fn impl_ent_query(
                     self.0,
                 )
             }
+
+            #[doc = "Computes the aggregate over the results of the query against the given database"]
+            pub fn aggregate<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                aggregate: #root::Aggregate,
+            ) -> #root::DatabaseResult<#root::Value> {
+                #root::Database::aggregate(database, self.0, aggregate)
+            }
+
+            #[doc = "Computes the aggregate separately for each group of results of the query against the given database"]
+            pub fn aggregate_by<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                aggregate: #root::Aggregate,
+                group_by: #root::GroupBy,
+            ) -> #root::DatabaseResult<::std::vec::Vec<(#root::Value, #root::Value)>> {
+                #root::Database::aggregate_by(database, self.0, aggregate, group_by)
+            }
+
+            #[doc = "Finds the values of the named fields, or paths into fields, of each result of the query against the given database"]
+            pub fn project<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+                fields: &[&::std::primitive::str],
+            ) -> #root::DatabaseResult<::std::vec::Vec<(
+                #root::Id,
+                ::std::collections::HashMap<::std::string::String, #root::Value>,
+            )>> {
+                #root::Database::project(
+                    database,
+                    self.0,
+                    ::std::iter::Iterator::collect(::std::iter::Iterator::map(
+                        fields.iter(),
+                        |x| ::std::string::ToString::to_string(x),
+                    )),
+                )
+            }
+
+            #[doc = "Counts the results of the query against the given database"]
+            pub fn count<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::primitive::usize> {
+                #root::Database::count(database, self.0)
+            }
+
+            #[doc = "Executes query against the given database, returning the first result"]
+            pub fn first<__entity_D: #root::Database>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::option::Option<#name #ty_generics>> {
+                #root::DatabaseExt::find_first_typed::<#name #ty_generics>(
+                    database,
+                    self.0,
+                )
+            }
+        }
+
+        #[#root::vendor::macros::async_support::include]
+        #[automatically_derived]
+        impl #impl_generics #query_name #ty_generics #where_clause {
+            #[doc = "Executes query against the given async database"]
+            pub async fn execute_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::vec::Vec<#name #ty_generics>> {
+                #root::AsyncDatabaseExt::find_all_typed::<#name #ty_generics>(
+                    database,
+                    self.0,
+                ).await
+            }
+
+            #[doc = "Counts the results of the query against the given async database"]
+            pub async fn count_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::primitive::usize> {
+                #root::AsyncDatabase::count(database, self.0).await
+            }
+
+            #[doc = "Finds the values of the named fields, or paths into fields, of each result of the query against the given async database"]
+            pub async fn project_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+                fields: &[&::std::primitive::str],
+            ) -> #root::DatabaseResult<::std::vec::Vec<(
+                #root::Id,
+                ::std::collections::HashMap<::std::string::String, #root::Value>,
+            )>> {
+                #root::AsyncDatabase::project(
+                    database,
+                    self.0,
+                    ::std::iter::Iterator::collect(::std::iter::Iterator::map(
+                        fields.iter(),
+                        |x| ::std::string::ToString::to_string(x),
+                    )),
+                ).await
+            }
+
+            #[doc = "Executes query against the given async database, returning the first result"]
+            pub async fn first_async<__entity_D: #root::AsyncDatabase>(
+                self,
+                database: &__entity_D,
+            ) -> #root::DatabaseResult<::std::option::Option<#name #ty_generics>> {
+                #root::AsyncDatabaseExt::find_first_typed::<#name #ty_generics>(
+                    database,
+                    self.0,
+                ).await
+            }
         }
     })
 }
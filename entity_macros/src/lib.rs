@@ -52,6 +52,12 @@ use syn::{parse_macro_input, AttributeArgs, DeriveInput, Item};
 ///     #[ent(field)]
 ///     url: String,
 ///
+///     /// A public ent field that is searchable, meaning that the words of
+///     /// its text are indexed by the database so ents can be found and
+///     /// ranked by full-text search of the field
+///     #[ent(field(searchable))]
+///     summary: String,
+///
 ///     /// An edge out to a ContentEnt that is shallowly connected, meaning
 ///     /// that when this ent is deleted, the ent connected by this edge
 ///     /// will remove this ent if it is reversely-connected
@@ -68,6 +74,22 @@ use syn::{parse_macro_input, AttributeArgs, DeriveInput, Item};
 ///     /// nothing special when this ent is deleted
 ///     #[ent(edge(type = "ContentEnt"))]
 ///     paragraphs: Vec<Id>,
+///
+///     /// An optional edge out to a PageEnt that is nullified, meaning that
+///     /// when the ent connected by this edge is deleted, this edge will be
+///     /// emptied; use "restrict" instead to refuse deleting the connected
+///     /// ent while this ent points at it
+///     #[ent(edge(policy = "nullify", type = "PageEnt", inverse = "children"))]
+///     parent: Option<Id>,
+///
+///     /// An edge out to zero or more PageEnt that is kept in sync as the
+///     /// inverse of their parent edge, meaning that committing or removing
+///     /// this ent adds it to or takes it out of the parent edge of the ents
+///     /// connected by this edge, and the same happens here when their
+///     /// parent edge changes; the inverse must name an optional or many
+///     /// edge of the other ent
+///     #[ent(edge(type = "PageEnt", inverse = "parent"))]
+///     children: Vec<Id>,
 /// }
 ///
 /// #[derive(Clone, Ent)]
@@ -160,6 +160,45 @@ pub fn is_map_type(input: &Type) -> bool {
         .unwrap_or_default()
 }
 
+/// Returns true if given type appears to be a primitive number
+pub fn is_number_type(input: &Type) -> bool {
+    type_to_ident(input)
+        .map(|ident| {
+            matches!(
+                ident.to_string().as_str(),
+                "f32"
+                    | "f64"
+                    | "i128"
+                    | "i16"
+                    | "i32"
+                    | "i64"
+                    | "i8"
+                    | "isize"
+                    | "u128"
+                    | "u16"
+                    | "u32"
+                    | "u64"
+                    | "u8"
+                    | "usize"
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Returns true if given type appears to be a value that cannot hold other
+/// values, being a primitive number, bool, char, or string
+pub fn is_scalar_type(input: &Type) -> bool {
+    is_number_type(input)
+        || type_to_ident(input)
+            .map(|ident| {
+                matches!(
+                    ident.to_string().as_str(),
+                    "bool" | "char" | "String" | "str"
+                )
+            })
+            .unwrap_or_default()
+}
+
 /// Returns ident of a type if it is a type path
 ///
 /// * `path::to::MyType` -> Some(`MyType`)
@@ -1,5 +1,8 @@
 use derivative::Derivative;
-use entity::{Database, Ent, Id, InmemoryDatabase, TypedPredicate as P, Value, WeakDatabaseRc};
+use entity::{
+    Aggregate, AsyncDatabaseAdapter, Database, Ent, EntType, Id, InmemoryDatabase, OrderDirection,
+    TypedPredicate as P, Value, WeakDatabaseRc,
+};
 use std::convert::TryFrom;
 
 #[derive(Clone, Derivative, Ent)]
@@ -287,3 +290,194 @@ fn supports_generic_fields() {
     assert_eq!(results.len(), 1, "Unexpected total results");
     assert!(results.contains(&2));
 }
+
+#[test]
+fn produces_method_to_execute_against_async_database() {
+    let database = AsyncDatabaseAdapter::new(InmemoryDatabase::default());
+
+    database
+        .as_database()
+        .insert(Box::from(TestEnt1 {
+            id: 1,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 999,
+            other: 2,
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .as_database()
+        .insert(Box::from(TestEnt2 {
+            id: 2,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            field1: 1000,
+            field2: String::from("test"),
+            maybe_other: Some(1),
+            dups: Vec::new(),
+        }))
+        .expect("Failed to insert a test ent");
+
+    let results: Vec<Id> = futures::executor::block_on(
+        TestEntQuery::default()
+            .where_id(P::equals(2))
+            .execute_async(&database),
+    )
+    .expect("Failed to query for ents")
+    .iter()
+    .map(Ent::id)
+    .collect();
+    assert_eq!(results.len(), 1);
+    assert!(results.contains(&2));
+}
+
+#[test]
+fn produces_methods_to_order_and_window_results() {
+    let database = InmemoryDatabase::default();
+
+    for (id, field1) in [(1, 30), (3, 10)] {
+        database
+            .insert(Box::from(TestEnt1 {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                field1,
+                other: 2,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    for (id, field1) in [(2, 20), (4, 40)] {
+        database
+            .insert(Box::from(TestEnt2 {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                field1,
+                field2: String::from("test"),
+                maybe_other: None,
+                dups: vec![],
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let execute = |q: TestEntQuery| -> Vec<Id> {
+        q.execute(&database)
+            .expect("Failed to query for ents")
+            .iter()
+            .map(Ent::id)
+            .collect()
+    };
+
+    let q = TestEntQuery::default().order_by_field("field1", OrderDirection::Descending);
+    assert_eq!(execute(q), vec![4, 1, 2, 3]);
+
+    let q = TestEntQuery::default()
+        .order_by_id(OrderDirection::Ascending)
+        .offset(1)
+        .limit(2);
+    assert_eq!(execute(q), vec![2, 3]);
+}
+
+#[test]
+fn produces_methods_to_count_and_get_first_result() {
+    let database = InmemoryDatabase::default();
+
+    for (id, field1) in [(1, 30), (3, 10)] {
+        database
+            .insert(Box::from(TestEnt1 {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                field1,
+                other: 2,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    for (id, field1) in [(2, 20), (4, 40)] {
+        database
+            .insert(Box::from(TestEnt2 {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                field1,
+                field2: String::from("test"),
+                maybe_other: None,
+                dups: vec![],
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let q = TestEntQuery::default();
+    assert_eq!(q.count(&database).expect("Failed to count ents"), 4);
+
+    let q = TestEntQuery::default().order_by_field("field1", OrderDirection::Descending);
+    let ent = q.first(&database).expect("Failed to get first ent");
+    assert!(matches!(ent, Some(TestEnt::Two(x)) if x.id() == 4));
+
+    let q = TestEntQuery::default().offset(4);
+    assert_eq!(q.count(&database).expect("Failed to count ents"), 0);
+
+    let q = TestEntQuery::default().offset(4);
+    assert!(q
+        .first(&database)
+        .expect("Failed to get first ent")
+        .is_none());
+}
+
+#[test]
+fn produces_methods_to_aggregate_results() {
+    let database = InmemoryDatabase::default();
+
+    for (id, field1) in [(1, 30), (3, 10)] {
+        database
+            .insert(Box::from(TestEnt1 {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                field1,
+                other: 2,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    for (id, field1) in [(2, 20), (4, 40)] {
+        database
+            .insert(Box::from(TestEnt2 {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                field1,
+                field2: String::from("test"),
+                maybe_other: None,
+                dups: vec![],
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let sum = TestEntQuery::default()
+        .aggregate(&database, Aggregate::sum("field1"))
+        .expect("Failed to sum ents");
+    assert_eq!(sum, Value::from(100));
+
+    let groups = TestEntQuery::default()
+        .aggregate_by_type(&database, Aggregate::max("field1"))
+        .expect("Failed to aggregate by type");
+    assert_eq!(
+        groups,
+        vec![
+            (Value::from(TestEnt1::type_str()), Value::from(30)),
+            (Value::from(TestEnt2::type_str()), Value::from(40)),
+        ]
+    );
+}
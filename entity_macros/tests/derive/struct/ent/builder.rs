@@ -1,5 +1,5 @@
 use derivative::Derivative;
-use entity::{Ent, Id, Value, WeakDatabaseRc, EPHEMERAL_ID};
+use entity::{DatabaseRc, Ent, Id, InmemoryDatabase, Value, WeakDatabaseRc, EPHEMERAL_ID};
 use std::convert::TryFrom;
 
 #[test]
@@ -345,3 +345,33 @@ fn supports_generic_fields() {
         .expect("Failed to create with generic field");
     assert_eq!(ent.generic_field, 3);
 }
+
+#[test]
+fn finish_and_commit_async_saves_ent_to_connected_database() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+    }
+
+    let db = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    let ent = futures::executor::block_on(
+        TestEnt::build()
+            .database(DatabaseRc::downgrade(&db))
+            .finish_and_commit_async(),
+    )
+    .expect("Failed to build ent")
+    .expect("Failed to commit ent");
+
+    assert_ne!(ent.id, EPHEMERAL_ID);
+    assert!(db.get(ent.id).unwrap().is_some(), "Ent not committed");
+}
@@ -1049,3 +1049,208 @@ fn remove_should_delete_ent_from_database() {
         "Ent unexpectedly in database",
     );
 }
+
+#[test]
+fn remove_should_apply_deletion_policies_of_edges() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(policy = "deep", type = "TestEnt"))]
+        children: Vec<Id>,
+
+        #[ent(edge(policy = "shallow", type = "TestEnt"))]
+        friends: Vec<Id>,
+    }
+
+    let database = InmemoryDatabase::default();
+    let new_ent = |id, children, friends| TestEnt {
+        id,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        children,
+        friends,
+    };
+    for ent in [
+        new_ent(1, vec![2], vec![3]),
+        new_ent(2, vec![1], vec![]),
+        new_ent(3, vec![], vec![1, 2, 4]),
+        new_ent(4, vec![], vec![]),
+    ] {
+        database
+            .insert(Box::from(ent))
+            .expect("Failed to insert a test ent");
+    }
+
+    let removal = database
+        .remove_cascading(1)
+        .expect("Failed to remove ent")
+        .expect("Ent missing");
+    assert_eq!(removal.removed(), &[1, 2]);
+    assert_eq!(removal.updated(), &[3]);
+
+    let ent = database
+        .get_typed::<TestEnt>(3)
+        .expect("Failed to get ent")
+        .expect("Ent missing");
+    assert_eq!(ent.friends, vec![4]);
+}
+
+#[test]
+fn remove_should_apply_restrict_and_nullify_policies_of_referring_edges() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(policy = "restrict", type = "TestEnt"))]
+        owner: Option<Id>,
+
+        #[ent(edge(policy = "nullify", type = "TestEnt"))]
+        parent: Option<Id>,
+    }
+
+    let database = InmemoryDatabase::default();
+    let new_ent = |id, owner, parent| TestEnt {
+        id,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        owner,
+        parent,
+    };
+    for ent in [
+        new_ent(1, None, None),
+        new_ent(2, Some(1), None),
+        new_ent(3, None, Some(2)),
+    ] {
+        database
+            .insert(Box::from(ent))
+            .expect("Failed to insert a test ent");
+    }
+
+    let ent = database
+        .get_typed::<TestEnt>(3)
+        .expect("Failed to get ent")
+        .expect("Ent missing");
+    let definitions = ent.edge_definitions();
+    assert!(definitions[0].has_restrict_deletion_policy());
+    assert!(definitions[1].has_nullify_deletion_policy());
+
+    match database.remove(1) {
+        Err(DatabaseError::RestrictedRemoval { id, referrers }) => {
+            assert_eq!(id, 1);
+            assert_eq!(referrers, vec![2]);
+        }
+        x => panic!("Unexpected result: {:?}", x),
+    }
+    assert!(database.get(1).expect("Failed to get ent").is_some());
+
+    let removal = database
+        .remove_cascading(2)
+        .expect("Failed to remove ent")
+        .expect("Ent missing");
+    assert_eq!(removal.removed(), &[2]);
+    assert_eq!(removal.updated(), &[3]);
+
+    let ent = database
+        .get_typed::<TestEnt>(3)
+        .expect("Failed to get ent")
+        .expect("Ent missing");
+    assert_eq!(ent.parent, None);
+    assert!(database.remove(1).expect("Failed to remove ent"));
+}
+
+#[test]
+fn commit_and_remove_should_keep_inverse_edges_in_sync() {
+    #[derive(Clone, Ent)]
+    struct User {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "Group", inverse = "members"))]
+        groups: Vec<Id>,
+    }
+
+    #[derive(Clone, Ent)]
+    struct Group {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "User", inverse = "groups"))]
+        members: Vec<Id>,
+    }
+
+    let database = DatabaseRc::new(Box::new(InmemoryDatabase::default()));
+    let members = |id| {
+        database
+            .get(id)
+            .expect("Failed to get ent")
+            .expect("Ent missing")
+            .edge("members")
+            .expect("Edge missing")
+    };
+
+    let mut group = Group {
+        id: 1,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        members: vec![],
+    };
+    group.connect(DatabaseRc::downgrade(&database));
+    group.commit().expect("Failed to commit group");
+    assert_eq!(group.edge_definitions()[0].inverse(), Some("groups"));
+
+    let mut user = User {
+        id: 2,
+        database: WeakDatabaseRc::new(),
+        created: 0,
+        last_updated: 0,
+        groups: vec![1],
+    };
+    user.connect(DatabaseRc::downgrade(&database));
+    user.commit().expect("Failed to commit user");
+    assert_eq!(members(1), EdgeValue::Many(vec![2]));
+
+    assert!(user.remove().expect("Failed to remove user"));
+    assert_eq!(members(1), EdgeValue::Many(vec![]));
+}
@@ -1,4 +1,7 @@
-use entity::{Database, Ent, Id, InmemoryDatabase, TypedPredicate as P, Value, WeakDatabaseRc};
+use entity::{
+    Aggregate, AsyncDatabaseAdapter, Database, Ent, Id, InmemoryDatabase, OrderDirection,
+    Predicate, TypedPredicate as P, Value, WeakDatabaseRc,
+};
 use std::convert::TryFrom;
 
 #[test]
@@ -228,6 +231,525 @@ fn produces_method_to_filter_by_field() {
     assert!(results.contains(&1));
 }
 
+#[test]
+fn produces_method_to_search_searchable_field() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field(searchable))]
+        text: String,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, text) in [(1, "red fish"), (2, "blue fish, red fish"), (3, "green")] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                text: String::from(text),
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .where_text_matches("Blue FISH")
+        .execute(&database)
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![2, 1]);
+}
+
+#[test]
+fn produces_method_to_filter_text_field_by_fuzzy_match() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        name: String,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, name) in [(1, "Jonathan"), (2, "Jonathon"), (3, "Joanna")] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                name: String::from(name),
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let mut results: Vec<Id> = TestEntQuery::default()
+        .where_name(P::text_fuzzy_matches("Johnathan", 2))
+        .execute(&database)
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    results.sort_unstable();
+    assert_eq!(results, vec![1, 2]);
+}
+
+#[test]
+fn produces_method_to_filter_by_path_within_field() {
+    #[derive(Clone, Value)]
+    struct Address {
+        city: String,
+        zip: Option<u32>,
+    }
+
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        address: Option<Address>,
+
+        #[ent(field)]
+        tags: Vec<String>,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    database
+        .insert(Box::from(TestEnt {
+            id: 1,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            address: Some(Address {
+                city: String::from("Springfield"),
+                zip: Some(12345),
+            }),
+            tags: vec![String::from("a"), String::from("b")],
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .insert(Box::from(TestEnt {
+            id: 2,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            address: Some(Address {
+                city: String::from("Shelbyville"),
+                zip: None,
+            }),
+            tags: vec![String::from("b")],
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .insert(Box::from(TestEnt {
+            id: 3,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            address: None,
+            tags: vec![],
+        }))
+        .expect("Failed to insert a test ent");
+
+    let execute = |q: TestEntQuery| -> Vec<Id> {
+        let mut ids: Vec<Id> = q
+            .execute(&database)
+            .expect("Failed to query for ents")
+            .iter()
+            .map(Ent::id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    let q = TestEntQuery::default().where_address_at("city", Predicate::equals("Springfield"));
+    assert_eq!(execute(q), vec![1]);
+
+    let q = TestEntQuery::default().where_address_at("zip", Predicate::IsNone);
+    assert_eq!(execute(q), vec![2]);
+
+    let q = TestEntQuery::default().where_tags_at("[0]", Predicate::equals("b"));
+    assert_eq!(execute(q), vec![2]);
+
+    // The same paths can be given to the untyped field filter
+    let q = TestEntQuery::from(
+        entity::Query::default().where_field("address.city", Predicate::text_starts_with("S")),
+    );
+    assert_eq!(execute(q), vec![1, 2]);
+}
+
+#[test]
+fn produces_methods_to_order_and_window_results() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, created, value) in [(1, 400, 30), (2, 300, 10), (3, 200, 40), (4, 100, 20)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created,
+                last_updated: 0,
+                value,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let execute = |q: TestEntQuery| -> Vec<Id> {
+        q.execute(&database)
+            .expect("Failed to query for ents")
+            .iter()
+            .map(Ent::id)
+            .collect()
+    };
+
+    let q = TestEntQuery::default().order_by_value(OrderDirection::Ascending);
+    assert_eq!(execute(q), vec![2, 4, 1, 3]);
+
+    let q = TestEntQuery::default()
+        .order_by_value(OrderDirection::Descending)
+        .offset(1)
+        .limit(2);
+    assert_eq!(execute(q), vec![1, 4]);
+
+    let q = TestEntQuery::default()
+        .order_by_id(OrderDirection::Descending)
+        .limit(1);
+    assert_eq!(execute(q), vec![4]);
+
+    let q = TestEntQuery::default().order_by_created(OrderDirection::Ascending);
+    assert_eq!(execute(q), vec![4, 3, 2, 1]);
+}
+
+#[test]
+fn produces_methods_to_count_and_get_first_result() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, value) in [(1, 30), (2, 10), (3, 40), (4, 20)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                value,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let q = TestEntQuery::default().where_value(P::greater_than(15));
+    assert_eq!(q.count(&database).expect("Failed to count ents"), 3);
+
+    let q = TestEntQuery::default()
+        .where_value(P::greater_than(15))
+        .order_by_value(OrderDirection::Descending);
+    let ent = q.first(&database).expect("Failed to get first ent");
+    assert_eq!(ent.map(|ent| ent.id()), Some(3));
+
+    let q = TestEntQuery::default().where_value(P::greater_than(100));
+    assert_eq!(q.count(&database).expect("Failed to count ents"), 0);
+
+    let q = TestEntQuery::default().where_value(P::greater_than(100));
+    assert!(q
+        .first(&database)
+        .expect("Failed to get first ent")
+        .is_none());
+}
+
+#[test]
+fn produces_methods_to_aggregate_results() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        team: String,
+
+        #[ent(field)]
+        score: u32,
+
+        #[ent(field)]
+        bonus: i64,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, team, score, bonus) in [
+        (1, "red", 30, -5),
+        (2, "blue", 10, 0),
+        (3, "red", 40, 3),
+        (4, "blue", 20, 0),
+    ] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                team: String::from(team),
+                score,
+                bonus,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let sum = TestEntQuery::default()
+        .sum_score(&database)
+        .expect("Failed to sum scores");
+    assert_eq!(sum.map(|x| x.to_u64()), Some(100));
+
+    let min = TestEntQuery::default()
+        .min_score(&database)
+        .expect("Failed to find smallest score");
+    assert_eq!(min.map(|x| x.to_u64()), Some(10));
+
+    let max = TestEntQuery::default()
+        .where_team(P::equals(String::from("blue")))
+        .max_score(&database)
+        .expect("Failed to find largest score");
+    assert_eq!(max.map(|x| x.to_u64()), Some(20));
+
+    let average = TestEntQuery::default()
+        .average_score(&database)
+        .expect("Failed to average scores");
+    assert_eq!(average, Some(25.0));
+
+    let sum = TestEntQuery::default()
+        .sum_bonus(&database)
+        .expect("Failed to sum bonuses");
+    assert_eq!(sum.map(|x| x.to_i64()), Some(-2));
+
+    let sum = TestEntQuery::default()
+        .where_score(P::greater_than(100))
+        .sum_bonus(&database)
+        .expect("Failed to sum bonuses");
+    assert!(sum.is_none());
+
+    let groups = TestEntQuery::default()
+        .aggregate_by_team(&database, Aggregate::sum("score"))
+        .expect("Failed to aggregate by team");
+    assert_eq!(
+        groups,
+        vec![
+            (Value::from("blue"), Value::from(30)),
+            (Value::from("red"), Value::from(70)),
+        ]
+    );
+
+    let count = TestEntQuery::default()
+        .aggregate(&database, Aggregate::count())
+        .expect("Failed to count ents");
+    assert_eq!(count, Value::from(4usize));
+}
+
+#[test]
+fn produces_methods_to_project_fields() {
+    #[derive(Clone, Value)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        name: String,
+
+        #[ent(field)]
+        address: Option<Address>,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, name, city) in [(1, "a", Some("Springfield")), (2, "b", None)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                name: String::from(name),
+                address: city.map(|city| Address {
+                    city: String::from(city),
+                }),
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let names = TestEntQuery::default()
+        .project_name(&database)
+        .expect("Failed to project names");
+    assert_eq!(names, vec![(1, String::from("a")), (2, String::from("b"))]);
+
+    let cities = TestEntQuery::default()
+        .where_name(P::equals(String::from("a")))
+        .project_address(&database)
+        .expect("Failed to project addresses")
+        .into_iter()
+        .map(|(id, address)| (id, address.map(|x| x.city)))
+        .collect::<Vec<(Id, Option<String>)>>();
+    assert_eq!(cities, vec![(1, Some(String::from("Springfield")))]);
+
+    let values = TestEntQuery::default()
+        .order_by_name(OrderDirection::Descending)
+        .project(&database, &["name", "address.city"])
+        .expect("Failed to project fields");
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0].0, 2);
+    assert_eq!(values[0].1.get("name"), Some(&Value::from("b")));
+    assert_eq!(values[0].1.get("address.city"), None);
+    assert_eq!(values[1].0, 1);
+    assert_eq!(
+        values[1].1.get("address.city"),
+        Some(&Value::from("Springfield"))
+    );
+}
+
+#[test]
+fn produces_query_that_can_be_sent_between_threads() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = std::sync::Arc::new(InmemoryDatabase::default());
+
+    for (id, value) in [(1, 10), (2, 20)] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                value,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let q = TestEntQuery::default().where_value(P::lambda(|x| x > 15));
+    let handle = {
+        let database = std::sync::Arc::clone(&database);
+        std::thread::spawn(move || q.execute(database.as_ref()))
+    };
+
+    let results: Vec<Id> = handle
+        .join()
+        .expect("Thread panicked")
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![2]);
+}
+
 #[test]
 fn supports_generic_fields() {
     #[derive(Clone, Ent)]
@@ -339,6 +861,59 @@ fn produces_method_to_filter_by_ents_connected_by_edge() {
     assert!(results.contains(&1));
 }
 
+#[test]
+fn produces_method_to_filter_by_total_ids_of_edge() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "TestEnt"))]
+        friends: Vec<Id>,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, friends) in [(1, vec![2, 3]), (2, vec![1]), (3, vec![])] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                friends,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .where_friends_count(P::greater_than(1))
+        .execute(&database)
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![1]);
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .where_friends_count(P::equals(0))
+        .execute(&database)
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results, vec![3]);
+}
+
 #[test]
 fn produces_method_to_yield_edge_ents() {
     #[derive(Clone, Ent)]
@@ -409,3 +984,139 @@ fn produces_method_to_yield_edge_ents() {
     assert_eq!(results.len(), 1);
     assert!(results.contains(&2));
 }
+
+#[test]
+fn produces_method_to_yield_ents_reached_recursively_through_edge() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(edge(type = "TestEnt"))]
+        children: Vec<Id>,
+    }
+
+    let database = InmemoryDatabase::default();
+
+    for (id, children) in [(1, vec![2, 3]), (2, vec![4]), (3, vec![]), (4, vec![1])] {
+        database
+            .insert(Box::from(TestEnt {
+                id,
+                database: WeakDatabaseRc::new(),
+                created: 0,
+                last_updated: 0,
+                children,
+            }))
+            .expect("Failed to insert a test ent");
+    }
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .where_id(P::equals(2))
+        .query_children_recursive(None)
+        .execute(&database)
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results.len(), 4);
+
+    let results: Vec<Id> = TestEntQuery::default()
+        .where_id(P::equals(1))
+        .query_children_recursive(Some(1))
+        .execute(&database)
+        .expect("Failed to query for ents")
+        .iter()
+        .map(Ent::id)
+        .collect();
+    assert_eq!(results.len(), 2);
+    assert!(results.contains(&2));
+    assert!(results.contains(&3));
+}
+
+#[test]
+fn produces_method_to_execute_against_async_database() {
+    #[derive(Clone, Ent)]
+    struct TestEnt {
+        #[ent(id)]
+        id: Id,
+
+        #[ent(database)]
+        database: WeakDatabaseRc,
+
+        #[ent(created)]
+        created: u64,
+
+        #[ent(last_updated)]
+        last_updated: u64,
+
+        #[ent(field)]
+        value: u32,
+    }
+
+    let database = AsyncDatabaseAdapter::new(InmemoryDatabase::default());
+
+    database
+        .as_database()
+        .insert(Box::from(TestEnt {
+            id: 1,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            value: 100,
+        }))
+        .expect("Failed to insert a test ent");
+
+    database
+        .as_database()
+        .insert(Box::from(TestEnt {
+            id: 2,
+            database: WeakDatabaseRc::new(),
+            created: 0,
+            last_updated: 0,
+            value: 200,
+        }))
+        .expect("Failed to insert a test ent");
+
+    let results: Vec<Id> = futures::executor::block_on(
+        TestEntQuery::default()
+            .where_value(P::equals(100))
+            .execute_async(&database),
+    )
+    .expect("Failed to query for ents")
+    .iter()
+    .map(Ent::id)
+    .collect();
+    assert_eq!(results.len(), 1);
+    assert!(results.contains(&1));
+
+    let count = futures::executor::block_on(TestEntQuery::default().count_async(&database))
+        .expect("Failed to count ents");
+    assert_eq!(count, 2);
+
+    let ent = futures::executor::block_on(
+        TestEntQuery::default()
+            .where_value(P::equals(200))
+            .first_async(&database),
+    )
+    .expect("Failed to get first ent");
+    assert_eq!(ent.map(|ent| ent.id()), Some(2));
+
+    let values = futures::executor::block_on(
+        TestEntQuery::default()
+            .where_value(P::equals(200))
+            .project_async(&database, &["value"]),
+    )
+    .expect("Failed to project ents");
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].0, 2);
+    assert_eq!(values[0].1.get("value"), Some(&Value::from(200u32)));
+}
@@ -13,6 +13,15 @@ pub fn noop_attr(
     body
 }
 
+/// General purpose attribute macro that removes the item it is attached to
+#[proc_macro_attribute]
+pub fn remove_attr(
+    _: proc_macro::TokenStream,
+    _: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::new()
+}
+
 /// General purpose noop for derive macro
 #[proc_macro_derive(NoopDerive)]
 pub fn noop_derive(_items: proc_macro::TokenStream) -> proc_macro::TokenStream {